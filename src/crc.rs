@@ -0,0 +1,48 @@
+//! CRC checksums for the data stored in the SRAM
+
+// CRC-8/SMBUS
+pub(crate) fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if (crc & 0x80) != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// CRC-16/CCITT-FALSE
+pub(crate) fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if (crc & 0x8000) != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc8_check_value() {
+        assert_eq!(0xF4, crc8(b"123456789"));
+    }
+
+    #[test]
+    fn crc16_check_value() {
+        assert_eq!(0x29B1, crc16(b"123456789"));
+    }
+}
@@ -1,8 +1,15 @@
 //! Functions exclusive of DS3231
 
-use crate::{ic, interface::I2cInterface, BitFlags, Ds323x, CONTROL_POR_VALUE};
+use crate::{
+    ic, interface::I2cInterface, BitFlags, CheckResult, Config, Ds323x, Error, HourMode,
+    PowerProfile, ResetOptions, SelfTestReport, WeekdayNumbering, CONTROL_POR_VALUE,
+    DEFAULT_CENTURY_BASE,
+};
 use core::marker::PhantomData;
-use embedded_hal::i2c;
+use embedded_hal::{delay::DelayNs, i2c};
+
+// Power-on reset value of the status register
+const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::EN32KHZ;
 
 impl<I2C, E> Ds323x<I2cInterface<I2C>, ic::DS3231>
 where
@@ -10,17 +17,95 @@ where
 {
     /// Create a new instance of the DS3231 device.
     pub fn new_ds3231(i2c: I2C) -> Self {
-        const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::EN32KHZ;
         Ds323x {
-            iface: I2cInterface { i2c },
+            iface: I2cInterface::new(i2c),
             control: CONTROL_POR_VALUE,
             status: STATUS_POR_VALUE,
+            aging_offset: 0,
+            century_base: DEFAULT_CENTURY_BASE,
+            hour_mode: HourMode::H24,
+            weekday_numbering: WeekdayNumbering::SundayFirst,
+            weekday_auto_repair: false,
+            write_verification: false,
+            time_set_marker: None,
+            time_set: false,
             _ic: PhantomData,
+            _mode: PhantomData,
         }
     }
+}
 
+impl<I2C, D, E> Ds323x<I2cInterface<I2C, D>, ic::DS3231>
+where
+    I2C: i2c::I2c<Error = E>,
+    D: DelayNs,
+{
+    /// Apply a device configuration.
+    ///
+    /// See [applying a configuration](crate#applying-a-configuration).
+    ///
+    /// Note: The settings `output_32khz_on_battery` and
+    /// `temperature_conversion_rate` are not available on the DS3231 and are
+    /// ignored.
+    pub fn apply(&mut self, config: Config) -> Result<(), Error<E>> {
+        self.apply_config(config, BitFlags::EN32KHZ)
+    }
+
+    /// Reset the control, status, aging offset and alarm registers to their
+    /// power-on reset values.
+    ///
+    /// See [resetting to the defaults](crate#resetting-to-the-defaults).
+    ///
+    /// Note: The option `clear_sram` is ignored, since the DS3231 has no SRAM.
+    pub fn reset_to_defaults(&mut self, options: ResetOptions) -> Result<(), Error<E>> {
+        self.reset_registers(STATUS_POR_VALUE, options)
+    }
+}
+
+impl<I2C, D, MODE, E> Ds323x<I2cInterface<I2C, D>, ic::DS3231, MODE>
+where
+    I2C: i2c::I2c<Error = E>,
+    D: DelayNs,
+{
     /// Destroy driver instance, return I²C bus instance.
     pub fn destroy_ds3231(self) -> I2C {
         self.iface.i2c
     }
+
+    /// Read the device configuration.
+    ///
+    /// This also updates the values cached by the driver.
+    ///
+    /// Note: The settings `output_32khz_on_battery` and
+    /// `temperature_conversion_rate` are not available on the DS3231 and are
+    /// ignored.
+    pub fn current_config(&mut self) -> Result<Config, Error<E>> {
+        self.read_config(BitFlags::EN32KHZ)
+    }
+
+    /// Apply the configuration with the lowest current consumption when
+    /// battery-powered.
+    ///
+    /// See [preparing for battery backup](crate#preparing-for-battery-backup).
+    ///
+    /// Note: Since the DS3231 keeps the 32kHz output running on battery, the
+    /// 32kHz output is disabled.
+    pub fn prepare_for_battery_backup(&mut self) -> Result<PowerProfile, Error<E>> {
+        self.prepare_config_for_battery_backup(BitFlags::EN32KHZ)
+    }
+
+    /// Restore the settings changed by
+    /// [`prepare_for_battery_backup()`](#method.prepare_for_battery_backup).
+    pub fn restore_power_profile(&mut self, profile: PowerProfile) -> Result<(), Error<E>> {
+        self.restore_config_from_power_profile(profile, BitFlags::EN32KHZ)
+    }
+
+    /// Run the production self-test.
+    ///
+    /// See [running the self-test](crate#running-the-self-test).
+    ///
+    /// Note: The SRAM check is skipped on the DS3231.
+    pub fn self_test<DL: DelayNs>(&mut self, delay: &mut DL) -> SelfTestReport {
+        self.run_self_test(delay, |_| Ok(CheckResult::Skipped))
+    }
 }
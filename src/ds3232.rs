@@ -1,10 +1,22 @@
 //! Functions exclusive of DS3232
 
+use crate::ds323x::check_sram;
 use crate::{
-    ic, interface::I2cInterface, BitFlags, Ds323x, Error, TempConvRate, CONTROL_POR_VALUE,
+    ic, interface::I2cInterface, BitFlags, Config, Ds323x, Error, HourMode, InputField,
+    PowerProfile, Register, ResetOptions, SelfTestReport, TempConvRate, TimeSetMarker,
+    WeekdayNumbering, CONTROL_POR_VALUE, DEFAULT_CENTURY_BASE, TIME_SET_MARKER_SIZE,
 };
 use core::marker::PhantomData;
-use embedded_hal::i2c;
+use embedded_hal::{delay::DelayNs, i2c};
+
+/// Size of the battery-backed SRAM in bytes (registers 0x14 to 0xFF)
+pub(crate) const SRAM_SIZE: usize = 236;
+
+// Configurable bits of the status register
+const STATUS_CONFIG_MASK: u8 =
+    BitFlags::BB32KHZ | BitFlags::CRATE1 | BitFlags::CRATE0 | BitFlags::EN32KHZ;
+// Power-on reset value of the status register
+const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::BB32KHZ | BitFlags::EN32KHZ;
 
 impl<I2C, E> Ds323x<I2cInterface<I2C>, ic::DS3232>
 where
@@ -12,20 +24,96 @@ where
 {
     /// Create a new instance of the DS3232 device.
     pub fn new_ds3232(i2c: I2C) -> Self {
-        const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::BB32KHZ | BitFlags::EN32KHZ;
         Ds323x {
-            iface: I2cInterface { i2c },
+            iface: I2cInterface::new(i2c),
             control: CONTROL_POR_VALUE,
             status: STATUS_POR_VALUE,
+            aging_offset: 0,
+            century_base: DEFAULT_CENTURY_BASE,
+            hour_mode: HourMode::H24,
+            weekday_numbering: WeekdayNumbering::SundayFirst,
+            weekday_auto_repair: false,
+            write_verification: false,
+            time_set_marker: None,
+            time_set: false,
             _ic: PhantomData,
+            _mode: PhantomData,
+        }
+    }
+}
+
+impl<I2C, D, E> Ds323x<I2cInterface<I2C, D>, ic::DS3232>
+where
+    I2C: i2c::I2c<Error = E>,
+    D: DelayNs,
+{
+    /// Apply a device configuration.
+    ///
+    /// See [applying a configuration](crate#applying-a-configuration).
+    pub fn apply(&mut self, config: Config) -> Result<(), Error<E>> {
+        self.apply_config(config, STATUS_CONFIG_MASK)
+    }
+
+    /// Reset the control, status, aging offset and alarm registers to their
+    /// power-on reset values.
+    ///
+    /// See [resetting to the defaults](crate#resetting-to-the-defaults).
+    ///
+    /// If `clear_sram` is set in the options, the SRAM is filled with zeros,
+    /// which also erases a time-set marker stored there.
+    pub fn reset_to_defaults(&mut self, options: ResetOptions) -> Result<(), Error<E>> {
+        self.reset_registers(STATUS_POR_VALUE, options)?;
+        if options.clear_sram {
+            self.write_sram(0, &[0; SRAM_SIZE])?;
         }
+        Ok(())
     }
+}
 
+impl<I2C, D, MODE, E> Ds323x<I2cInterface<I2C, D>, ic::DS3232, MODE>
+where
+    I2C: i2c::I2c<Error = E>,
+    D: DelayNs,
+{
     /// Destroy driver instance, return I²C bus instance.
     pub fn destroy_ds3232(self) -> I2C {
         self.iface.i2c
     }
 
+    /// Read the device configuration.
+    ///
+    /// This also updates the values cached by the driver.
+    pub fn current_config(&mut self) -> Result<Config, Error<E>> {
+        self.read_config(STATUS_CONFIG_MASK)
+    }
+
+    /// Apply the configuration with the lowest current consumption when
+    /// battery-powered.
+    ///
+    /// See [preparing for battery backup](crate#preparing-for-battery-backup).
+    ///
+    /// Note: The 32kHz output is disabled when battery-powered (BB32KHZ) and
+    /// the temperature conversion rate (CRATE) is set to once every 512
+    /// seconds.
+    pub fn prepare_for_battery_backup(&mut self) -> Result<PowerProfile, Error<E>> {
+        self.prepare_config_for_battery_backup(STATUS_CONFIG_MASK)
+    }
+
+    /// Restore the settings changed by
+    /// [`prepare_for_battery_backup()`](#method.prepare_for_battery_backup).
+    pub fn restore_power_profile(&mut self, profile: PowerProfile) -> Result<(), Error<E>> {
+        self.restore_config_from_power_profile(profile, STATUS_CONFIG_MASK)
+    }
+
+    /// Run the production self-test.
+    ///
+    /// See [running the self-test](crate#running-the-self-test).
+    ///
+    /// Note: The whole SRAM is also written and read back.
+    pub fn self_test<DL: DelayNs>(&mut self, delay: &mut DL) -> SelfTestReport {
+        self.run_self_test(delay, check_sram)
+    }
+
     /// Enable the 32kHz output when battery-powered. (enabled per default)
     ///
     /// Additionally, the 32kHz output needs to be enabled. See
@@ -64,4 +152,78 @@ where
         };
         self.write_status_without_clearing_alarm(status)
     }
+
+    /// Read data from the battery-backed SRAM starting at `offset`.
+    ///
+    /// The offset is relative to the beginning of the SRAM. The SRAM has
+    /// 236 bytes, which correspond to the registers 0x14 to 0xFF.
+    /// Will return `Error::InvalidInputData` if the data does not fit in the SRAM.
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub fn read_sram(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Error<E>> {
+        if usize::from(offset) + data.len() > SRAM_SIZE {
+            return Err(Error::InvalidInputData {
+                field: InputField::SramRange,
+            });
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.iface.read_at(Register::SRAM_START + offset, data)
+    }
+
+    /// Write data to the battery-backed SRAM starting at `offset`.
+    ///
+    /// The offset is relative to the beginning of the SRAM. The SRAM has
+    /// 236 bytes, which correspond to the registers 0x14 to 0xFF.
+    /// Will return `Error::InvalidInputData` if the data does not fit in the SRAM.
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub fn write_sram(&mut self, offset: u8, data: &[u8]) -> Result<(), Error<E>> {
+        if usize::from(offset) + data.len() > SRAM_SIZE {
+            return Err(Error::InvalidInputData {
+                field: InputField::SramRange,
+            });
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.iface.write_at(Register::SRAM_START + offset, data)?;
+        if self.write_verification {
+            self.verify_registers(Register::SRAM_START + offset, data)?;
+        }
+        Ok(())
+    }
+
+    /// Store a time-set marker in the SRAM starting at `offset` each time
+    /// the date and time are set with
+    /// [`set_datetime()`](#method.set_datetime) or any of the methods
+    /// setting part of them, like [`set_date()`](#method.set_date) or
+    /// [`set_hours()`](#method.set_hours). The latter read the resulting
+    /// date and time back to write the marker.
+    ///
+    /// The marker takes 9 bytes and contains the date and time that was set
+    /// protected by a CRC-8. It allows
+    /// [`time_validity()`](#method.time_validity) to detect a device whose
+    /// date and time have never been set or have gone backwards.
+    /// The marker location is only kept in the driver.
+    /// Will return `Error::InvalidInputData` if the marker does not fit in the SRAM.
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub fn enable_time_set_marker(&mut self, offset: u8) -> Result<(), Error<E>> {
+        if usize::from(offset) + TIME_SET_MARKER_SIZE > SRAM_SIZE {
+            return Err(Error::InvalidInputData {
+                field: InputField::SramRange,
+            });
+        }
+        self.time_set_marker = Some(TimeSetMarker::Register(Register::SRAM_START + offset));
+        Ok(())
+    }
+
+    /// Stop storing the time-set marker in the SRAM. (disabled per default)
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub fn disable_time_set_marker(&mut self) {
+        self.time_set_marker = None;
+    }
 }
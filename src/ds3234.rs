@@ -1,8 +1,25 @@
 //! Functions exclusive of DS3234
-use crate::interface::{SpiInterface, WriteData};
-use crate::{ic, BitFlags, Ds323x, Error, Register, TempConvRate, CONTROL_POR_VALUE};
+use crate::ds323x::{check_sram, first_mismatch};
+use crate::interface::{ReadData, SpiInterface, WriteData};
+use crate::{
+    ic, BitFlags, Config, Ds323x, Error, HourMode, InputField, PowerProfile, Register,
+    ResetOptions, SelfTestReport, TempConvRate, TimeSetMarker, WeekdayNumbering, CONTROL_POR_VALUE,
+    DEFAULT_CENTURY_BASE, TIME_SET_MARKER_SIZE,
+};
 use core::marker::PhantomData;
-use embedded_hal::spi;
+use embedded_hal::{
+    delay::DelayNs,
+    spi::{self, Operation},
+};
+
+/// Size of the battery-backed SRAM in bytes
+pub(crate) const SRAM_SIZE: usize = 256;
+
+// Configurable bits of the status register
+const STATUS_CONFIG_MASK: u8 =
+    BitFlags::BB32KHZ | BitFlags::CRATE1 | BitFlags::CRATE0 | BitFlags::EN32KHZ;
+// Power-on reset value of the status register
+const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::BB32KHZ | BitFlags::EN32KHZ;
 
 impl<SPI, E> Ds323x<SpiInterface<SPI>, ic::DS3234>
 where
@@ -10,20 +27,103 @@ where
 {
     /// Create a new instance.
     pub fn new_ds3234(spi: SPI) -> Self {
-        const STATUS_POR_VALUE: u8 = BitFlags::OSC_STOP | BitFlags::BB32KHZ | BitFlags::EN32KHZ;
         Ds323x {
             iface: SpiInterface { spi },
             control: CONTROL_POR_VALUE,
             status: STATUS_POR_VALUE,
+            aging_offset: 0,
+            century_base: DEFAULT_CENTURY_BASE,
+            hour_mode: HourMode::H24,
+            weekday_numbering: WeekdayNumbering::SundayFirst,
+            weekday_auto_repair: false,
+            write_verification: false,
+            time_set_marker: None,
+            time_set: false,
             _ic: PhantomData,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Apply a device configuration.
+    ///
+    /// See [applying a configuration](crate#applying-a-configuration).
+    pub fn apply(&mut self, config: Config) -> Result<(), Error<E>> {
+        self.apply_config(config, STATUS_CONFIG_MASK)
+    }
+
+    /// Reset the control, status, aging offset and alarm registers to their
+    /// power-on reset values.
+    ///
+    /// See [resetting to the defaults](crate#resetting-to-the-defaults).
+    ///
+    /// Note: The temperature conversions when battery-powered (BB_TD) are
+    /// enabled again. If `clear_sram` is set in the options, the SRAM is filled with zeros,
+    /// which also erases a time-set marker stored there.
+    pub fn reset_to_defaults(&mut self, options: ResetOptions) -> Result<(), Error<E>> {
+        self.reset_registers(STATUS_POR_VALUE, options)?;
+        self.enable_temperature_conversions_on_battery()?;
+        if options.clear_sram {
+            self.write_sram(0, &[0; SRAM_SIZE])?;
         }
+        Ok(())
     }
+}
 
+impl<SPI, MODE, E> Ds323x<SpiInterface<SPI>, ic::DS3234, MODE>
+where
+    SPI: spi::SpiDevice<u8, Error = E>,
+{
     /// Destroy driver instance, return SPI bus instance and CS output pin.
     pub fn destroy_ds3234(self) -> SPI {
         self.iface.spi
     }
 
+    /// Read the device configuration.
+    ///
+    /// This also updates the values cached by the driver.
+    pub fn current_config(&mut self) -> Result<Config, Error<E>> {
+        self.read_config(STATUS_CONFIG_MASK)
+    }
+
+    /// Apply the configuration with the lowest current consumption when
+    /// battery-powered.
+    ///
+    /// See [preparing for battery backup](crate#preparing-for-battery-backup).
+    ///
+    /// Note: The 32kHz output is disabled when battery-powered (BB32KHZ), the
+    /// temperature conversion rate (CRATE) is set to once every 512 seconds
+    /// and the temperature conversions are disabled when battery-powered
+    /// (BB_TD).
+    pub fn prepare_for_battery_backup(&mut self) -> Result<PowerProfile, Error<E>> {
+        let mut profile = self.prepare_config_for_battery_backup(STATUS_CONFIG_MASK)?;
+        let temp_conv = self.iface.read_register(Register::TEMP_CONV)?;
+        if (temp_conv & BitFlags::TEMP_CONV_BAT) == 0 {
+            self.disable_temperature_conversions_on_battery()?;
+            profile.temperature_conversions_on_battery = Some(true);
+        }
+        Ok(profile)
+    }
+
+    /// Restore the settings changed by
+    /// [`prepare_for_battery_backup()`](#method.prepare_for_battery_backup).
+    pub fn restore_power_profile(&mut self, profile: PowerProfile) -> Result<(), Error<E>> {
+        self.restore_config_from_power_profile(profile, STATUS_CONFIG_MASK)?;
+        match profile.temperature_conversions_on_battery {
+            Some(true) => self.enable_temperature_conversions_on_battery(),
+            Some(false) => self.disable_temperature_conversions_on_battery(),
+            None => Ok(()),
+        }
+    }
+
+    /// Run the production self-test.
+    ///
+    /// See [running the self-test](crate#running-the-self-test).
+    ///
+    /// Note: The whole SRAM is also written and read back.
+    pub fn self_test<DL: DelayNs>(&mut self, delay: &mut DL) -> SelfTestReport {
+        self.run_self_test(delay, check_sram)
+    }
+
     /// Enable the 32kHz output when battery-powered. (enabled per default)
     ///
     /// Additionally, the 32kHz output needs to be enabled. See
@@ -67,14 +167,108 @@ where
     ///
     /// Note: This is only available for DS3234 devices.
     pub fn enable_temperature_conversions_on_battery(&mut self) -> Result<(), Error<E>> {
-        self.iface.write_register(Register::TEMP_CONV, 0)
+        self.write_register_verified(Register::TEMP_CONV, 0)
     }
 
     /// Disable the temperature conversions when battery-powered.
     ///
     /// Note: This is only available for DS3234 devices.
     pub fn disable_temperature_conversions_on_battery(&mut self) -> Result<(), Error<E>> {
+        self.write_register_verified(Register::TEMP_CONV, BitFlags::TEMP_CONV_BAT)
+    }
+
+    /// Read data from the battery-backed SRAM starting at `offset`.
+    ///
+    /// The SRAM has 256 bytes and is accessed through the SRAM address and
+    /// data registers.
+    /// Will return `Error::InvalidInputData` if the data does not fit in the SRAM.
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub fn read_sram(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Error<E>> {
+        if usize::from(offset) + data.len() > SRAM_SIZE {
+            return Err(Error::InvalidInputData {
+                field: InputField::SramRange,
+            });
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.iface.write_register(Register::SRAM_ADDRESS, offset)?;
         self.iface
-            .write_register(Register::TEMP_CONV, BitFlags::TEMP_CONV_BAT)
+            .spi
+            .transaction(&mut [
+                Operation::Write(&[Register::SRAM_DATA]),
+                Operation::Read(data),
+            ])
+            .map_err(Error::Comm)
+    }
+
+    /// Write data to the battery-backed SRAM starting at `offset`.
+    ///
+    /// The SRAM has 256 bytes and is accessed through the SRAM address and
+    /// data registers.
+    /// Will return `Error::InvalidInputData` if the data does not fit in the SRAM.
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub fn write_sram(&mut self, offset: u8, data: &[u8]) -> Result<(), Error<E>> {
+        if usize::from(offset) + data.len() > SRAM_SIZE {
+            return Err(Error::InvalidInputData {
+                field: InputField::SramRange,
+            });
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.iface.write_register(Register::SRAM_ADDRESS, offset)?;
+        self.iface
+            .spi
+            .transaction(&mut [
+                Operation::Write(&[Register::SRAM_DATA + 0x80]),
+                Operation::Write(data),
+            ])
+            .map_err(Error::Comm)?;
+        if self.write_verification {
+            let index = first_mismatch(data, |start, chunk| {
+                self.read_sram(offset + start as u8, chunk)
+            })?;
+            if let Some(index) = index {
+                return Err(Error::SramVerificationFailed {
+                    offset: offset + index as u8,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Store a time-set marker in the SRAM starting at `offset` each time
+    /// the date and time are set with
+    /// [`set_datetime()`](#method.set_datetime) or any of the methods
+    /// setting part of them, like [`set_date()`](#method.set_date) or
+    /// [`set_hours()`](#method.set_hours). The latter read the resulting
+    /// date and time back to write the marker.
+    ///
+    /// The marker takes 9 bytes and contains the date and time that was set
+    /// protected by a CRC-8. It allows
+    /// [`time_validity()`](#method.time_validity) to detect a device whose
+    /// date and time have never been set or have gone backwards.
+    /// The marker location is only kept in the driver.
+    /// Will return `Error::InvalidInputData` if the marker does not fit in the SRAM.
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub fn enable_time_set_marker(&mut self, offset: u8) -> Result<(), Error<E>> {
+        if usize::from(offset) + TIME_SET_MARKER_SIZE > SRAM_SIZE {
+            return Err(Error::InvalidInputData {
+                field: InputField::SramRange,
+            });
+        }
+        self.time_set_marker = Some(TimeSetMarker::Indirect(offset));
+        Ok(())
+    }
+
+    /// Stop storing the time-set marker in the SRAM. (disabled per default)
+    ///
+    /// Note: This is only available for DS3232 and DS3234 devices.
+    pub fn disable_time_set_marker(&mut self) {
+        self.time_set_marker = None;
     }
 }
@@ -0,0 +1,154 @@
+//! Validated serialization of the alarm parameters
+//!
+//! The alarm parameters are serialized through these mirror types. The
+//! matching strategy is passed separately to the setters, which ignore
+//! invalid values in the fields that the strategy does not use. So that
+//! every alarm accepted by a setter can be deserialized again, only the
+//! fields used by all matching strategies are validated here. The other
+//! fields are validated by the setters depending on the strategy.
+
+use super::alarms::{DayAlarm1, DayAlarm2, WeekdayAlarm1, WeekdayAlarm2};
+use crate::{Hours, Weekday};
+use core::convert::TryFrom;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Hours")]
+enum HoursDef {
+    AM(u8),
+    PM(u8),
+    H24(u8),
+}
+
+fn check_second(second: u8) -> Result<u8, &'static str> {
+    if second <= 59 {
+        Ok(second)
+    } else {
+        Err("second out of range [0-59]")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "DayAlarm1")]
+pub(crate) struct DayAlarm1Data {
+    day: u8,
+    #[serde(with = "HoursDef")]
+    hour: Hours,
+    minute: u8,
+    second: u8,
+}
+
+impl From<DayAlarm1> for DayAlarm1Data {
+    fn from(alarm: DayAlarm1) -> Self {
+        DayAlarm1Data {
+            day: alarm.day,
+            hour: alarm.hour,
+            minute: alarm.minute,
+            second: alarm.second,
+        }
+    }
+}
+
+impl TryFrom<DayAlarm1Data> for DayAlarm1 {
+    type Error = &'static str;
+
+    fn try_from(data: DayAlarm1Data) -> Result<Self, Self::Error> {
+        // the seconds are used by all matching strategies of a day alarm
+        Ok(DayAlarm1 {
+            day: data.day,
+            hour: data.hour,
+            minute: data.minute,
+            second: check_second(data.second)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "WeekdayAlarm1")]
+pub(crate) struct WeekdayAlarm1Data {
+    weekday: Weekday,
+    #[serde(with = "HoursDef")]
+    hour: Hours,
+    minute: u8,
+    second: u8,
+}
+
+impl From<WeekdayAlarm1> for WeekdayAlarm1Data {
+    fn from(alarm: WeekdayAlarm1) -> Self {
+        WeekdayAlarm1Data {
+            weekday: alarm.weekday,
+            hour: alarm.hour,
+            minute: alarm.minute,
+            second: alarm.second,
+        }
+    }
+}
+
+impl From<WeekdayAlarm1Data> for WeekdayAlarm1 {
+    fn from(data: WeekdayAlarm1Data) -> Self {
+        WeekdayAlarm1 {
+            weekday: data.weekday,
+            hour: data.hour,
+            minute: data.minute,
+            second: data.second,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "DayAlarm2")]
+pub(crate) struct DayAlarm2Data {
+    day: u8,
+    #[serde(with = "HoursDef")]
+    hour: Hours,
+    minute: u8,
+}
+
+impl From<DayAlarm2> for DayAlarm2Data {
+    fn from(alarm: DayAlarm2) -> Self {
+        DayAlarm2Data {
+            day: alarm.day,
+            hour: alarm.hour,
+            minute: alarm.minute,
+        }
+    }
+}
+
+impl From<DayAlarm2Data> for DayAlarm2 {
+    fn from(data: DayAlarm2Data) -> Self {
+        DayAlarm2 {
+            day: data.day,
+            hour: data.hour,
+            minute: data.minute,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "WeekdayAlarm2")]
+pub(crate) struct WeekdayAlarm2Data {
+    weekday: Weekday,
+    #[serde(with = "HoursDef")]
+    hour: Hours,
+    minute: u8,
+}
+
+impl From<WeekdayAlarm2> for WeekdayAlarm2Data {
+    fn from(alarm: WeekdayAlarm2) -> Self {
+        WeekdayAlarm2Data {
+            weekday: alarm.weekday,
+            hour: alarm.hour,
+            minute: alarm.minute,
+        }
+    }
+}
+
+impl From<WeekdayAlarm2Data> for WeekdayAlarm2 {
+    fn from(data: WeekdayAlarm2Data) -> Self {
+        WeekdayAlarm2 {
+            weekday: data.weekday,
+            hour: data.hour,
+            minute: data.minute,
+        }
+    }
+}
@@ -1,10 +1,12 @@
 //! Alarm support
 
-use super::{decimal_to_packed_bcd, hours_to_register};
+use super::{
+    datetime::hours_from_h24, decimal_to_packed_bcd, hours_to_register, weekday_to_register,
+};
 use crate::{
     ds323x::{NaiveTime, Timelike},
     interface::{ReadData, WriteData},
-    BitFlags, Ds323x, Error, Hours, Register,
+    BitFlags, Ds323x, Error, Hours, InputField, Register, Weekday,
 };
 
 /// Parameters for setting Alarm1 on a day of the month
@@ -16,6 +18,14 @@ use crate::{
 /// - Day: 1
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "super::alarm_serde::DayAlarm1Data",
+        into = "super::alarm_serde::DayAlarm1Data"
+    )
+)]
 pub struct DayAlarm1 {
     /// Day of the month [1-31]
     pub day: u8,
@@ -33,12 +43,22 @@ pub struct DayAlarm1 {
 /// case, invalid values are ignored and the minimum valid values are used instead to
 /// configure the alarm:
 /// - Second, minute and hour: 0
-/// - Weekday: 1
+///
+/// The weekday is written according to the configured weekday numbering.
+/// See [`set_weekday_numbering()`](crate::Ds323x::set_weekday_numbering).
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        from = "super::alarm_serde::WeekdayAlarm1Data",
+        into = "super::alarm_serde::WeekdayAlarm1Data"
+    )
+)]
 pub struct WeekdayAlarm1 {
-    /// Weekday [1-7]
-    pub weekday: u8,
+    /// Weekday
+    pub weekday: Weekday,
     /// Hour
     pub hour: Hours,
     /// Minute [0-59]
@@ -50,6 +70,7 @@ pub struct WeekdayAlarm1 {
 /// Alarm1 trigger rate
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Alarm1Matching {
     /// Alarm once per second.
     OncePerSecond,
@@ -72,6 +93,14 @@ pub enum Alarm1Matching {
 /// - Day: 1
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        from = "super::alarm_serde::DayAlarm2Data",
+        into = "super::alarm_serde::DayAlarm2Data"
+    )
+)]
 pub struct DayAlarm2 {
     /// Day of month [1-31]
     pub day: u8,
@@ -87,12 +116,22 @@ pub struct DayAlarm2 {
 /// case, invalid values are ignored and the minimum valid values are used instead to
 /// configure the alarm:
 /// - Minute and hour: 0
-/// - Weekday: 1
+///
+/// The weekday is written according to the configured weekday numbering.
+/// See [`set_weekday_numbering()`](crate::Ds323x::set_weekday_numbering).
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        from = "super::alarm_serde::WeekdayAlarm2Data",
+        into = "super::alarm_serde::WeekdayAlarm2Data"
+    )
+)]
 pub struct WeekdayAlarm2 {
-    /// Weekday [1-7]
-    pub weekday: u8,
+    /// Weekday
+    pub weekday: Weekday,
     /// Hour
     pub hour: Hours,
     /// Minute [0-59]
@@ -102,6 +141,7 @@ pub struct WeekdayAlarm2 {
 /// Alarm2 trigger rate
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Alarm2Matching {
     /// Alarm once per minute. (00 seconds of every minute)
     OncePerMinute,
@@ -156,7 +196,16 @@ fn amend_hour(hours: Hours) -> Hours {
     }
 }
 
-impl<DI, IC, E> Ds323x<DI, IC>
+// Fail with the given field if it is invalid and used by the matching strategy.
+fn check_alarm_field<E>(invalid: bool, field: InputField) -> Result<(), Error<E>> {
+    if invalid {
+        Err(Error::InvalidInputData { field })
+    } else {
+        Ok(())
+    }
+}
+
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
 where
     DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
 {
@@ -181,15 +230,23 @@ where
         let hour = amend_hour(when.hour);
         let minute = if minute_invalid { 0 } else { when.minute };
 
-        if (matching == Alarm1Matching::AllMatch && (day_invalid || hour_invalid))
-            || (hour_invalid && matching == Alarm1Matching::HoursMinutesAndSecondsMatch)
-            || ((matching != Alarm1Matching::SecondsMatch
-                && matching != Alarm1Matching::OncePerSecond)
-                && minute_invalid)
-            || second_invalid
-        {
-            return Err(Error::InvalidInputData);
-        }
+        check_alarm_field(
+            day_invalid && matching == Alarm1Matching::AllMatch,
+            InputField::Day,
+        )?;
+        check_alarm_field(
+            hour_invalid
+                && (matching == Alarm1Matching::AllMatch
+                    || matching == Alarm1Matching::HoursMinutesAndSecondsMatch),
+            InputField::Hour,
+        )?;
+        check_alarm_field(
+            minute_invalid
+                && matching != Alarm1Matching::SecondsMatch
+                && matching != Alarm1Matching::OncePerSecond,
+            InputField::Minute,
+        )?;
+        check_alarm_field(second_invalid, InputField::Second)?;
 
         let match_mask = get_matching_mask_alarm1(matching);
         let mut data = [
@@ -199,17 +256,19 @@ where
             hours_to_register(hour)? | match_mask[2],
             decimal_to_packed_bcd(day) | match_mask[3],
         ];
-        self.iface.write_data(&mut data)
+        self.write_data_verified(&mut data)
     }
 
     /// Set Alarm1 for a time (fires when hours, minutes and seconds match).
     ///
     /// Will return an `Error::InvalidInputData` if any of the parameters is out of range.
     /// The day is not used by this matching strategy and is set to 1.
+    /// The hour is written in the format configured with
+    /// [`set_hour_mode()`](#method.set_hour_mode).
     pub fn set_alarm1_hms(&mut self, when: NaiveTime) -> Result<(), Error<E>> {
         let alarm = DayAlarm1 {
             day: 1,
-            hour: Hours::H24(when.hour() as u8),
+            hour: hours_from_h24(when.hour() as u8, self.hour_mode),
             minute: when.minute() as u8,
             second: when.second() as u8,
         };
@@ -222,40 +281,45 @@ where
     /// (depending on the matching startegy) is out of range. Any unused
     /// parameter is set to the corresponding minimum valid value:
     /// - Second, minute, hour: 0
-    /// - Weekday: 1
     pub fn set_alarm1_weekday(
         &mut self,
         when: WeekdayAlarm1,
         matching: Alarm1Matching,
     ) -> Result<(), Error<E>> {
-        let weekday_invalid = when.weekday < 1 || when.weekday > 7;
         let hour_invalid = is_hour_valid(when.hour);
         let minute_invalid = when.minute > 59;
         let second_invalid = when.second > 59;
 
-        let weekday = if weekday_invalid { 1 } else { when.weekday };
+        let weekday = weekday_to_register(when.weekday, self.weekday_numbering);
         let hour = amend_hour(when.hour);
         let minute = if minute_invalid { 0 } else { when.minute };
         let second = if second_invalid { 0 } else { when.second };
 
-        if ((hour_invalid || weekday_invalid) && matching == Alarm1Matching::AllMatch)
-            || (hour_invalid && matching == Alarm1Matching::HoursMinutesAndSecondsMatch)
-            || (minute_invalid
-                && (matching != Alarm1Matching::OncePerSecond
-                    && matching != Alarm1Matching::SecondsMatch))
-            || (second_invalid && matching != Alarm1Matching::OncePerSecond)
-        {
-            return Err(Error::InvalidInputData);
-        }
+        check_alarm_field(
+            hour_invalid
+                && (matching == Alarm1Matching::AllMatch
+                    || matching == Alarm1Matching::HoursMinutesAndSecondsMatch),
+            InputField::Hour,
+        )?;
+        check_alarm_field(
+            minute_invalid
+                && matching != Alarm1Matching::OncePerSecond
+                && matching != Alarm1Matching::SecondsMatch,
+            InputField::Minute,
+        )?;
+        check_alarm_field(
+            second_invalid && matching != Alarm1Matching::OncePerSecond,
+            InputField::Second,
+        )?;
         let match_mask = get_matching_mask_alarm1(matching);
         let mut data = [
             Register::ALARM1_SECONDS,
             decimal_to_packed_bcd(second) | match_mask[0],
             decimal_to_packed_bcd(minute) | match_mask[1],
             hours_to_register(hour)? | match_mask[2],
-            decimal_to_packed_bcd(weekday) | match_mask[3] | BitFlags::WEEKDAY,
+            weekday | match_mask[3] | BitFlags::WEEKDAY,
         ];
-        self.iface.write_data(&mut data)
+        self.write_data_verified(&mut data)
     }
 
     /// Set Alarm2 for date (day of month).
@@ -278,12 +342,20 @@ where
         let hour = amend_hour(when.hour);
         let minute = if minute_invalid { 0 } else { when.minute };
 
-        if ((day_invalid || hour_invalid) && matching == Alarm2Matching::AllMatch)
-            || (hour_invalid && matching == Alarm2Matching::HoursAndMinutesMatch)
-            || (matching != Alarm2Matching::OncePerMinute && minute_invalid)
-        {
-            return Err(Error::InvalidInputData);
-        }
+        check_alarm_field(
+            day_invalid && matching == Alarm2Matching::AllMatch,
+            InputField::Day,
+        )?;
+        check_alarm_field(
+            hour_invalid
+                && (matching == Alarm2Matching::AllMatch
+                    || matching == Alarm2Matching::HoursAndMinutesMatch),
+            InputField::Hour,
+        )?;
+        check_alarm_field(
+            minute_invalid && matching != Alarm2Matching::OncePerMinute,
+            InputField::Minute,
+        )?;
 
         let match_mask = get_matching_mask_alarm2(matching);
         let mut data = [
@@ -292,17 +364,19 @@ where
             hours_to_register(hour)? | match_mask[1],
             decimal_to_packed_bcd(day) | match_mask[2],
         ];
-        self.iface.write_data(&mut data)
+        self.write_data_verified(&mut data)
     }
 
     /// Set Alarm2 for a time (fires when hours and minutes match).
     ///
     /// Will return an `Error::InvalidInputData` if any of the parameters is out of range.
     /// The day is not used by this matching strategy and is set to 1.
+    /// The hour is written in the format configured with
+    /// [`set_hour_mode()`](#method.set_hour_mode).
     pub fn set_alarm2_hm(&mut self, when: NaiveTime) -> Result<(), Error<E>> {
         let alarm = DayAlarm2 {
             day: 1,
-            hour: Hours::H24(when.hour() as u8),
+            hour: hours_from_h24(when.hour() as u8, self.hour_mode),
             minute: when.minute() as u8,
         };
         self.set_alarm2_day(alarm, Alarm2Matching::HoursAndMinutesMatch)
@@ -314,33 +388,35 @@ where
     /// (depending on the matching startegy) is out of range. Any unused
     /// parameter is set to the corresponding minimum valid value:
     /// - Minute, hour: 0
-    /// - Weekday: 1
     pub fn set_alarm2_weekday(
         &mut self,
         when: WeekdayAlarm2,
         matching: Alarm2Matching,
     ) -> Result<(), Error<E>> {
-        let weekday_invalid = when.weekday < 1 || when.weekday > 7;
         let hour_invalid = is_hour_valid(when.hour);
         let minute_invalid = when.minute > 59;
 
-        let weekday = if weekday_invalid { 1 } else { when.weekday };
+        let weekday = weekday_to_register(when.weekday, self.weekday_numbering);
         let hour = amend_hour(when.hour);
         let minute = if minute_invalid { 0 } else { when.minute };
 
-        if (matching == Alarm2Matching::AllMatch && (weekday_invalid || hour_invalid))
-            || (matching == Alarm2Matching::HoursAndMinutesMatch && hour_invalid)
-            || (minute_invalid && matching != Alarm2Matching::OncePerMinute)
-        {
-            return Err(Error::InvalidInputData);
-        }
+        check_alarm_field(
+            hour_invalid
+                && (matching == Alarm2Matching::AllMatch
+                    || matching == Alarm2Matching::HoursAndMinutesMatch),
+            InputField::Hour,
+        )?;
+        check_alarm_field(
+            minute_invalid && matching != Alarm2Matching::OncePerMinute,
+            InputField::Minute,
+        )?;
         let match_mask = get_matching_mask_alarm2(matching);
         let mut data = [
             Register::ALARM2_MINUTES,
             decimal_to_packed_bcd(minute) | match_mask[0],
             hours_to_register(hour)? | match_mask[1],
-            decimal_to_packed_bcd(weekday) | match_mask[2] | BitFlags::WEEKDAY,
+            weekday | match_mask[2] | BitFlags::WEEKDAY,
         ];
-        self.iface.write_data(&mut data)
+        self.write_data_verified(&mut data)
     }
 }
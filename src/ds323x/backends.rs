@@ -0,0 +1,180 @@
+//! Date and time in the types of other date and time crates
+//!
+//! All conversions go through the `NaiveDateTime` codec of the device
+//! registers, so the same range checks apply. The types of the other crates
+//! are converted through the seconds since the Unix epoch.
+
+use crate::{
+    interface::{ReadData, WriteData},
+    DateTime, DateTimeAccess, Ds323x, Error, Utc,
+};
+#[cfg(any(feature = "time", feature = "jiff"))]
+use crate::{InputField, Register};
+
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Read the date and time as a `DateTime<Utc>`.
+    ///
+    /// The device is assumed to keep UTC.
+    /// Will return an `Error::InvalidDeviceState` if the date and time
+    /// registers do not contain a valid date and time.
+    pub fn datetime_utc(&mut self) -> Result<DateTime<Utc>, Error<E>> {
+        Ok(self.datetime()?.and_utc())
+    }
+
+    /// Set the date and time from a `DateTime<Utc>`.
+    ///
+    /// Fractions of a second are discarded.
+    /// Will return an `Error::InvalidInputData` if the year is out of the
+    /// range of the century base.
+    pub fn set_datetime_utc(&mut self, datetime: &DateTime<Utc>) -> Result<(), Error<E>> {
+        self.set_datetime(&datetime.naive_utc())
+    }
+
+    // Read the date and time as seconds since the Unix epoch assuming UTC.
+    #[cfg(any(feature = "time", feature = "jiff"))]
+    fn unix_timestamp(&mut self) -> Result<i64, Error<E>> {
+        Ok(self.datetime_utc()?.timestamp())
+    }
+
+    // Set the date and time from seconds since the Unix epoch in UTC.
+    #[cfg(any(feature = "time", feature = "jiff"))]
+    fn set_unix_timestamp(&mut self, seconds: i64) -> Result<(), Error<E>> {
+        let datetime = DateTime::from_timestamp(seconds, 0).ok_or(Error::InvalidInputData {
+            field: InputField::Year,
+        })?;
+        self.set_datetime_utc(&datetime)
+    }
+}
+
+// A date and time read from the device that cannot be represented in the
+// other crate. Both crates are limited to the year 9999, which can be
+// exceeded with a century base of 9900 or more.
+#[cfg(any(feature = "time", feature = "jiff"))]
+fn unrepresentable<E>() -> Error<E> {
+    Error::InvalidDeviceState {
+        register: Register::YEAR,
+    }
+}
+
+#[cfg(feature = "time")]
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Read the date and time as a `time::PrimitiveDateTime`.
+    ///
+    /// Will return an `Error::InvalidDeviceState` if the date and time
+    /// registers do not contain a valid date and time or if the year is after
+    /// 9999, which the `time` crate cannot represent.
+    ///
+    /// Note: This is only available with the `time` feature.
+    pub fn primitive_datetime(&mut self) -> Result<time::PrimitiveDateTime, Error<E>> {
+        let datetime = self.offset_datetime()?;
+        Ok(time::PrimitiveDateTime::new(
+            datetime.date(),
+            datetime.time(),
+        ))
+    }
+
+    /// Set the date and time from a `time::PrimitiveDateTime`.
+    ///
+    /// Fractions of a second are discarded.
+    /// Will return an `Error::InvalidInputData` if the year is out of the
+    /// range of the century base.
+    ///
+    /// Note: This is only available with the `time` feature.
+    pub fn set_primitive_datetime(
+        &mut self,
+        datetime: &time::PrimitiveDateTime,
+    ) -> Result<(), Error<E>> {
+        self.set_unix_timestamp(datetime.assume_utc().unix_timestamp())
+    }
+
+    /// Read the date and time as a `time::OffsetDateTime` in UTC.
+    ///
+    /// The device is assumed to keep UTC.
+    /// Will return an `Error::InvalidDeviceState` if the date and time
+    /// registers do not contain a valid date and time or if the year is after
+    /// 9999, which the `time` crate cannot represent.
+    ///
+    /// Note: This is only available with the `time` feature.
+    pub fn offset_datetime(&mut self) -> Result<time::OffsetDateTime, Error<E>> {
+        let seconds = self.unix_timestamp()?;
+        time::OffsetDateTime::from_unix_timestamp(seconds).map_err(|_| unrepresentable())
+    }
+
+    /// Set the date and time from a `time::OffsetDateTime`.
+    ///
+    /// The date and time are converted to UTC before being stored.
+    /// Fractions of a second are discarded.
+    /// Will return an `Error::InvalidInputData` if the year is out of the
+    /// range of the century base.
+    ///
+    /// Note: This is only available with the `time` feature.
+    pub fn set_offset_datetime(&mut self, datetime: &time::OffsetDateTime) -> Result<(), Error<E>> {
+        self.set_unix_timestamp(datetime.unix_timestamp())
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Read the date and time as a `jiff::civil::DateTime`.
+    ///
+    /// Will return an `Error::InvalidDeviceState` if the date and time
+    /// registers do not contain a valid date and time or if the year is after
+    /// 9999, which the `jiff` crate cannot represent.
+    ///
+    /// Note: This is only available with the `jiff` feature.
+    pub fn civil_datetime(&mut self) -> Result<jiff::civil::DateTime, Error<E>> {
+        let timestamp = self.timestamp()?;
+        Ok(jiff::tz::Offset::UTC.to_datetime(timestamp))
+    }
+
+    /// Set the date and time from a `jiff::civil::DateTime`.
+    ///
+    /// Fractions of a second are discarded.
+    /// Will return an `Error::InvalidInputData` if the year is out of the
+    /// range of the century base.
+    ///
+    /// Note: This is only available with the `jiff` feature.
+    pub fn set_civil_datetime(&mut self, datetime: &jiff::civil::DateTime) -> Result<(), Error<E>> {
+        let timestamp =
+            jiff::tz::Offset::UTC
+                .to_timestamp(*datetime)
+                .map_err(|_| Error::InvalidInputData {
+                    field: InputField::Year,
+                })?;
+        self.set_timestamp(&timestamp)
+    }
+
+    /// Read the date and time as a `jiff::Timestamp`.
+    ///
+    /// The device is assumed to keep UTC.
+    /// Will return an `Error::InvalidDeviceState` if the date and time
+    /// registers do not contain a valid date and time or if the year is after
+    /// 9999, which the `jiff` crate cannot represent.
+    ///
+    /// Note: This is only available with the `jiff` feature.
+    pub fn timestamp(&mut self) -> Result<jiff::Timestamp, Error<E>> {
+        let seconds = self.unix_timestamp()?;
+        jiff::Timestamp::from_second(seconds).map_err(|_| unrepresentable())
+    }
+
+    /// Set the date and time from a `jiff::Timestamp`.
+    ///
+    /// The date and time are stored in UTC.
+    /// Fractions of a second are discarded.
+    /// Will return an `Error::InvalidInputData` if the year is out of the
+    /// range of the century base.
+    ///
+    /// Note: This is only available with the `jiff` feature.
+    pub fn set_timestamp(&mut self, timestamp: &jiff::Timestamp) -> Result<(), Error<E>> {
+        self.set_unix_timestamp(timestamp.as_second())
+    }
+}
@@ -2,10 +2,12 @@
 
 use crate::{
     interface::{ReadData, WriteData},
-    BitFlags, Ds323x, Error, Register, SqWFreq,
+    mode, BitFlags, Config, Ds323x, Error, IntSqwMode, ModeChangeError, PowerProfile, Register,
+    ResetOptions, SqWFreq, TempConvRate, CONTROL_POR_VALUE,
 };
+use core::marker::PhantomData;
 
-impl<DI, IC, E> Ds323x<DI, IC>
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
 where
     DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
 {
@@ -48,26 +50,16 @@ where
 
     /// Set the aging offset.
     pub fn set_aging_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
-        self.iface
-            .write_register(Register::AGING_OFFSET, offset as u8)
+        self.write_register_verified(Register::AGING_OFFSET, offset as u8)?;
+        self.aging_offset = offset;
+        Ok(())
     }
 
     /// Read the aging offset.
     pub fn aging_offset(&mut self) -> Result<i8, Error<E>> {
         let offset = self.iface.read_register(Register::AGING_OFFSET)?;
-        Ok(offset as i8)
-    }
-
-    /// Set the interrupt/square-wave output to be used as interrupt output.
-    pub fn use_int_sqw_output_as_interrupt(&mut self) -> Result<(), Error<E>> {
-        let control = self.control;
-        self.write_control(control | BitFlags::INTCN)
-    }
-
-    /// Set the interrupt/square-wave output to be used as square-wave output. (default)
-    pub fn use_int_sqw_output_as_square_wave(&mut self) -> Result<(), Error<E>> {
-        let control = self.control;
-        self.write_control(control & !BitFlags::INTCN)
+        self.aging_offset = offset as i8;
+        Ok(self.aging_offset)
     }
 
     /// Enable battery-backed square wave generation.
@@ -82,17 +74,237 @@ where
         self.write_control(control & !BitFlags::BBSQW)
     }
 
-    /// Set the square-wave output frequency.
-    pub fn set_square_wave_frequency(&mut self, freq: SqWFreq) -> Result<(), Error<E>> {
-        let new_control = match freq {
-            SqWFreq::_1Hz => self.control & !BitFlags::RS2 & !BitFlags::RS1,
-            SqWFreq::_1_024Hz => self.control & !BitFlags::RS2 | BitFlags::RS1,
-            SqWFreq::_4_096Hz => self.control | BitFlags::RS2 & !BitFlags::RS1,
-            SqWFreq::_8_192Hz => self.control | BitFlags::RS2 | BitFlags::RS1,
+    /// Use the INT/SQW output pin as interrupt output and track this in
+    /// the type of the driver.
+    ///
+    /// In the returned [`mode::Interrupt`] driver, the square-wave frequency
+    /// cannot be selected. In case of an error, the unchanged driver is returned.
+    pub fn into_interrupt_mode(
+        self,
+    ) -> Result<Ds323x<DI, IC, mode::Interrupt>, ModeChangeError<E, Self>> {
+        let control = self.control | BitFlags::INTCN;
+        self.into_mode(control)
+    }
+
+    /// Use the INT/SQW output pin as square-wave output and track this in
+    /// the type of the driver.
+    ///
+    /// In the returned [`mode::SquareWave`] driver, the alarm interrupts
+    /// cannot be configured. In case of an error, the unchanged driver is returned.
+    pub fn into_square_wave_mode(
+        self,
+    ) -> Result<Ds323x<DI, IC, mode::SquareWave>, ModeChangeError<E, Self>> {
+        let control = self.control & !BitFlags::INTCN;
+        self.into_mode(control)
+    }
+
+    /// Stop tracking the INT/SQW pin mode in the type of the driver.
+    ///
+    /// This does not communicate with the device.
+    pub fn into_unchecked_mode(self) -> Ds323x<DI, IC, mode::Unchecked> {
+        self.with_mode()
+    }
+
+    // Write the control, status and aging offset registers that differ from
+    // the configuration. Only the bits in `status_mask` are configurable in
+    // the status register of the device.
+    pub(crate) fn apply_config(&mut self, config: Config, status_mask: u8) -> Result<(), Error<E>> {
+        let control = control_from_config(&config);
+        if control != self.control {
+            self.write_control(control)?;
+        }
+        let status = (self.status & !status_mask) | (status_from_config(&config) & status_mask);
+        if status != self.status {
+            self.write_status_without_clearing_alarm(status)?;
+        }
+        if config.aging_offset != self.aging_offset {
+            self.set_aging_offset(config.aging_offset)?;
+        }
+        Ok(())
+    }
+
+    // Write the power-on reset values of the control, alarm, status and aging
+    // offset registers and update the cached values. The alarm registers are
+    // cleared and the alarm flags are cleared after disabling the alarm
+    // interrupts.
+    pub(crate) fn reset_registers(
+        &mut self,
+        status: u8,
+        options: ResetOptions,
+    ) -> Result<(), Error<E>> {
+        let control = if options.stop_oscillator_on_battery {
+            CONTROL_POR_VALUE | BitFlags::EOSC
+        } else {
+            CONTROL_POR_VALUE
         };
-        self.write_control(new_control)
+        self.write_control(control)?;
+        self.write_data_verified(&mut [Register::ALARM1_SECONDS, 0, 0, 0, 0, 0, 0, 0])?;
+        self.write_status(status)?;
+        self.set_aging_offset(0)
+    }
+
+    // Read the control, status and aging offset registers, update the cached
+    // values and return the configuration. Settings outside `status_mask`
+    // keep their default value.
+    pub(crate) fn read_config(&mut self, status_mask: u8) -> Result<Config, Error<E>> {
+        let mut data = [Register::CONTROL, 0, 0, 0];
+        self.iface.read_data(&mut data)?;
+        // do not keep a conversion request or the status flags
+        self.control = data[1] & !BitFlags::TEMP_CONV;
+        self.status = data[2] & !(BitFlags::BUSY | BitFlags::ALARM2F | BitFlags::ALARM1F);
+        self.aging_offset = data[3] as i8;
+
+        let control = self.control;
+        let status =
+            (self.status & status_mask) | (status_from_config(&Config::default()) & !status_mask);
+        let square_wave_frequency = match control & (BitFlags::RS2 | BitFlags::RS1) {
+            0 => SqWFreq::_1Hz,
+            BitFlags::RS1 => SqWFreq::_1_024Hz,
+            BitFlags::RS2 => SqWFreq::_4_096Hz,
+            _ => SqWFreq::_8_192Hz,
+        };
+        let temperature_conversion_rate = match status & (BitFlags::CRATE1 | BitFlags::CRATE0) {
+            0 => TempConvRate::_64s,
+            BitFlags::CRATE0 => TempConvRate::_128s,
+            BitFlags::CRATE1 => TempConvRate::_256s,
+            _ => TempConvRate::_512s,
+        };
+        Ok(Config {
+            oscillator_enabled: (control & BitFlags::EOSC) == 0,
+            int_sqw_mode: if (control & BitFlags::INTCN) != 0 {
+                IntSqwMode::Interrupt
+            } else {
+                IntSqwMode::SquareWave
+            },
+            square_wave_frequency,
+            square_wave_on_battery: (control & BitFlags::BBSQW) != 0,
+            output_32khz: (status & BitFlags::EN32KHZ) != 0,
+            output_32khz_on_battery: (status & BitFlags::BB32KHZ) != 0,
+            alarm1_interrupts: (control & BitFlags::ALARM1_INT_EN) != 0,
+            alarm2_interrupts: (control & BitFlags::ALARM2_INT_EN) != 0,
+            temperature_conversion_rate,
+            aging_offset: self.aging_offset,
+        })
+    }
+
+    // Change the settings affecting the battery current to the lowest-power
+    // values available with the configurable bits in `status_mask` and
+    // return the previous values of the changed settings.
+    pub(crate) fn prepare_config_for_battery_backup(
+        &mut self,
+        status_mask: u8,
+    ) -> Result<PowerProfile, Error<E>> {
+        let config = self.read_config(status_mask)?;
+        let mut new_config = config;
+        let mut profile = PowerProfile::default();
+        // the interrupt output also needs BBSQW to work on battery
+        let alarm_interrupts = config.int_sqw_mode == IntSqwMode::Interrupt
+            && (config.alarm1_interrupts || config.alarm2_interrupts);
+        if config.square_wave_on_battery && !alarm_interrupts {
+            new_config.square_wave_on_battery = false;
+            profile.square_wave_on_battery = Some(true);
+        }
+        if (status_mask & BitFlags::BB32KHZ) != 0 {
+            if config.output_32khz_on_battery {
+                new_config.output_32khz_on_battery = false;
+                profile.output_32khz_on_battery = Some(true);
+            }
+        } else if config.output_32khz {
+            new_config.output_32khz = false;
+            profile.output_32khz = Some(true);
+        }
+        if (status_mask & BitFlags::CRATE1) != 0
+            && config.temperature_conversion_rate != TempConvRate::_512s
+        {
+            new_config.temperature_conversion_rate = TempConvRate::_512s;
+            profile.temperature_conversion_rate = Some(config.temperature_conversion_rate);
+        }
+        self.apply_config(new_config, status_mask)?;
+        Ok(profile)
+    }
+
+    // Restore the settings changed by `prepare_config_for_battery_backup()`.
+    pub(crate) fn restore_config_from_power_profile(
+        &mut self,
+        profile: PowerProfile,
+        status_mask: u8,
+    ) -> Result<(), Error<E>> {
+        let mut config = self.read_config(status_mask)?;
+        if let Some(enabled) = profile.square_wave_on_battery {
+            config.square_wave_on_battery = enabled;
+        }
+        if let Some(enabled) = profile.output_32khz {
+            config.output_32khz = enabled;
+        }
+        if let Some(enabled) = profile.output_32khz_on_battery {
+            config.output_32khz_on_battery = enabled;
+        }
+        if let Some(rate) = profile.temperature_conversion_rate {
+            config.temperature_conversion_rate = rate;
+        }
+        self.apply_config(config, status_mask)
+    }
+
+    fn into_mode<M>(mut self, control: u8) -> Result<Ds323x<DI, IC, M>, ModeChangeError<E, Self>> {
+        match self.write_control(control) {
+            Ok(()) => Ok(self.with_mode()),
+            Err(error) => Err(ModeChangeError { error, dev: self }),
+        }
     }
 
+    fn with_mode<M>(self) -> Ds323x<DI, IC, M> {
+        Ds323x {
+            iface: self.iface,
+            control: self.control,
+            status: self.status,
+            aging_offset: self.aging_offset,
+            century_base: self.century_base,
+            hour_mode: self.hour_mode,
+            weekday_numbering: self.weekday_numbering,
+            weekday_auto_repair: self.weekday_auto_repair,
+            write_verification: self.write_verification,
+            time_set_marker: self.time_set_marker,
+            time_set: self.time_set,
+            _ic: PhantomData,
+            _mode: PhantomData,
+        }
+    }
+
+    pub(crate) fn write_status_without_clearing_alarm(
+        &mut self,
+        status: u8,
+    ) -> Result<(), Error<E>> {
+        // avoid clearing alarm flags
+        self.write_status(status | BitFlags::ALARM2F | BitFlags::ALARM1F)
+    }
+}
+
+impl<DI, IC, E> Ds323x<DI, IC, mode::Unchecked>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Set the interrupt/square-wave output to be used as interrupt output.
+    ///
+    /// See also: [`into_interrupt_mode()`](#method.into_interrupt_mode)
+    pub fn use_int_sqw_output_as_interrupt(&mut self) -> Result<(), Error<E>> {
+        let control = self.control;
+        self.write_control(control | BitFlags::INTCN)
+    }
+
+    /// Set the interrupt/square-wave output to be used as square-wave output. (default)
+    ///
+    /// See also: [`into_square_wave_mode()`](#method.into_square_wave_mode)
+    pub fn use_int_sqw_output_as_square_wave(&mut self) -> Result<(), Error<E>> {
+        let control = self.control;
+        self.write_control(control & !BitFlags::INTCN)
+    }
+}
+
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+    MODE: mode::AlarmInterruptMode,
+{
     /// Enable Alarm1 interrupts.
     pub fn enable_alarm1_interrupts(&mut self) -> Result<(), Error<E>> {
         let control = self.control;
@@ -116,21 +328,62 @@ where
         let control = self.control;
         self.write_control(control & !BitFlags::ALARM2_INT_EN)
     }
+}
 
-    fn write_control(&mut self, control: u8) -> Result<(), Error<E>> {
-        self.iface.write_register(Register::CONTROL, control)?;
-        self.control = control;
-        Ok(())
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+    MODE: mode::SquareWaveMode,
+{
+    /// Set the square-wave output frequency.
+    pub fn set_square_wave_frequency(&mut self, freq: SqWFreq) -> Result<(), Error<E>> {
+        let new_control = match freq {
+            SqWFreq::_1Hz => self.control & !BitFlags::RS2 & !BitFlags::RS1,
+            SqWFreq::_1_024Hz => self.control & !BitFlags::RS2 | BitFlags::RS1,
+            SqWFreq::_4_096Hz => self.control | BitFlags::RS2 & !BitFlags::RS1,
+            SqWFreq::_8_192Hz => self.control | BitFlags::RS2 | BitFlags::RS1,
+        };
+        self.write_control(new_control)
     }
+}
 
-    pub(crate) fn write_status_without_clearing_alarm(
-        &mut self,
-        status: u8,
-    ) -> Result<(), Error<E>> {
-        // avoid clearing alarm flags
-        let new_status = status | BitFlags::ALARM2F | BitFlags::ALARM1F;
-        self.iface.write_register(Register::STATUS, new_status)?;
-        self.status = status;
-        Ok(())
+fn control_from_config(config: &Config) -> u8 {
+    let mut control = match config.square_wave_frequency {
+        SqWFreq::_1Hz => 0,
+        SqWFreq::_1_024Hz => BitFlags::RS1,
+        SqWFreq::_4_096Hz => BitFlags::RS2,
+        SqWFreq::_8_192Hz => BitFlags::RS2 | BitFlags::RS1,
+    };
+    if !config.oscillator_enabled {
+        control |= BitFlags::EOSC;
+    }
+    if config.square_wave_on_battery {
+        control |= BitFlags::BBSQW;
+    }
+    if config.int_sqw_mode == IntSqwMode::Interrupt {
+        control |= BitFlags::INTCN;
+    }
+    if config.alarm2_interrupts {
+        control |= BitFlags::ALARM2_INT_EN;
+    }
+    if config.alarm1_interrupts {
+        control |= BitFlags::ALARM1_INT_EN;
+    }
+    control
+}
+
+fn status_from_config(config: &Config) -> u8 {
+    let mut status = match config.temperature_conversion_rate {
+        TempConvRate::_64s => 0,
+        TempConvRate::_128s => BitFlags::CRATE0,
+        TempConvRate::_256s => BitFlags::CRATE1,
+        TempConvRate::_512s => BitFlags::CRATE1 | BitFlags::CRATE0,
+    };
+    if config.output_32khz_on_battery {
+        status |= BitFlags::BB32KHZ;
+    }
+    if config.output_32khz {
+        status |= BitFlags::EN32KHZ;
     }
+    status
 }
@@ -1,15 +1,17 @@
 //! Common implementation
 
 use super::{
-    decimal_to_packed_bcd, hours_to_register, packed_bcd_to_decimal, some_or_invalid_error,
+    checked_packed_bcd_to_decimal, decimal_to_packed_bcd, hours_to_register, some_or_invalid_error,
+    weekday_from_register, weekday_to_register,
 };
 use crate::{
     interface::{ReadData, WriteData},
-    BitFlags, DateTimeAccess, Datelike, Ds323x, Error, Hours, NaiveDate, NaiveDateTime, NaiveTime,
-    Register, Rtcc, Timelike,
+    BitFlags, DateTimeAccess, Datelike, Ds323x, Error, HourMode, Hours, InputField, NaiveDate,
+    NaiveDateTime, NaiveTime, Register, Rtcc, Timelike, Weekday, WeekdayNumbering,
 };
+use core::ops::RangeInclusive;
 
-impl<DI, IC, E> DateTimeAccess for Ds323x<DI, IC>
+impl<DI, IC, MODE, E> DateTimeAccess for Ds323x<DI, IC, MODE>
 where
     DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
 {
@@ -18,89 +20,93 @@ where
     fn datetime(&mut self) -> Result<NaiveDateTime, Self::Error> {
         let mut data = [0; 8];
         self.iface.read_data(&mut data)?;
-
-        let year = year_from_registers(
-            data[Register::MONTH as usize + 1],
-            data[Register::YEAR as usize + 1],
-        );
-        let month = packed_bcd_to_decimal(data[Register::MONTH as usize + 1] & !BitFlags::CENTURY);
-        let day = packed_bcd_to_decimal(data[Register::DOM as usize + 1]);
-        let hour = hours_from_register(data[Register::HOURS as usize + 1]);
-        let minute = packed_bcd_to_decimal(data[Register::MINUTES as usize + 1]);
-        let second = packed_bcd_to_decimal(data[Register::SECONDS as usize + 1]);
-
-        let date = NaiveDate::from_ymd_opt(year.into(), month.into(), day.into());
-        let date = some_or_invalid_error(date)?;
-        let datetime = date.and_hms_opt(get_h24(hour).into(), minute.into(), second.into());
-        some_or_invalid_error(datetime)
+        self.datetime_from_registers(&data)
     }
 
     fn set_datetime(&mut self, datetime: &NaiveDateTime) -> Result<(), Self::Error> {
-        if datetime.year() < 2000 || datetime.year() > 2100 {
-            return Err(Error::InvalidInputData);
-        }
-        let (month, year) = month_year_to_registers(datetime.month() as u8, datetime.year() as u16);
+        self.check_year(datetime.year())?;
+        let (month, year) = month_year_to_registers(
+            datetime.month() as u8,
+            datetime.year() as u16,
+            self.century_base,
+        );
         let mut payload = [
             Register::SECONDS,
             decimal_to_packed_bcd(datetime.second() as u8),
             decimal_to_packed_bcd(datetime.minute() as u8),
-            hours_to_register(Hours::H24(datetime.hour() as u8))?,
-            datetime.weekday().number_from_sunday() as u8,
+            hours_to_register(hours_from_h24(datetime.hour() as u8, self.hour_mode))?,
+            weekday_to_register(datetime.weekday(), self.weekday_numbering),
             decimal_to_packed_bcd(datetime.day() as u8),
             month,
             year,
         ];
-        self.iface.write_data(&mut payload)
+        self.iface.write_data(&mut payload)?;
+        self.write_time_set_marker(datetime)?;
+        self.time_set = true;
+        Ok(())
+    }
+}
+
+impl<DI, IC, MODE, E> DateTimeAccess for &mut Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    type Error = Error<E>;
+
+    fn datetime(&mut self) -> Result<NaiveDateTime, Self::Error> {
+        (**self).datetime()
+    }
+
+    fn set_datetime(&mut self, datetime: &NaiveDateTime) -> Result<(), Self::Error> {
+        (**self).set_datetime(datetime)
     }
 }
 
-impl<DI, IC, E> Rtcc for Ds323x<DI, IC>
+impl<DI, IC, MODE, E> Rtcc for Ds323x<DI, IC, MODE>
 where
     DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
 {
     fn seconds(&mut self) -> Result<u8, Self::Error> {
-        self.read_register_decimal(Register::SECONDS)
+        self.read_register_decimal(Register::SECONDS, 0..=59)
     }
 
     fn minutes(&mut self) -> Result<u8, Self::Error> {
-        self.read_register_decimal(Register::MINUTES)
+        self.read_register_decimal(Register::MINUTES, 0..=59)
     }
 
     fn hours(&mut self) -> Result<Hours, Self::Error> {
         let data = self.iface.read_register(Register::HOURS)?;
-        Ok(hours_from_register(data))
+        some_or_invalid_error(hours_from_register(data), Register::HOURS)
     }
 
     fn time(&mut self) -> Result<NaiveTime, Self::Error> {
         let mut data = [0; 4];
         self.iface.read_data(&mut data)?;
-        let hour = hours_from_register(data[Register::HOURS as usize + 1]);
-        let minute = packed_bcd_to_decimal(data[Register::MINUTES as usize + 1]);
-        let second = packed_bcd_to_decimal(data[Register::SECONDS as usize + 1]);
-
-        let time = NaiveTime::from_hms_opt(get_h24(hour).into(), minute.into(), second.into());
-        some_or_invalid_error(time)
+        time_from_registers(
+            data[Register::SECONDS as usize + 1],
+            data[Register::MINUTES as usize + 1],
+            data[Register::HOURS as usize + 1],
+        )
     }
 
     fn weekday(&mut self) -> Result<u8, Self::Error> {
-        self.read_register_decimal(Register::DOW)
+        self.read_register_decimal(Register::DOW, 1..=7)
     }
 
     fn day(&mut self) -> Result<u8, Self::Error> {
-        self.read_register_decimal(Register::DOM)
+        self.read_register_decimal(Register::DOM, 1..=31)
     }
 
     fn month(&mut self) -> Result<u8, Self::Error> {
         let data = self.iface.read_register(Register::MONTH)?;
-        let value = data & !BitFlags::CENTURY;
-        Ok(packed_bcd_to_decimal(value))
+        month_from_register(data)
     }
 
     fn year(&mut self) -> Result<u16, Self::Error> {
         let mut data = [0; 3];
         data[0] = Register::MONTH;
         self.iface.read_data(&mut data)?;
-        Ok(year_from_registers(data[1], data[2]))
+        year_from_registers(data[1], data[2], self.century_base)
     }
 
     fn date(&mut self) -> Result<NaiveDate, Self::Error> {
@@ -109,34 +115,38 @@ where
         self.iface.read_data(&mut data)?;
 
         let offset = Register::DOM as usize;
-        let year = year_from_registers(
+        self.date_from_registers(
+            data[Register::DOM as usize + 1 - offset],
             data[Register::MONTH as usize + 1 - offset],
             data[Register::YEAR as usize + 1 - offset],
-        );
-        let month =
-            packed_bcd_to_decimal(data[Register::MONTH as usize + 1 - offset] & !BitFlags::CENTURY);
-        let day = packed_bcd_to_decimal(data[Register::DOM as usize + 1 - offset]);
-        let date = NaiveDate::from_ymd_opt(year.into(), month.into(), day.into());
-        some_or_invalid_error(date)
+        )
     }
 
     fn set_seconds(&mut self, seconds: u8) -> Result<(), Self::Error> {
         if seconds > 59 {
-            return Err(Error::InvalidInputData);
+            return Err(Error::InvalidInputData {
+                field: InputField::Second,
+            });
         }
-        self.write_register_decimal(Register::SECONDS, seconds)
+        self.write_register_decimal(Register::SECONDS, seconds)?;
+        self.refresh_time_set_marker()
     }
 
     fn set_minutes(&mut self, minutes: u8) -> Result<(), Self::Error> {
         if minutes > 59 {
-            return Err(Error::InvalidInputData);
+            return Err(Error::InvalidInputData {
+                field: InputField::Minute,
+            });
         }
-        self.write_register_decimal(Register::MINUTES, minutes)
+        self.write_register_decimal(Register::MINUTES, minutes)?;
+        self.refresh_time_set_marker()
     }
 
     fn set_hours(&mut self, hours: Hours) -> Result<(), Self::Error> {
         let value = hours_to_register(hours)?;
-        self.iface.write_register(Register::HOURS, value)
+        self.iface.write_register(Register::HOURS, value)?;
+        self.hour_mode = hour_mode_from_register(value);
+        self.refresh_time_set_marker()
     }
 
     fn set_time(&mut self, time: &NaiveTime) -> Result<(), Self::Error> {
@@ -144,81 +154,367 @@ where
             Register::SECONDS,
             decimal_to_packed_bcd(time.second() as u8),
             decimal_to_packed_bcd(time.minute() as u8),
-            hours_to_register(Hours::H24(time.hour() as u8))?,
+            hours_to_register(hours_from_h24(time.hour() as u8, self.hour_mode))?,
         ];
-        self.iface.write_data(&mut payload)
+        self.iface.write_data(&mut payload)?;
+        self.refresh_time_set_marker()
     }
 
     fn set_weekday(&mut self, weekday: u8) -> Result<(), Self::Error> {
         if !(1..=7).contains(&weekday) {
-            return Err(Error::InvalidInputData);
+            return Err(Error::InvalidInputData {
+                field: InputField::Weekday,
+            });
         }
         self.iface.write_register(Register::DOW, weekday)
     }
 
     fn set_day(&mut self, day: u8) -> Result<(), Self::Error> {
         if !(1..=31).contains(&day) {
-            return Err(Error::InvalidInputData);
+            return Err(Error::InvalidInputData {
+                field: InputField::Day,
+            });
         }
-        self.write_register_decimal(Register::DOM, day)
+        self.write_register_decimal(Register::DOM, day)?;
+        self.auto_repair_weekday()?;
+        self.refresh_time_set_marker()
     }
 
     fn set_month(&mut self, month: u8) -> Result<(), Self::Error> {
         if !(1..=12).contains(&month) {
-            return Err(Error::InvalidInputData);
+            return Err(Error::InvalidInputData {
+                field: InputField::Month,
+            });
         }
         // keep the century bit
         let data = self.iface.read_register(Register::MONTH)?;
         let value = (data & BitFlags::CENTURY) | decimal_to_packed_bcd(month);
-        self.iface.write_register(Register::MONTH, value)
+        self.iface.write_register(Register::MONTH, value)?;
+        self.auto_repair_weekday()?;
+        self.refresh_time_set_marker()
     }
 
     fn set_year(&mut self, year: u16) -> Result<(), Self::Error> {
-        if !(2000..=2100).contains(&year) {
-            return Err(Error::InvalidInputData);
-        }
+        self.check_year(year.into())?;
         let data = self.iface.read_register(Register::MONTH)?;
-        let month_bcd = data & !BitFlags::CENTURY;
-        if year > 2099 {
-            let mut data = [
-                Register::MONTH,
-                BitFlags::CENTURY | month_bcd,
-                decimal_to_packed_bcd((year - 2100) as u8),
-            ];
-            self.iface.write_data(&mut data)
-        } else {
-            let mut data = [
-                Register::MONTH,
-                month_bcd,
-                decimal_to_packed_bcd((year - 2000) as u8),
-            ];
-            self.iface.write_data(&mut data)
-        }
+        let month = month_from_register(data)?;
+        let (month, year) = month_year_to_registers(month, year, self.century_base);
+        let mut data = [Register::MONTH, month, year];
+        self.iface.write_data(&mut data)?;
+        self.auto_repair_weekday()?;
+        self.refresh_time_set_marker()
     }
 
     fn set_date(&mut self, date: &rtcc::NaiveDate) -> Result<(), Self::Error> {
-        if date.year() < 2000 || date.year() > 2100 {
-            return Err(Error::InvalidInputData);
-        }
-        let (month, year) = month_year_to_registers(date.month() as u8, date.year() as u16);
+        self.check_year(date.year())?;
+        let (month, year) =
+            month_year_to_registers(date.month() as u8, date.year() as u16, self.century_base);
         let mut payload = [
             Register::DOW,
-            date.weekday().number_from_sunday() as u8,
+            weekday_to_register(date.weekday(), self.weekday_numbering),
             decimal_to_packed_bcd(date.day() as u8),
             month,
             year,
         ];
-        self.iface.write_data(&mut payload)
+        self.iface.write_data(&mut payload)?;
+        self.refresh_time_set_marker()
     }
 }
 
-impl<DI, IC, E> Ds323x<DI, IC>
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
 where
     DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
 {
-    fn read_register_decimal(&mut self, register: u8) -> Result<u8, Error<E>> {
+    /// Set the first year of the range of years that can be stored in the device.
+    ///
+    /// The device only stores the last two digits of the year plus a century
+    /// bit, so it can represent 200 consecutive years. The driver maps these
+    /// to the range `[century_base, century_base + 199]`. The default is 2000,
+    /// which results in the range 2000-2199.
+    ///
+    /// The base must be a multiple of 100 so that the year register always
+    /// holds the last two digits of the year. Otherwise, or if the resulting
+    /// range would not fit in an `u16`, `Error::InvalidInputData` is returned.
+    ///
+    /// This setting is only kept in the driver. The date registers of the
+    /// device are not modified, so the base must be set to the same value
+    /// each time the driver is created.
+    ///
+    /// Note: The device considers every year whose last two digits are
+    /// divisible by four a leap year. This is wrong for years that are a
+    /// multiple of 100 but not of 400 (e.g. 2100), for which the device will
+    /// count a 29th of February. See [`correct_leap_day()`](#method.correct_leap_day).
+    pub fn set_century_base(&mut self, century_base: u16) -> Result<(), Error<E>> {
+        if century_base % 100 != 0 || century_base > u16::MAX - 199 {
+            return Err(Error::InvalidInputData {
+                field: InputField::CenturyBase,
+            });
+        }
+        self.century_base = century_base;
+        Ok(())
+    }
+
+    /// Get the first year of the range of years that can be stored in the device.
+    ///
+    /// See [`set_century_base()`](#method.set_century_base).
+    pub fn century_base(&self) -> u16 {
+        self.century_base
+    }
+
+    /// Set the hour format used by the device.
+    ///
+    /// This converts the current time as well as the hour of the alarms
+    /// 1 and 2 to the new format. Afterwards, the time set with
+    /// [`set_datetime()`](#method.set_datetime), [`set_time()`](#method.set_time),
+    /// [`set_alarm1_hms()`](#method.set_alarm1_hms) and
+    /// [`set_alarm2_hm()`](#method.set_alarm2_hm) is written in this format.
+    ///
+    /// Alarm hour values that are not valid are left unchanged.
+    pub fn set_hour_mode(&mut self, mode: HourMode) -> Result<(), Error<E>> {
+        self.convert_time_hours(mode)?;
+        for register in [Register::ALARM1_HOURS, Register::ALARM2_HOURS] {
+            let data = self.iface.read_register(register)?;
+            let converted = convert_hours_register(data, mode);
+            if converted != data {
+                self.write_register_verified(register, converted)?;
+            }
+        }
+        self.hour_mode = mode;
+        Ok(())
+    }
+
+    // Convert the hours of the current time to the given format. In the last
+    // second of an hour, the hours can roll over between reading and writing
+    // them. If the minutes have rolled over afterwards, the following hour
+    // is written, which is correct whether the rollover happened before or
+    // after the write.
+    fn convert_time_hours(&mut self, mode: HourMode) -> Result<(), Error<E>> {
+        let mut time = [Register::SECONDS, 0, 0, 0];
+        self.iface.read_data(&mut time)?;
+        let converted = convert_hours_register(time[3], mode);
+        if converted == time[3] {
+            return Ok(());
+        }
+        self.iface.write_register(Register::HOURS, converted)?;
+        if time[1] == 0x59 && time[2] == 0x59 {
+            let mut now = [Register::SECONDS, 0, 0, 0];
+            self.iface.read_data(&mut now)?;
+            if now[2] != 0x59 {
+                let next = next_hours_register(converted);
+                self.iface.write_register(Register::HOURS, next)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the hour format currently used by the device.
+    ///
+    /// Until this or [`set_hour_mode()`](#method.set_hour_mode) is called,
+    /// the driver assumes the device uses the 24-hour format.
+    pub fn hour_mode(&mut self) -> Result<HourMode, Error<E>> {
+        let data = self.iface.read_register(Register::HOURS)?;
+        self.hour_mode = hour_mode_from_register(data);
+        Ok(self.hour_mode)
+    }
+
+    /// Set the numbering of the days of the week used in the day-of-week register.
+    ///
+    /// This is used by [`set_datetime()`](#method.set_datetime),
+    /// [`set_date()`](#method.set_date), [`day_of_week()`](#method.day_of_week),
+    /// [`set_day_of_week()`](#method.set_day_of_week) and the weekday alarms.
+    /// The default is [`WeekdayNumbering::SundayFirst`].
+    ///
+    /// This setting is only kept in the driver. The day-of-week register of
+    /// the device is not modified.
+    pub fn set_weekday_numbering(&mut self, numbering: WeekdayNumbering) {
+        self.weekday_numbering = numbering;
+    }
+
+    /// Get the numbering of the days of the week used in the day-of-week register.
+    pub fn weekday_numbering(&self) -> WeekdayNumbering {
+        self.weekday_numbering
+    }
+
+    /// Read the day of the week.
+    ///
+    /// The value of the day-of-week register is interpreted according to the
+    /// configured [`WeekdayNumbering`].
+    /// Will return an `Error::InvalidDeviceState` if the register does not
+    /// contain a value in the range [1-7].
+    pub fn day_of_week(&mut self) -> Result<Weekday, Error<E>> {
+        let data = self.iface.read_register(Register::DOW)?;
+        some_or_invalid_error(
+            weekday_from_register(data, self.weekday_numbering),
+            Register::DOW,
+        )
+    }
+
+    /// Set the day of the week.
+    ///
+    /// The value written to the day-of-week register depends on the
+    /// configured [`WeekdayNumbering`].
+    pub fn set_day_of_week(&mut self, weekday: Weekday) -> Result<(), Error<E>> {
+        let value = weekday_to_register(weekday, self.weekday_numbering);
+        self.iface.write_register(Register::DOW, value)
+    }
+
+    /// Check whether the day-of-week register matches the date.
+    ///
+    /// The day-of-week register is independent of the date registers, so
+    /// setting the day, month or year individually can leave it with a
+    /// value that does not correspond to the date, which makes weekday
+    /// alarms match on the wrong days.
+    ///
+    /// Returns `true` if the day-of-week register matches the date according
+    /// to the configured [`WeekdayNumbering`].
+    /// Will return an `Error::InvalidDeviceState` if the date is not valid.
+    ///
+    /// See also: [`repair_weekday()`](#method.repair_weekday)
+    pub fn verify_weekday(&mut self) -> Result<bool, Error<E>> {
+        let (stored, expected) = self.read_weekday_and_expected()?;
+        Ok(stored == expected)
+    }
+
+    /// Rewrite the day-of-week register so that it matches the date.
+    ///
+    /// Returns `true` if the register had to be rewritten.
+    /// Will return an `Error::InvalidDeviceState` if the date is not valid.
+    ///
+    /// See also: [`verify_weekday()`](#method.verify_weekday)
+    pub fn repair_weekday(&mut self) -> Result<bool, Error<E>> {
+        let (stored, expected) = self.read_weekday_and_expected()?;
+        if stored == expected {
+            return Ok(false);
+        }
+        self.iface.write_register(Register::DOW, expected)?;
+        Ok(true)
+    }
+
+    /// Keep the day-of-week register consistent when setting the day,
+    /// month or year individually.
+    ///
+    /// When enabled, [`set_day()`](#method.set_day), [`set_month()`](#method.set_month)
+    /// and [`set_year()`](#method.set_year) additionally read the date and
+    /// rewrite the day-of-week register if necessary. If the date is not
+    /// valid after setting the value (e.g. while changing from the 31st of
+    /// January to the 28th of February), the register is left unchanged.
+    pub fn enable_weekday_auto_repair(&mut self) {
+        self.weekday_auto_repair = true;
+    }
+
+    /// Do not modify the day-of-week register when setting the day, month or
+    /// year individually. (default)
+    pub fn disable_weekday_auto_repair(&mut self) {
+        self.weekday_auto_repair = false;
+    }
+
+    /// Correct the date if the device is on a 29th of February that does
+    /// not exist.
+    ///
+    /// The device considers every year whose last two digits are divisible
+    /// by four a leap year, so in years that are a multiple of 100 but not
+    /// of 400 (e.g. 2100) it counts a 29th of February. While the device is
+    /// on this day, [`datetime()`](#method.datetime) and [`date()`](#method.date)
+    /// already return the 1st of March, and this method writes the 1st of
+    /// March to the day and month registers.
+    ///
+    /// Returns `true` if the date was corrected.
+    ///
+    /// Note: The driver cannot detect that the device has passed the
+    /// non-existent day, so if this method is not called during that day,
+    /// the date of the device stays one day behind afterwards. Call it at
+    /// least once a day, for example when handling a daily alarm, or set
+    /// the date again after the 28th of February of such a year.
+    pub fn correct_leap_day(&mut self) -> Result<bool, Error<E>> {
+        let mut data = [0; 4];
+        data[0] = Register::DOM;
+        self.iface.read_data(&mut data)?;
+        let (day, month_register, year) = (data[1], data[2], data[3]);
+        let year = year_from_registers(month_register, year, self.century_base)?;
+        let month = month_from_register(month_register)?;
+        let day = decimal_from_register(Register::DOM, day, 1..=31)?;
+        if compensate_leap_day(year, month, day) == (month, day) {
+            return Ok(false);
+        }
+        let mut data = [
+            Register::DOM,
+            decimal_to_packed_bcd(1),
+            (month_register & BitFlags::CENTURY) | decimal_to_packed_bcd(3),
+        ];
+        self.iface.write_data(&mut data)?;
+        Ok(true)
+    }
+
+    fn read_weekday_and_expected(&mut self) -> Result<(u8, u8), Error<E>> {
+        let mut data = [0; 5];
+        data[0] = Register::DOW;
+        self.iface.read_data(&mut data)?;
+        let offset = Register::DOW as usize;
+        let date = self.date_from_registers(
+            data[Register::DOM as usize + 1 - offset],
+            data[Register::MONTH as usize + 1 - offset],
+            data[Register::YEAR as usize + 1 - offset],
+        )?;
+        let expected = weekday_to_register(date.weekday(), self.weekday_numbering);
+        Ok((data[Register::DOW as usize + 1 - offset], expected))
+    }
+
+    fn auto_repair_weekday(&mut self) -> Result<(), Error<E>> {
+        if !self.weekday_auto_repair {
+            return Ok(());
+        }
+        match self.repair_weekday() {
+            Err(Error::InvalidDeviceState { .. }) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    // Decode the date and time from the payload of a read starting at the
+    // seconds register.
+    pub(crate) fn datetime_from_registers(
+        &self,
+        data: &[u8; 8],
+    ) -> Result<NaiveDateTime, Error<E>> {
+        let date = self.date_from_registers(
+            data[Register::DOM as usize + 1],
+            data[Register::MONTH as usize + 1],
+            data[Register::YEAR as usize + 1],
+        )?;
+        let time = time_from_registers(
+            data[Register::SECONDS as usize + 1],
+            data[Register::MINUTES as usize + 1],
+            data[Register::HOURS as usize + 1],
+        )?;
+        Ok(date.and_time(time))
+    }
+
+    fn date_from_registers(&self, day: u8, month: u8, year: u8) -> Result<NaiveDate, Error<E>> {
+        let year = year_from_registers(month, year, self.century_base)?;
+        let month = month_from_register(month)?;
+        let day = decimal_from_register(Register::DOM, day, 1..=31)?;
+        let (month, day) = compensate_leap_day(year, month, day);
+        // The day may still be beyond the end of the month (e.g. 30th of February)
+        let date = NaiveDate::from_ymd_opt(year.into(), month.into(), day.into());
+        some_or_invalid_error(date, Register::DOM)
+    }
+
+    fn check_year(&self, year: i32) -> Result<(), Error<E>> {
+        let first = i32::from(self.century_base);
+        if year < first || year > first + 199 {
+            return Err(Error::InvalidInputData {
+                field: InputField::Year,
+            });
+        }
+        Ok(())
+    }
+
+    fn read_register_decimal(
+        &mut self,
+        register: u8,
+        range: RangeInclusive<u8>,
+    ) -> Result<u8, Error<E>> {
         let data = self.iface.read_register(register)?;
-        Ok(packed_bcd_to_decimal(data))
+        decimal_from_register(register, data, range)
     }
 
     fn write_register_decimal(&mut self, register: u8, decimal_number: u8) -> Result<(), Error<E>> {
@@ -227,42 +523,77 @@ where
     }
 }
 
-fn hours_from_register(data: u8) -> Hours {
+fn decimal_from_register<E>(
+    register: u8,
+    data: u8,
+    range: RangeInclusive<u8>,
+) -> Result<u8, Error<E>> {
+    some_or_invalid_error(checked_packed_bcd_to_decimal(data, range), register)
+}
+
+fn time_from_registers<E>(seconds: u8, minutes: u8, hours: u8) -> Result<NaiveTime, Error<E>> {
+    let second = decimal_from_register(Register::SECONDS, seconds, 0..=59)?;
+    let minute = decimal_from_register(Register::MINUTES, minutes, 0..=59)?;
+    let hour = some_or_invalid_error(hours_from_register(hours), Register::HOURS)?;
+    let time = NaiveTime::from_hms_opt(get_h24(hour).into(), minute.into(), second.into());
+    some_or_invalid_error(time, Register::HOURS)
+}
+
+fn hours_from_register(data: u8) -> Option<Hours> {
     if is_24h_format(data) {
-        Hours::H24(packed_bcd_to_decimal(data & !BitFlags::H24_H12))
-    } else if is_am(data) {
-        Hours::AM(packed_bcd_to_decimal(
-            data & !(BitFlags::H24_H12 | BitFlags::AM_PM),
-        ))
+        checked_packed_bcd_to_decimal(data & !BitFlags::H24_H12, 0..=23).map(Hours::H24)
     } else {
-        Hours::PM(packed_bcd_to_decimal(
-            data & !(BitFlags::H24_H12 | BitFlags::AM_PM),
-        ))
+        let hours =
+            checked_packed_bcd_to_decimal(data & !(BitFlags::H24_H12 | BitFlags::AM_PM), 1..=12);
+        if is_am(data) {
+            hours.map(Hours::AM)
+        } else {
+            hours.map(Hours::PM)
+        }
     }
 }
 
-fn year_from_registers(month: u8, year: u8) -> u16 {
+fn month_from_register<E>(data: u8) -> Result<u8, Error<E>> {
+    decimal_from_register(Register::MONTH, data & !BitFlags::CENTURY, 1..=12)
+}
+
+fn year_from_registers<E>(month: u8, year: u8, century_base: u16) -> Result<u16, Error<E>> {
     let century = month & BitFlags::CENTURY;
-    let year = packed_bcd_to_decimal(year);
+    let year = u16::from(decimal_from_register(Register::YEAR, year, 0..=99)?);
     if century != 0 {
-        2100 + u16::from(year)
+        Ok(century_base + 100 + year)
     } else {
-        2000 + u16::from(year)
+        Ok(century_base + year)
     }
 }
 
-fn month_year_to_registers(month: u8, year: u16) -> (u8, u8) {
-    if year > 2099 {
+fn month_year_to_registers(month: u8, year: u16, century_base: u16) -> (u8, u8) {
+    let year = year - century_base;
+    if year > 99 {
         let month = BitFlags::CENTURY | decimal_to_packed_bcd(month);
-        (month, decimal_to_packed_bcd((year - 2100) as u8))
+        (month, decimal_to_packed_bcd((year - 100) as u8))
     } else {
         (
             decimal_to_packed_bcd(month),
-            decimal_to_packed_bcd((year - 2000) as u8),
+            decimal_to_packed_bcd(year as u8),
         )
     }
 }
 
+// The device counts a 29th of February on every year with the last two
+// digits divisible by 4. Such a day that does not exist is the 1st of March.
+fn compensate_leap_day(year: u16, month: u8, day: u8) -> (u8, u8) {
+    if month == 2 && day == 29 && !is_leap_year(year) {
+        (3, 1)
+    } else {
+        (month, day)
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
 fn is_24h_format(hours_data: u8) -> bool {
     hours_data & BitFlags::H24_H12 == 0
 }
@@ -271,16 +602,62 @@ fn is_am(hours_data: u8) -> bool {
     hours_data & BitFlags::AM_PM == 0
 }
 
-fn get_h24(hour: Hours) -> u8 {
+pub(crate) fn hour_mode_from_register(hours_data: u8) -> HourMode {
+    if is_24h_format(hours_data) {
+        HourMode::H24
+    } else {
+        HourMode::H12
+    }
+}
+
+pub(super) fn get_h24(hour: Hours) -> u8 {
     match hour {
         Hours::H24(h) => h,
+        Hours::AM(12) => 0,
         Hours::AM(h) => h,
+        Hours::PM(12) => 12,
         Hours::PM(h) => h + 12,
     }
 }
 
+pub(super) fn hours_from_h24(hour: u8, mode: HourMode) -> Hours {
+    match mode {
+        HourMode::H24 => Hours::H24(hour),
+        HourMode::H12 => match hour {
+            0 => Hours::AM(12),
+            1..=11 => Hours::AM(hour),
+            12 => Hours::PM(12),
+            _ => Hours::PM(hour - 12),
+        },
+    }
+}
+
+// Converts the content of an hours register to the given format.
+// The alarm mask bit is kept.
+fn convert_hours_register(data: u8, mode: HourMode) -> u8 {
+    const HOURS_MASK: u8 = !BitFlags::ALARM_MATCH;
+    let hours = hours_from_register(data & HOURS_MASK)
+        .and_then(|hours| hours_to_register::<()>(hours_from_h24(get_h24(hours), mode)).ok());
+    match hours {
+        Some(hours) => (data & BitFlags::ALARM_MATCH) | hours,
+        None => data,
+    }
+}
+
+// Returns the content of the hours register for the hour following the one
+// in `data`, in the same format.
+fn next_hours_register(data: u8) -> u8 {
+    let mode = hour_mode_from_register(data);
+    hours_from_register(data)
+        .and_then(|hours| {
+            hours_to_register::<()>(hours_from_h24((get_h24(hours) + 1) % 24, mode)).ok()
+        })
+        .unwrap_or(data)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::packed_bcd_to_decimal;
     use super::*;
 
     #[test]
@@ -294,7 +671,210 @@ mod tests {
         assert_eq!(13, get_h24(Hours::PM(1)));
 
         assert_eq!(23, get_h24(Hours::H24(23)));
-        assert_eq!(12, get_h24(Hours::AM(12)));
+        assert_eq!(0, get_h24(Hours::AM(12)));
+        assert_eq!(12, get_h24(Hours::PM(12)));
+        assert_eq!(11, get_h24(Hours::AM(11)));
         assert_eq!(23, get_h24(Hours::PM(11)));
     }
+
+    #[test]
+    fn can_convert_from_h24() {
+        assert_eq!(Hours::AM(12), hours_from_h24(0, HourMode::H12));
+        assert_eq!(Hours::AM(1), hours_from_h24(1, HourMode::H12));
+        assert_eq!(Hours::AM(11), hours_from_h24(11, HourMode::H12));
+        assert_eq!(Hours::PM(12), hours_from_h24(12, HourMode::H12));
+        assert_eq!(Hours::PM(1), hours_from_h24(13, HourMode::H12));
+        assert_eq!(Hours::PM(11), hours_from_h24(23, HourMode::H12));
+        for hour in 0..24 {
+            assert_eq!(Hours::H24(hour), hours_from_h24(hour, HourMode::H24));
+            assert_eq!(hour, get_h24(hours_from_h24(hour, HourMode::H12)));
+        }
+    }
+
+    #[test]
+    fn can_convert_hours_register() {
+        assert_eq!(0b0101_0010, convert_hours_register(0, HourMode::H12));
+        assert_eq!(
+            0b0111_0001,
+            convert_hours_register(0b0010_0011, HourMode::H12)
+        );
+        assert_eq!(0, convert_hours_register(0b0101_0010, HourMode::H24));
+        assert_eq!(
+            0b0001_0010,
+            convert_hours_register(0b0111_0010, HourMode::H24)
+        );
+        assert_eq!(
+            0b0010_0011,
+            convert_hours_register(0b0111_0001, HourMode::H24)
+        );
+        // alarm mask bit is kept
+        assert_eq!(
+            0b1111_0001,
+            convert_hours_register(0b1010_0011, HourMode::H12)
+        );
+        // invalid values are kept
+        assert_eq!(
+            0b0011_1001,
+            convert_hours_register(0b0011_1001, HourMode::H12)
+        );
+        assert_eq!(
+            0b0100_0000,
+            convert_hours_register(0b0100_0000, HourMode::H24)
+        );
+    }
+
+    #[test]
+    fn can_get_next_hours_register() {
+        assert_eq!(0b0001_0000, next_hours_register(0b0000_1001));
+        assert_eq!(0, next_hours_register(0b0010_0011));
+        // 11 AM to 12 PM
+        assert_eq!(0b0111_0010, next_hours_register(0b0101_0001));
+        // 12 PM to 1 PM
+        assert_eq!(0b0110_0001, next_hours_register(0b0111_0010));
+        // 11 PM to 12 AM
+        assert_eq!(0b0101_0010, next_hours_register(0b0111_0001));
+        // invalid values are kept
+        assert_eq!(0b0011_1001, next_hours_register(0b0011_1001));
+    }
+
+    #[test]
+    fn can_decode_all_hours_register_values() {
+        let mut valid_count = 0;
+        for data in 0..=255u8 {
+            match hours_from_register(data) {
+                Some(hours) => {
+                    valid_count += 1;
+                    assert_eq!(Ok(data), hours_to_register::<()>(hours).map_err(|_| ()));
+                }
+                None => {
+                    let h24 = data & BitFlags::H24_H12 == 0;
+                    let value = if h24 {
+                        data & !BitFlags::H24_H12
+                    } else {
+                        data & !(BitFlags::H24_H12 | BitFlags::AM_PM)
+                    };
+                    let range = if h24 { 0..=23 } else { 1..=12 };
+                    assert_eq!(None, checked_packed_bcd_to_decimal(value, range));
+                }
+            }
+        }
+        // 24 hours in the 24-hour format plus 12 AM and 12 PM values
+        assert_eq!(48, valid_count);
+    }
+
+    #[test]
+    fn can_decode_all_month_register_values() {
+        for data in 0..=255u8 {
+            let month = data & !BitFlags::CENTURY;
+            let valid = (0x01..=0x09).contains(&month) || (0x10..=0x12).contains(&month);
+            match month_from_register::<()>(data) {
+                Ok(value) => {
+                    assert!(valid);
+                    assert_eq!(month, decimal_to_packed_bcd(value));
+                }
+                Err(Error::InvalidDeviceState { register }) => {
+                    assert!(!valid);
+                    assert_eq!(Register::MONTH, register);
+                }
+                Err(_) => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn can_decode_all_year_register_values() {
+        for data in 0..=255u8 {
+            let valid = data >> 4 <= 9 && data & 0xF <= 9;
+            match year_from_registers::<()>(0, data, 2000) {
+                Ok(year) => {
+                    assert!(valid);
+                    assert_eq!(data, decimal_to_packed_bcd((year - 2000) as u8));
+                }
+                Err(Error::InvalidDeviceState { register }) => {
+                    assert!(!valid);
+                    assert_eq!(Register::YEAR, register);
+                }
+                Err(_) => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn can_decode_all_time_register_values() {
+        for data in 0..=255u8 {
+            let valid = data >> 4 <= 5 && data & 0xF <= 9;
+            match time_from_registers::<()>(data, 0, 0) {
+                Ok(time) => {
+                    assert!(valid);
+                    assert_eq!(data, decimal_to_packed_bcd(time.second() as u8));
+                }
+                Err(Error::InvalidDeviceState { register }) => {
+                    assert!(!valid);
+                    assert_eq!(Register::SECONDS, register);
+                }
+                Err(_) => panic!(),
+            }
+            match time_from_registers::<()>(0, data, 0) {
+                Ok(time) => assert_eq!(data, decimal_to_packed_bcd(time.minute() as u8)),
+                Err(Error::InvalidDeviceState { register }) => {
+                    assert!(!valid);
+                    assert_eq!(Register::MINUTES, register);
+                }
+                Err(_) => panic!(),
+            }
+            match time_from_registers::<()>(0, 0, data) {
+                Ok(time) => assert_eq!(
+                    Some(time.hour() as u8),
+                    hours_from_register(data).map(get_h24)
+                ),
+                Err(Error::InvalidDeviceState { register }) => {
+                    assert_eq!(None, hours_from_register(data));
+                    assert_eq!(Register::HOURS, register);
+                }
+                Err(_) => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn can_convert_all_years_for_all_century_bases() {
+        for century_base in (0..=u16::MAX - 199).step_by(100) {
+            for year in century_base..=century_base + 199 {
+                for month in 1..=12 {
+                    let (month_reg, year_reg) = month_year_to_registers(month, year, century_base);
+                    assert_eq!(year % 100, u16::from(packed_bcd_to_decimal(year_reg)));
+                    assert_eq!(month, packed_bcd_to_decimal(month_reg & !BitFlags::CENTURY));
+                    assert_eq!(
+                        year,
+                        year_from_registers::<()>(month_reg, year_reg, century_base).unwrap()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn century_bit_is_set_for_second_century() {
+        let (month, year) = month_year_to_registers(1, 2099, 2000);
+        assert_eq!((0b0000_0001, 0b1001_1001), (month, year));
+        let (month, year) = month_year_to_registers(1, 2100, 2000);
+        assert_eq!((0b1000_0001, 0), (month, year));
+        let (month, year) = month_year_to_registers(1, 2199, 2000);
+        assert_eq!((0b1000_0001, 0b1001_1001), (month, year));
+        let (month, year) = month_year_to_registers(1, 2200, 2100);
+        assert_eq!((0b1000_0001, 0), (month, year));
+    }
+
+    #[test]
+    fn can_determine_leap_years() {
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(2004));
+        assert!(is_leap_year(2096));
+        assert!(!is_leap_year(2100));
+        assert!(!is_leap_year(2101));
+        assert!(is_leap_year(2104));
+        assert!(!is_leap_year(2200));
+        assert!(!is_leap_year(2300));
+        assert!(is_leap_year(2400));
+    }
 }
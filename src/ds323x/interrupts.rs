@@ -0,0 +1,103 @@
+//! Waiting for alarm interrupts on the INT/SQW pin
+
+use crate::{
+    interface::{ReadData, WriteData},
+    mode, BitFlags, Ds323x, Error, Register,
+};
+use embedded_hal::digital::InputPin;
+
+/// Alarms that caused an interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlarmEvent {
+    /// Alarm 1 matched
+    Alarm1,
+    /// Alarm 2 matched
+    Alarm2,
+    /// Both alarms matched
+    Both,
+}
+
+/// Errors while waiting for an alarm interrupt
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WaitError<E, PinE> {
+    /// Error communicating with the device
+    Device(Error<E>),
+    /// Error reading the interrupt pin
+    Pin(PinE),
+    /// The pin is asserted but no alarm with enabled interrupts has matched.
+    ///
+    /// This happens if the pin is in square-wave mode, is driven by another
+    /// source or if the alarm interrupts were enabled without the driver.
+    NoAlarmMatched,
+}
+
+impl<E, PinE> From<Error<E>> for WaitError<E, PinE> {
+    fn from(error: Error<E>) -> Self {
+        WaitError::Device(error)
+    }
+}
+
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+    MODE: mode::AlarmInterruptMode,
+{
+    /// Wait for an alarm interrupt by polling the INT/SQW pin.
+    ///
+    /// This blocks until the (active-low) pin is asserted and reads which
+    /// alarms with enabled interrupts have matched. Their matched flags are
+    /// then cleared, which releases the pin. If none has matched,
+    /// `WaitError::NoAlarmMatched` is returned, since the pin would otherwise
+    /// stay asserted.
+    ///
+    /// The INT/SQW pin must be configured as an interrupt output with
+    /// [`use_int_sqw_output_as_interrupt()`](#method.use_int_sqw_output_as_interrupt)
+    /// and the interrupts of the alarms to wait for must have been enabled
+    /// through the driver with [`enable_alarm1_interrupts()`](#method.enable_alarm1_interrupts)
+    /// and/or [`enable_alarm2_interrupts()`](#method.enable_alarm2_interrupts).
+    pub fn wait_for_alarm_blocking<P: InputPin>(
+        &mut self,
+        pin: &mut P,
+    ) -> Result<AlarmEvent, WaitError<E, P::Error>> {
+        while !pin.is_low().map_err(WaitError::Pin)? {}
+        self.take_alarm_event()?.ok_or(WaitError::NoAlarmMatched)
+    }
+
+    /// Wait for an alarm interrupt on the INT/SQW pin.
+    ///
+    /// This waits until the (active-low) pin is asserted and reads which
+    /// alarms with enabled interrupts have matched. Their matched flags are
+    /// then cleared, which releases the pin. If none has matched,
+    /// `WaitError::NoAlarmMatched` is returned.
+    ///
+    /// The same configuration as for
+    /// [`wait_for_alarm_blocking()`](#method.wait_for_alarm_blocking) is necessary.
+    #[cfg(feature = "async")]
+    pub async fn wait_for_alarm<P: embedded_hal_async::digital::Wait>(
+        &mut self,
+        pin: &mut P,
+    ) -> Result<AlarmEvent, WaitError<E, P::Error>> {
+        pin.wait_for_low().await.map_err(WaitError::Pin)?;
+        self.take_alarm_event()?.ok_or(WaitError::NoAlarmMatched)
+    }
+
+    // Determine which alarms with enabled interrupts have matched and clear
+    // their flags.
+    fn take_alarm_event(&mut self) -> Result<Option<AlarmEvent>, Error<E>> {
+        let status = self.iface.read_register(Register::STATUS)?;
+        let alarm1 =
+            (status & BitFlags::ALARM1F) != 0 && (self.control & BitFlags::ALARM1_INT_EN) != 0;
+        let alarm2 =
+            (status & BitFlags::ALARM2F) != 0 && (self.control & BitFlags::ALARM2_INT_EN) != 0;
+        let (event, flags_to_keep) = match (alarm1, alarm2) {
+            (true, true) => (AlarmEvent::Both, 0),
+            (true, false) => (AlarmEvent::Alarm1, BitFlags::ALARM2F),
+            (false, true) => (AlarmEvent::Alarm2, BitFlags::ALARM1F),
+            (false, false) => return Ok(None),
+        };
+        self.write_status(self.status | flags_to_keep)?;
+        Ok(Some(event))
+    }
+}
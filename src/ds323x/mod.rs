@@ -1,11 +1,26 @@
+#[cfg(feature = "serde")]
+mod alarm_serde;
 mod alarms;
+mod backends;
 mod configuration;
+mod datetime;
+mod interrupts;
+mod registers;
+mod retry;
+mod self_test;
 mod status;
+mod validity;
+mod verify;
 pub use self::alarms::{
     Alarm1Matching, Alarm2Matching, DayAlarm1, DayAlarm2, WeekdayAlarm1, WeekdayAlarm2,
 };
-mod datetime;
-use crate::{BitFlags, Error, Hours, NaiveTime, Timelike};
+pub use self::interrupts::{AlarmEvent, WaitError};
+pub use self::registers::Reg;
+pub(crate) use self::self_test::check_sram;
+pub use self::self_test::{CheckResult, SelfTestReport};
+pub(crate) use self::verify::first_mismatch;
+use crate::{BitFlags, Error, Hours, InputField, NaiveTime, Timelike, Weekday, WeekdayNumbering};
+use core::ops::RangeInclusive;
 
 // Transforms a decimal number to packed BCD format
 fn decimal_to_packed_bcd(dec: u8) -> u8 {
@@ -17,22 +32,55 @@ fn packed_bcd_to_decimal(bcd: u8) -> u8 {
     (bcd >> 4) * 10 + (bcd & 0xF)
 }
 
+// Transforms a number in packed BCD format to decimal if both nibbles are
+// decimal digits and the result is within the given range
+fn checked_packed_bcd_to_decimal(bcd: u8, range: RangeInclusive<u8>) -> Option<u8> {
+    if bcd >> 4 > 9 || bcd & 0xF > 9 {
+        return None;
+    }
+    Some(packed_bcd_to_decimal(bcd)).filter(|dec| range.contains(dec))
+}
+
 fn hours_to_register<E>(hours: Hours) -> Result<u8, Error<E>> {
     match hours {
-        Hours::H24(h) if h > 23 => Err(Error::InvalidInputData),
+        Hours::H24(h) if h > 23 => Err(Error::InvalidInputData {
+            field: InputField::Hour,
+        }),
         Hours::H24(h) => Ok(decimal_to_packed_bcd(h)),
-        Hours::AM(h) if !(1..=12).contains(&h) => Err(Error::InvalidInputData),
+        Hours::AM(h) if !(1..=12).contains(&h) => Err(Error::InvalidInputData {
+            field: InputField::Hour,
+        }),
         Hours::AM(h) => Ok(BitFlags::H24_H12 | decimal_to_packed_bcd(h)),
-        Hours::PM(h) if !(1..=12).contains(&h) => Err(Error::InvalidInputData),
+        Hours::PM(h) if !(1..=12).contains(&h) => Err(Error::InvalidInputData {
+            field: InputField::Hour,
+        }),
         Hours::PM(h) => Ok(BitFlags::H24_H12 | BitFlags::AM_PM | decimal_to_packed_bcd(h)),
     }
 }
 
-fn some_or_invalid_error<T, E>(data: Option<T>) -> Result<T, Error<E>> {
+fn weekday_to_register(weekday: Weekday, numbering: WeekdayNumbering) -> u8 {
+    match numbering {
+        WeekdayNumbering::SundayFirst => weekday.number_from_sunday() as u8,
+        WeekdayNumbering::MondayFirst => weekday.number_from_monday() as u8,
+    }
+}
+
+fn weekday_from_register(value: u8, numbering: WeekdayNumbering) -> Option<Weekday> {
+    if !(1..=7).contains(&value) {
+        return None;
+    }
+    let first = match numbering {
+        WeekdayNumbering::SundayFirst => Weekday::Sun,
+        WeekdayNumbering::MondayFirst => Weekday::Mon,
+    };
+    Some((1..value).fold(first, |day, _| day.succ()))
+}
+
+fn some_or_invalid_error<T, E>(data: Option<T>, register: u8) -> Result<T, Error<E>> {
     if let Some(data) = data {
         Ok(data)
     } else {
-        Err(Error::InvalidDeviceState)
+        Err(Error::InvalidDeviceState { register })
     }
 }
 
@@ -42,7 +90,7 @@ mod tests {
 
     #[test]
     fn if_some_then_get_inner() {
-        match some_or_invalid_error::<u8, ()>(Some(1)) {
+        match some_or_invalid_error::<u8, ()>(Some(1), 0) {
             Ok(1) => (),
             _ => panic!(),
         }
@@ -50,12 +98,42 @@ mod tests {
 
     #[test]
     fn if_none_then_error() {
-        match some_or_invalid_error::<u8, ()>(None) {
-            Err(Error::InvalidDeviceState) => (),
+        match some_or_invalid_error::<u8, ()>(None, 3) {
+            Err(Error::InvalidDeviceState { register: 3 }) => (),
             _ => panic!(),
         }
     }
 
+    #[test]
+    fn can_convert_weekday_to_register() {
+        let sunday_first = WeekdayNumbering::SundayFirst;
+        let monday_first = WeekdayNumbering::MondayFirst;
+        assert_eq!(1, weekday_to_register(Weekday::Sun, sunday_first));
+        assert_eq!(2, weekday_to_register(Weekday::Mon, sunday_first));
+        assert_eq!(7, weekday_to_register(Weekday::Sat, sunday_first));
+        assert_eq!(1, weekday_to_register(Weekday::Mon, monday_first));
+        assert_eq!(6, weekday_to_register(Weekday::Sat, monday_first));
+        assert_eq!(7, weekday_to_register(Weekday::Sun, monday_first));
+    }
+
+    #[test]
+    fn can_convert_weekday_from_register() {
+        for numbering in [WeekdayNumbering::SundayFirst, WeekdayNumbering::MondayFirst] {
+            assert_eq!(None, weekday_from_register(0, numbering));
+            assert_eq!(None, weekday_from_register(8, numbering));
+            for value in 1..=7 {
+                let weekday = weekday_from_register(value, numbering).unwrap();
+                assert_eq!(value, weekday_to_register(weekday, numbering));
+            }
+        }
+        let sunday_first = WeekdayNumbering::SundayFirst;
+        let monday_first = WeekdayNumbering::MondayFirst;
+        assert_eq!(Some(Weekday::Sun), weekday_from_register(1, sunday_first));
+        assert_eq!(Some(Weekday::Sat), weekday_from_register(7, sunday_first));
+        assert_eq!(Some(Weekday::Mon), weekday_from_register(1, monday_first));
+        assert_eq!(Some(Weekday::Sun), weekday_from_register(7, monday_first));
+    }
+
     #[test]
     fn can_convert_packed_bcd_to_decimal() {
         assert_eq!(0, packed_bcd_to_decimal(0b0000_0000));
@@ -69,6 +147,33 @@ mod tests {
         assert_eq!(59, packed_bcd_to_decimal(0b0101_1001));
     }
 
+    #[test]
+    fn checked_conversion_rejects_invalid_packed_bcd() {
+        for bcd in 0..=255u8 {
+            let valid = bcd >> 4 <= 9 && bcd & 0xF <= 9;
+            let expected = if valid {
+                Some(packed_bcd_to_decimal(bcd))
+            } else {
+                None
+            };
+            assert_eq!(expected, checked_packed_bcd_to_decimal(bcd, 0..=99));
+        }
+    }
+
+    #[test]
+    fn checked_conversion_rejects_values_out_of_range() {
+        for bcd in 0..=255u8 {
+            let expected = checked_packed_bcd_to_decimal(bcd, 0..=99).filter(|dec| *dec <= 59);
+            assert_eq!(expected, checked_packed_bcd_to_decimal(bcd, 0..=59));
+            let expected =
+                checked_packed_bcd_to_decimal(bcd, 0..=99).filter(|dec| (1..=12).contains(dec));
+            assert_eq!(expected, checked_packed_bcd_to_decimal(bcd, 1..=12));
+        }
+        assert_eq!(Some(59), checked_packed_bcd_to_decimal(0b0101_1001, 0..=59));
+        assert_eq!(None, checked_packed_bcd_to_decimal(0b0110_0000, 0..=59));
+        assert_eq!(None, checked_packed_bcd_to_decimal(0, 1..=31));
+    }
+
     #[test]
     fn can_convert_decimal_to_packed_bcd() {
         assert_eq!(0b0000_0000, decimal_to_packed_bcd(0));
@@ -0,0 +1,265 @@
+//! Typed access to the device registers
+
+use super::datetime::hour_mode_from_register;
+use crate::{
+    interface::{ReadData, WriteData},
+    mode, private, BitFlags, Ds323x, Error, InputField, Register,
+};
+
+/// Device register
+///
+/// This covers the registers of the DS3231, DS3232 and DS3234 devices.
+/// The SRAM of the DS3232 and DS3234 is accessed with `read_sram()` and
+/// `write_sram()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Reg {
+    /// Seconds (0x00)
+    Seconds,
+    /// Minutes (0x01)
+    Minutes,
+    /// Hours and 12/24-hour format (0x02)
+    Hours,
+    /// Day of the week (0x03)
+    DayOfWeek,
+    /// Day of the month (0x04)
+    DayOfMonth,
+    /// Month and century (0x05)
+    Month,
+    /// Year (0x06)
+    Year,
+    /// Alarm 1 seconds (0x07)
+    Alarm1Seconds,
+    /// Alarm 1 minutes (0x08)
+    Alarm1Minutes,
+    /// Alarm 1 hours (0x09)
+    Alarm1Hours,
+    /// Alarm 1 day of the week or of the month (0x0A)
+    Alarm1DayDate,
+    /// Alarm 2 minutes (0x0B)
+    Alarm2Minutes,
+    /// Alarm 2 hours (0x0C)
+    Alarm2Hours,
+    /// Alarm 2 day of the week or of the month (0x0D)
+    Alarm2DayDate,
+    /// Control (0x0E)
+    Control,
+    /// Control/status (0x0F)
+    Status,
+    /// Aging offset (0x10)
+    AgingOffset,
+    /// Temperature, integer part (0x11)
+    TempMsb,
+    /// Temperature, fractional part (0x12)
+    TempLsb,
+    /// Disable temperature conversions on battery (0x13, only DS3234)
+    TempConv,
+    /// SRAM address (0x18, only DS3234)
+    SramAddress,
+    /// SRAM data (0x19, only DS3234)
+    SramData,
+}
+
+impl Reg {
+    // All registers in address order
+    pub(crate) const ALL: [Reg; 22] = [
+        Reg::Seconds,
+        Reg::Minutes,
+        Reg::Hours,
+        Reg::DayOfWeek,
+        Reg::DayOfMonth,
+        Reg::Month,
+        Reg::Year,
+        Reg::Alarm1Seconds,
+        Reg::Alarm1Minutes,
+        Reg::Alarm1Hours,
+        Reg::Alarm1DayDate,
+        Reg::Alarm2Minutes,
+        Reg::Alarm2Hours,
+        Reg::Alarm2DayDate,
+        Reg::Control,
+        Reg::Status,
+        Reg::AgingOffset,
+        Reg::TempMsb,
+        Reg::TempLsb,
+        Reg::TempConv,
+        Reg::SramAddress,
+        Reg::SramData,
+    ];
+
+    /// Address of the register
+    pub fn address(self) -> u8 {
+        match self {
+            Reg::Seconds => Register::SECONDS,
+            Reg::Minutes => Register::MINUTES,
+            Reg::Hours => Register::HOURS,
+            Reg::DayOfWeek => Register::DOW,
+            Reg::DayOfMonth => Register::DOM,
+            Reg::Month => Register::MONTH,
+            Reg::Year => Register::YEAR,
+            Reg::Alarm1Seconds => Register::ALARM1_SECONDS,
+            Reg::Alarm1Minutes => Register::ALARM1_MINUTES,
+            Reg::Alarm1Hours => Register::ALARM1_HOURS,
+            Reg::Alarm1DayDate => Register::ALARM1_DAY_DATE,
+            Reg::Alarm2Minutes => Register::ALARM2_MINUTES,
+            Reg::Alarm2Hours => Register::ALARM2_HOURS,
+            Reg::Alarm2DayDate => Register::ALARM2_DAY_DATE,
+            Reg::Control => Register::CONTROL,
+            Reg::Status => Register::STATUS,
+            Reg::AgingOffset => Register::AGING_OFFSET,
+            Reg::TempMsb => Register::TEMP_MSB,
+            Reg::TempLsb => Register::TEMP_LSB,
+            Reg::TempConv => Register::TEMP_CONV,
+            Reg::SramAddress => Register::SRAM_ADDRESS,
+            Reg::SramData => Register::SRAM_DATA,
+        }
+    }
+
+    /// Name of the register as in the datasheets
+    pub fn name(self) -> &'static str {
+        match self {
+            Reg::Seconds => "SECONDS",
+            Reg::Minutes => "MINUTES",
+            Reg::Hours => "HOURS",
+            Reg::DayOfWeek => "DOW",
+            Reg::DayOfMonth => "DOM",
+            Reg::Month => "MONTH",
+            Reg::Year => "YEAR",
+            Reg::Alarm1Seconds => "ALARM1_SECONDS",
+            Reg::Alarm1Minutes => "ALARM1_MINUTES",
+            Reg::Alarm1Hours => "ALARM1_HOURS",
+            Reg::Alarm1DayDate => "ALARM1_DAY_DATE",
+            Reg::Alarm2Minutes => "ALARM2_MINUTES",
+            Reg::Alarm2Hours => "ALARM2_HOURS",
+            Reg::Alarm2DayDate => "ALARM2_DAY_DATE",
+            Reg::Control => "CONTROL",
+            Reg::Status => "STATUS",
+            Reg::AgingOffset => "AGING_OFFSET",
+            Reg::TempMsb => "TEMP_MSB",
+            Reg::TempLsb => "TEMP_LSB",
+            Reg::TempConv => "TEMP_CONV",
+            Reg::SramAddress => "SRAM_ADDRESS",
+            Reg::SramData => "SRAM_DATA",
+        }
+    }
+
+    /// Whether the register is only available on the DS3234
+    pub fn is_ds3234_only(self) -> bool {
+        matches!(self, Reg::TempConv | Reg::SramAddress | Reg::SramData)
+    }
+
+    // Register at the address, including the registers only available on
+    // the DS3234 if `ds3234` is set.
+    pub(crate) fn from_address(address: u8, ds3234: bool) -> Option<Reg> {
+        Reg::ALL
+            .iter()
+            .copied()
+            .find(|reg| reg.address() == address && (ds3234 || !reg.is_ds3234_only()))
+    }
+}
+
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+    IC: private::RegisterMap,
+{
+    /// Read a register.
+    ///
+    /// Reading the control, status or aging offset register also updates
+    /// the values cached by the driver.
+    ///
+    /// Will return `Error::InvalidInputData` if the register is not
+    /// available on the device.
+    pub fn read_reg(&mut self, reg: Reg) -> Result<u8, Error<E>> {
+        check_available::<IC, E>(reg)?;
+        let value = self.iface.read_register(reg.address())?;
+        match reg {
+            // do not keep a conversion request or the status flags
+            Reg::Control => self.control = value & !BitFlags::TEMP_CONV,
+            Reg::Status => {
+                self.status = value & !(BitFlags::BUSY | BitFlags::ALARM2F | BitFlags::ALARM1F)
+            }
+            Reg::AgingOffset => self.aging_offset = value as i8,
+            _ => (),
+        }
+        Ok(value)
+    }
+}
+
+impl<DI, IC, E> Ds323x<DI, IC, mode::Unchecked>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+    IC: private::RegisterMap,
+{
+    /// Write a register.
+    ///
+    /// This allows using features not covered by the driver. Writing the
+    /// control, status or aging offset register also updates the values
+    /// cached by the driver, so that the other methods keep the written
+    /// settings. Writing the hours register updates the hour format used by
+    /// the driver like [`set_hour_mode()`](#method.set_hour_mode). With write
+    /// verification enabled, the register is read back like when written by
+    /// the other methods. The date and time registers, which can advance
+    /// before being read back, the read-only temperature registers and the
+    /// SRAM address and data registers, whose address advances with each
+    /// data access, are not verified.
+    ///
+    /// Note that writing the status register clears the alarm matched flags
+    /// that are not set in the value.
+    ///
+    /// This is only available in the [`mode::Unchecked`]
+    /// mode, since the control register includes the INT/SQW pin mode.
+    ///
+    /// Will return `Error::InvalidInputData` if the register is not
+    /// available on the device.
+    pub fn write_reg(&mut self, reg: Reg, value: u8) -> Result<(), Error<E>> {
+        check_available::<IC, E>(reg)?;
+        match reg {
+            Reg::Control => {
+                let result = self.write_control(value);
+                // do not repeat a conversion request in later writes
+                self.control &= !BitFlags::TEMP_CONV;
+                result
+            }
+            Reg::Status => self.write_status(value & !BitFlags::BUSY),
+            Reg::AgingOffset => self.set_aging_offset(value as i8),
+            Reg::Hours => {
+                self.iface.write_register(reg.address(), value)?;
+                self.hour_mode = hour_mode_from_register(value);
+                Ok(())
+            }
+            Reg::Seconds
+            | Reg::Minutes
+            | Reg::DayOfWeek
+            | Reg::DayOfMonth
+            | Reg::Month
+            | Reg::Year
+            | Reg::TempMsb
+            | Reg::TempLsb
+            | Reg::SramAddress
+            | Reg::SramData => self.iface.write_register(reg.address(), value),
+            _ => self.write_register_verified(reg.address(), value),
+        }
+    }
+
+    /// Read a register, change its value and write it back.
+    ///
+    /// The alarm matched flags of the status register are kept unless
+    /// cleared by `f`. See [`write_reg()`](#method.write_reg).
+    pub fn modify_reg<F>(&mut self, reg: Reg, f: F) -> Result<(), Error<E>>
+    where
+        F: FnOnce(u8) -> u8,
+    {
+        let value = self.read_reg(reg)?;
+        self.write_reg(reg, f(value))
+    }
+}
+
+fn check_available<IC: private::RegisterMap, E>(reg: Reg) -> Result<(), Error<E>> {
+    if reg.is_ds3234_only() && !IC::DS3234_REGISTERS {
+        return Err(Error::InvalidInputData {
+            field: InputField::Register,
+        });
+    }
+    Ok(())
+}
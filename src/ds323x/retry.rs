@@ -0,0 +1,59 @@
+//! Retries of transient I²C bus errors
+
+use crate::{
+    interface::{I2cInterface, RetryPolicy},
+    Ds323x,
+};
+use core::marker::PhantomData;
+use embedded_hal::delay::DelayNs;
+
+impl<I2C, D, IC, MODE> Ds323x<I2cInterface<I2C, D>, IC, MODE> {
+    /// Set the policy to retry bus operations failing with a transient error.
+    ///
+    /// This applies to every bus operation done by the driver, so all
+    /// methods benefit from it. Operations are retried if the device did not
+    /// acknowledge them or if the arbitration was lost. A write whose data
+    /// was not acknowledged is not retried, since part of it may already
+    /// have been written.
+    ///
+    /// Without a delay provided with
+    /// [`with_retry_delay()`](#method.with_retry_delay) the retries are done
+    /// immediately.
+    ///
+    /// Note: This is only available for devices connected through I²C.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.iface.retry_policy = policy;
+    }
+
+    /// Get the policy to retry bus operations failing with a transient error.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.iface.retry_policy
+    }
+
+    /// Use a delay to wait the backoff time of the retry policy between
+    /// attempts.
+    pub fn with_retry_delay<D2: DelayNs>(
+        self,
+        delay: D2,
+    ) -> Ds323x<I2cInterface<I2C, D2>, IC, MODE> {
+        Ds323x {
+            iface: I2cInterface {
+                i2c: self.iface.i2c,
+                delay,
+                retry_policy: self.iface.retry_policy,
+            },
+            control: self.control,
+            status: self.status,
+            aging_offset: self.aging_offset,
+            century_base: self.century_base,
+            hour_mode: self.hour_mode,
+            weekday_numbering: self.weekday_numbering,
+            weekday_auto_repair: self.weekday_auto_repair,
+            write_verification: self.write_verification,
+            time_set_marker: self.time_set_marker,
+            time_set: self.time_set,
+            _ic: PhantomData,
+            _mode: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Production self-test
+
+use crate::{
+    interface::{ReadData, WriteData},
+    BitFlags, Ds323x, Error, NaiveDateTime, Register, Sram,
+};
+use core::ops::RangeInclusive;
+use embedded_hal::delay::DelayNs;
+
+// Time waited between the two date/time readings
+const OSCILLATOR_WAIT_MS: u32 = 1100;
+// Accepted time difference between the two readings
+const OSCILLATOR_ADVANCE_S: RangeInclusive<i64> = 1..=2;
+// Polling of the end of the temperature conversion
+const TEMP_CONV_POLL_MS: u32 = 10;
+const TEMP_CONV_POLLS: u32 = 50;
+// Operating temperature range of the devices
+const TEMPERATURE_RANGE: RangeInclusive<f32> = -40.0..=85.0;
+// Patterns written to the aging offset register
+const AGING_OFFSET_PATTERNS: [[u8; 1]; 2] = [[0x55], [0xAA]];
+// Patterns written to the alarm registers. The alarm mask bits are cleared
+// and the hours are invalid so that the alarms can not match.
+const ALARM_PATTERNS: [[u8; 7]; 2] = [[0x5A; 7], [0x25; 7]];
+// Patterns written to the SRAM
+const SRAM_PATTERNS: [u8; 2] = [0x55, 0xAA];
+// Largest SRAM size of the devices
+const MAX_SRAM_SIZE: usize = 256;
+
+/// Result of a self-test check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CheckResult {
+    /// The check passed.
+    Passed,
+    /// The check failed or was interrupted by a bus error.
+    Failed,
+    /// The check was not run because the device does not respond, does
+    /// not support it or a previous check was interrupted by a bus error.
+    Skipped,
+}
+
+/// Results of the self-test checks
+///
+/// See `self_test()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SelfTestReport {
+    /// The device responds on the bus.
+    pub communication: CheckResult,
+    /// The time advances by one or two seconds within 1.1 seconds.
+    pub oscillator: CheckResult,
+    /// A forced temperature conversion finishes and the temperature is
+    /// within the operating range (-40°C to +85°C).
+    pub temperature: CheckResult,
+    /// Test patterns written to the aging offset register are read back.
+    pub aging_offset: CheckResult,
+    /// Test patterns written to the alarm registers are read back.
+    pub alarms: CheckResult,
+    /// Test patterns written to the whole SRAM are read back.
+    /// This is skipped on the DS3231.
+    pub sram: CheckResult,
+}
+
+impl SelfTestReport {
+    /// Whether no check failed and the device responds.
+    pub fn passed(&self) -> bool {
+        self.communication == CheckResult::Passed
+            && [
+                self.oscillator,
+                self.temperature,
+                self.aging_offset,
+                self.alarms,
+                self.sram,
+            ]
+            .iter()
+            .all(|&result| result != CheckResult::Failed)
+    }
+}
+
+impl Default for SelfTestReport {
+    fn default() -> Self {
+        SelfTestReport {
+            communication: CheckResult::Skipped,
+            oscillator: CheckResult::Skipped,
+            temperature: CheckResult::Skipped,
+            aging_offset: CheckResult::Skipped,
+            alarms: CheckResult::Skipped,
+            sram: CheckResult::Skipped,
+        }
+    }
+}
+
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    // Run the checks common to all devices followed by the SRAM check.
+    // Write verification is disabled while running the checks.
+    pub(crate) fn run_self_test<DL: DelayNs>(
+        &mut self,
+        delay: &mut DL,
+        sram_check: impl FnOnce(&mut Self) -> Result<CheckResult, Error<E>>,
+    ) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        if self.iface.read_register(Register::CONTROL).is_err() {
+            report.communication = CheckResult::Failed;
+            return report;
+        }
+        report.communication = CheckResult::Passed;
+        let write_verification = self.write_verification;
+        self.write_verification = false;
+        // A bus error is recorded in the report as a failure of the
+        // interrupted check and the remaining checks are skipped.
+        let _ = self.run_checks(&mut report, delay, sram_check);
+        self.write_verification = write_verification;
+        report
+    }
+
+    fn run_checks<DL: DelayNs>(
+        &mut self,
+        report: &mut SelfTestReport,
+        delay: &mut DL,
+        sram_check: impl FnOnce(&mut Self) -> Result<CheckResult, Error<E>>,
+    ) -> Result<(), Error<E>> {
+        record(&mut report.oscillator, self.check_oscillator(delay))?;
+        record(&mut report.temperature, self.check_temperature(delay))?;
+        record(
+            &mut report.aging_offset,
+            self.check_registers(Register::AGING_OFFSET, &AGING_OFFSET_PATTERNS),
+        )?;
+        record(
+            &mut report.alarms,
+            self.check_registers(Register::ALARM1_SECONDS, &ALARM_PATTERNS),
+        )?;
+        record(&mut report.sram, sram_check(self))
+    }
+
+    fn check_oscillator<DL: DelayNs>(&mut self, delay: &mut DL) -> Result<CheckResult, Error<E>> {
+        let before = match self.read_time_registers()? {
+            Some(before) => before,
+            None => return Ok(CheckResult::Failed),
+        };
+        delay.delay_ms(OSCILLATOR_WAIT_MS);
+        let after = match self.read_time_registers()? {
+            Some(after) => after,
+            None => return Ok(CheckResult::Failed),
+        };
+        Ok(check_result(
+            OSCILLATOR_ADVANCE_S.contains(&(after - before).num_seconds()),
+        ))
+    }
+
+    // Read the date and time registers without any of the side effects of
+    // `datetime()`. Invalid contents are returned as `None`.
+    fn read_time_registers(&mut self) -> Result<Option<NaiveDateTime>, Error<E>> {
+        let mut data = [0; 8];
+        data[0] = Register::SECONDS;
+        self.iface.read_data(&mut data)?;
+        Ok(self.datetime_from_registers(&data).ok())
+    }
+
+    fn check_temperature<DL: DelayNs>(&mut self, delay: &mut DL) -> Result<CheckResult, Error<E>> {
+        self.convert_temperature()?;
+        for _ in 0..TEMP_CONV_POLLS {
+            delay.delay_ms(TEMP_CONV_POLL_MS);
+            let control = self.iface.read_register(Register::CONTROL)?;
+            if (control & BitFlags::TEMP_CONV) == 0 {
+                let temperature = self.temperature()?;
+                return Ok(check_result(TEMPERATURE_RANGE.contains(&temperature)));
+            }
+        }
+        Ok(CheckResult::Failed)
+    }
+
+    // Write and read back the patterns to the registers starting at
+    // `register` and restore their original contents.
+    fn check_registers<const N: usize>(
+        &mut self,
+        register: u8,
+        patterns: &[[u8; N]],
+    ) -> Result<CheckResult, Error<E>> {
+        let mut original = [0; 8];
+        original[0] = register;
+        self.iface.read_data(&mut original[..=N])?;
+        let result = self.write_patterns(register, patterns);
+        original[0] = register;
+        let restored = self.iface.write_data(&mut original[..=N]);
+        if register == Register::AGING_OFFSET {
+            self.aging_offset = original[1] as i8;
+        }
+        let result = result?;
+        restored?;
+        Ok(result)
+    }
+
+    fn write_patterns<const N: usize>(
+        &mut self,
+        register: u8,
+        patterns: &[[u8; N]],
+    ) -> Result<CheckResult, Error<E>> {
+        for pattern in patterns {
+            let mut payload = [0; 8];
+            payload[0] = register;
+            payload[1..=N].copy_from_slice(pattern);
+            self.iface.write_data(&mut payload[..=N])?;
+            match self.verify_registers(register, pattern) {
+                Ok(()) => (),
+                Err(Error::VerificationFailed { .. }) => return Ok(CheckResult::Failed),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(CheckResult::Passed)
+    }
+}
+
+// Write and read back the patterns to the whole SRAM and restore its
+// original contents.
+pub(crate) fn check_sram<S, E>(sram: &mut S) -> Result<CheckResult, Error<E>>
+where
+    S: Sram<Error = Error<E>>,
+{
+    let size = sram.sram_size();
+    let mut original = [0; MAX_SRAM_SIZE];
+    let original = &mut original[..size];
+    sram.read_sram(0, original)?;
+    let result = write_sram_patterns(sram, size);
+    let restored = sram.write_sram(0, original);
+    let result = result?;
+    restored?;
+    Ok(result)
+}
+
+fn write_sram_patterns<S, E>(sram: &mut S, size: usize) -> Result<CheckResult, Error<E>>
+where
+    S: Sram<Error = Error<E>>,
+{
+    let mut data = [0; MAX_SRAM_SIZE];
+    let data = &mut data[..size];
+    for pattern in SRAM_PATTERNS {
+        data.fill(pattern);
+        sram.write_sram(0, data)?;
+        sram.read_sram(0, data)?;
+        if data.iter().any(|&value| value != pattern) {
+            return Ok(CheckResult::Failed);
+        }
+    }
+    Ok(CheckResult::Passed)
+}
+
+// Store the result of a check. A bus error is recorded as a failure.
+fn record<E>(
+    check: &mut CheckResult,
+    result: Result<CheckResult, Error<E>>,
+) -> Result<(), Error<E>> {
+    match result {
+        Ok(result) => {
+            *check = result;
+            Ok(())
+        }
+        Err(error) => {
+            *check = CheckResult::Failed;
+            Err(error)
+        }
+    }
+}
+
+fn check_result(passed: bool) -> CheckResult {
+    if passed {
+        CheckResult::Passed
+    } else {
+        CheckResult::Failed
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     BitFlags, Ds323x, Error, Register,
 };
 
-impl<DI, IC, E> Ds323x<DI, IC>
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
 where
     DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
 {
@@ -56,7 +56,7 @@ where
     /// See also: [`has_alarm1_matched()`](#method.has_alarm1_matched)
     pub fn clear_alarm1_matched_flag(&mut self) -> Result<(), Error<E>> {
         let status = self.status | BitFlags::ALARM2F;
-        self.iface.write_register(Register::STATUS, status)
+        self.write_status(status)
     }
 
     /// Read whether the Alarm2 has matched at some point.
@@ -73,7 +73,7 @@ where
     /// See also: [`has_alarm2_matched()`](#method.has_alarm2_matched)
     pub fn clear_alarm2_matched_flag(&mut self) -> Result<(), Error<E>> {
         let status = self.status | BitFlags::ALARM1F;
-        self.iface.write_register(Register::STATUS, status)
+        self.write_status(status)
     }
 
     /// Read the temperature.
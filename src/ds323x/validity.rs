@@ -0,0 +1,155 @@
+//! Validity of the date and time
+
+use crate::{
+    crc::crc8,
+    interface::{ReadData, WriteData},
+    DateTimeAccess, Datelike, Ds323x, Error, NaiveDate, NaiveDateTime, Register, TimeSetMarker,
+    TimeValidity, Timelike, TIME_SET_MARKER_SIZE,
+};
+
+const MARKER_MAGIC: u8 = 0x54;
+
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Assess whether the date and time kept by the device can be trusted.
+    ///
+    /// The checks are done in this order and the first one failing is
+    /// returned:
+    /// - If the time-set marker is enabled (DS3232 and DS3234), whether it
+    ///   contains a valid date and time. See `enable_time_set_marker()`.
+    /// - Whether the oscillator is stopped or has been stopped at some point.
+    ///   See [`has_been_stopped()`](#method.has_been_stopped).
+    /// - Whether the oscillator is running. See [`running()`](#method.running).
+    /// - Whether the date and time registers contain a valid date and time
+    ///   and, if the time-set marker is enabled, whether they are not earlier
+    ///   than the date and time stored in the marker.
+    pub fn time_validity(&mut self) -> Result<TimeValidity, Error<E>> {
+        let set_at = match self.time_set_marker {
+            Some(marker) => match self.read_time_set_marker(marker)? {
+                Some(set_at) => Some(set_at),
+                None => return Ok(TimeValidity::NeverSet),
+            },
+            None => None,
+        };
+        if self.has_been_stopped()? {
+            return Ok(TimeValidity::OscillatorStopped);
+        }
+        if !self.running()? {
+            return Ok(TimeValidity::OscillatorDisabled);
+        }
+        match self.datetime() {
+            Ok(now) if set_at.map_or(true, |set_at| now >= set_at) => Ok(TimeValidity::Valid),
+            Ok(_) | Err(Error::InvalidDeviceState { .. }) => Ok(TimeValidity::ImplausibleDate),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Clear the oscillator-stop flag if the date and time have been set.
+    ///
+    /// The flag is only cleared after a successful call to
+    /// [`set_datetime()`](#method.set_datetime) with this driver instance,
+    /// so that a stopped oscillator is not hidden before the time has been
+    /// corrected.
+    ///
+    /// Returns whether the flag was cleared.
+    ///
+    /// See also: [`time_validity()`](#method.time_validity)
+    pub fn mark_time_valid(&mut self) -> Result<bool, Error<E>> {
+        if !self.time_set {
+            return Ok(false);
+        }
+        self.clear_has_been_stopped_flag()?;
+        Ok(true)
+    }
+
+    pub(crate) fn write_time_set_marker(
+        &mut self,
+        datetime: &NaiveDateTime,
+    ) -> Result<(), Error<E>> {
+        let marker = match self.time_set_marker {
+            Some(marker) => marker,
+            None => return Ok(()),
+        };
+        let year = (datetime.year() as u16).to_be_bytes();
+        let mut payload = [
+            0,
+            MARKER_MAGIC,
+            year[0],
+            year[1],
+            datetime.month() as u8,
+            datetime.day() as u8,
+            datetime.hour() as u8,
+            datetime.minute() as u8,
+            datetime.second() as u8,
+            0,
+        ];
+        payload[TIME_SET_MARKER_SIZE] = crc8(&payload[1..TIME_SET_MARKER_SIZE]);
+        payload[0] = self.select_time_set_marker(marker)?;
+        self.iface.write_data(&mut payload)?;
+        if self.write_verification {
+            let mut actual = [0; TIME_SET_MARKER_SIZE + 1];
+            actual[0] = self.select_time_set_marker(marker)?;
+            self.iface.read_data(&mut actual)?;
+            let expected = &payload[1..];
+            if let Some(index) = expected.iter().zip(&actual[1..]).position(|(e, a)| e != a) {
+                return Err(match marker {
+                    TimeSetMarker::Register(register) => Error::VerificationFailed {
+                        register: register + index as u8,
+                    },
+                    TimeSetMarker::Indirect(offset) => Error::SramVerificationFailed {
+                        offset: offset + index as u8,
+                    },
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Write the date and time of the device to the time-set marker after
+    // setting part of them. The marker is left unchanged if the date and
+    // time are not valid, e.g. while setting the date field by field.
+    pub(crate) fn refresh_time_set_marker(&mut self) -> Result<(), Error<E>> {
+        if self.time_set_marker.is_none() {
+            return Ok(());
+        }
+        match self.datetime() {
+            Ok(datetime) => self.write_time_set_marker(&datetime),
+            Err(Error::InvalidDeviceState { .. }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read_time_set_marker(
+        &mut self,
+        marker: TimeSetMarker,
+    ) -> Result<Option<NaiveDateTime>, Error<E>> {
+        let mut data = [0; TIME_SET_MARKER_SIZE + 1];
+        data[0] = self.select_time_set_marker(marker)?;
+        self.iface.read_data(&mut data)?;
+        let data = &data[1..];
+        if data[0] != MARKER_MAGIC || crc8(&data[..TIME_SET_MARKER_SIZE - 1]) != data[8] {
+            return Ok(None);
+        }
+        let year = u16::from_be_bytes([data[1], data[2]]);
+        Ok(
+            NaiveDate::from_ymd_opt(i32::from(year), u32::from(data[3]), u32::from(data[4]))
+                .and_then(|date| {
+                    date.and_hms_opt(u32::from(data[5]), u32::from(data[6]), u32::from(data[7]))
+                }),
+        )
+    }
+
+    // Prepare the access to the marker and return the register to read or
+    // write its contents from.
+    fn select_time_set_marker(&mut self, marker: TimeSetMarker) -> Result<u8, Error<E>> {
+        match marker {
+            TimeSetMarker::Register(register) => Ok(register),
+            TimeSetMarker::Indirect(offset) => {
+                self.iface.write_register(Register::SRAM_ADDRESS, offset)?;
+                Ok(Register::SRAM_DATA)
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Verification of the written configuration
+
+use crate::{
+    interface::{ReadData, WriteData},
+    BitFlags, Ds323x, Error, Register,
+};
+
+// Bits of the control register that are not changed by the device
+const CONTROL_VERIFY_MASK: u8 = !BitFlags::TEMP_CONV;
+// Bits of the status register that are not changed by the device
+const STATUS_VERIFY_MASK: u8 =
+    BitFlags::BB32KHZ | BitFlags::CRATE1 | BitFlags::CRATE0 | BitFlags::EN32KHZ;
+// Flags of the status register that are not kept in the cache
+const STATUS_FLAGS: u8 = BitFlags::BUSY | BitFlags::ALARM2F | BitFlags::ALARM1F;
+// Number of bytes read back at once
+const CHUNK_SIZE: usize = 16;
+
+impl<DI, IC, MODE, E> Ds323x<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    /// Read back and compare the control, status, aging offset, alarm and
+    /// SRAM registers after writing them.
+    ///
+    /// When enabled, an `Error::VerificationFailed` is returned if a
+    /// register does not contain the written value. The bits changed by the
+    /// device (the temperature conversion bit, the busy and oscillator-stop
+    /// flags and the alarm flags) are not compared. The cached values of the
+    /// control and status registers are updated from the values read back.
+    pub fn enable_write_verification(&mut self) {
+        self.write_verification = true;
+    }
+
+    /// Do not read back the written registers. (default)
+    pub fn disable_write_verification(&mut self) {
+        self.write_verification = false;
+    }
+
+    // Write the control register and update the cached value.
+    pub(crate) fn write_control(&mut self, control: u8) -> Result<(), Error<E>> {
+        self.iface.write_register(Register::CONTROL, control)?;
+        self.control = control;
+        if self.write_verification {
+            let actual = self.iface.read_register(Register::CONTROL)?;
+            // do not keep a conversion request
+            self.control = actual & !BitFlags::TEMP_CONV;
+            check_register(Register::CONTROL, control, actual, CONTROL_VERIFY_MASK)?;
+        }
+        Ok(())
+    }
+
+    // Write the status register and update the cached value, which does not
+    // contain the alarm flags.
+    pub(crate) fn write_status(&mut self, status: u8) -> Result<(), Error<E>> {
+        self.iface.write_register(Register::STATUS, status)?;
+        self.status = status & !(BitFlags::ALARM2F | BitFlags::ALARM1F);
+        if self.write_verification {
+            let actual = self.iface.read_register(Register::STATUS)?;
+            self.status = actual & !STATUS_FLAGS;
+            check_register(Register::STATUS, status, actual, STATUS_VERIFY_MASK)?;
+        }
+        Ok(())
+    }
+
+    // Write a register and verify all of its bits.
+    pub(crate) fn write_register_verified(
+        &mut self,
+        register: u8,
+        value: u8,
+    ) -> Result<(), Error<E>> {
+        self.iface.write_register(register, value)?;
+        if self.write_verification {
+            let actual = self.iface.read_register(register)?;
+            check_register(register, value, actual, 0xFF)?;
+        }
+        Ok(())
+    }
+
+    // Write data and verify all bits of the registers.
+    // The first element corresponds to the starting address.
+    pub(crate) fn write_data_verified(&mut self, payload: &mut [u8]) -> Result<(), Error<E>> {
+        let register = payload[0];
+        self.iface.write_data(payload)?;
+        if self.write_verification {
+            self.verify_registers(register, &payload[1..])?;
+        }
+        Ok(())
+    }
+
+    // Compare consecutive registers starting at `register` with the data.
+    pub(crate) fn verify_registers(
+        &mut self,
+        register: u8,
+        expected: &[u8],
+    ) -> Result<(), Error<E>> {
+        let iface = &mut self.iface;
+        let index = first_mismatch(expected, |start, data| {
+            let mut buffer = [0; CHUNK_SIZE + 1];
+            buffer[0] = register + start as u8;
+            let buffer = &mut buffer[..=data.len()];
+            iface.read_data(buffer)?;
+            data.copy_from_slice(&buffer[1..]);
+            Ok(())
+        })?;
+        match index {
+            Some(index) => Err(Error::VerificationFailed {
+                register: register + index as u8,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+// Compare the bits in `mask` of the written and read values of a register.
+fn check_register<E>(register: u8, expected: u8, actual: u8, mask: u8) -> Result<(), Error<E>> {
+    if (expected ^ actual) & mask == 0 {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailed { register })
+    }
+}
+
+// Read back the data in chunks and return the index of the first byte that
+// differs. `read` fills the buffer with the data starting at the index.
+pub(crate) fn first_mismatch<E>(
+    expected: &[u8],
+    mut read: impl FnMut(usize, &mut [u8]) -> Result<(), E>,
+) -> Result<Option<usize>, E> {
+    let mut actual = [0; CHUNK_SIZE];
+    for (chunk_index, chunk) in expected.chunks(CHUNK_SIZE).enumerate() {
+        let start = chunk_index * CHUNK_SIZE;
+        let actual = &mut actual[..chunk.len()];
+        read(start, actual)?;
+        if let Some(index) = chunk.iter().zip(actual.iter()).position(|(e, a)| e != a) {
+            return Ok(Some(start + index));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,183 @@
+//! Error details, formatting and classification
+
+use crate::Error;
+use core::fmt::{self, Debug, Display};
+use embedded_hal::{i2c, spi};
+
+/// Input parameter that was out of range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InputField {
+    /// Second [0-59]
+    Second,
+    /// Minute [0-59]
+    Minute,
+    /// Hour [0-23] in 24-hour format or [1-12] in 12-hour format
+    Hour,
+    /// Day of the week [1-7]
+    Weekday,
+    /// Day of the month [1-31]
+    Day,
+    /// Month [1-12]
+    Month,
+    /// Year, which must be in the range covered by the century base
+    Year,
+    /// Century base, which must be a multiple of 100 not greater than 65300
+    CenturyBase,
+    /// Offset and length of an SRAM access, which must fit in the SRAM
+    SramRange,
+    /// Size of a record, which must not be greater than
+    /// [`RecordStore::MAX_RECORD_SIZE`](crate::RecordStore::MAX_RECORD_SIZE)
+    RecordSize,
+    /// Capacity of an event log, which must not be zero
+    EventLogCapacity,
+    /// Code of a custom event, which must be at least 0x10
+    EventCode,
+    /// Timestamp of an event log entry, which must be from 2000 until 2136
+    EventTimestamp,
+    /// Range to erase, which must be aligned to the erase size
+    EraseRange,
+    /// Register, which must be available on the device
+    Register,
+}
+
+impl Display for InputField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            InputField::Second => "second out of range [0-59]",
+            InputField::Minute => "minute out of range [0-59]",
+            InputField::Hour => "hour out of range [0-23] or [1-12] in 12-hour format",
+            InputField::Weekday => "day of the week out of range [1-7]",
+            InputField::Day => "day of the month out of range [1-31]",
+            InputField::Month => "month out of range [1-12]",
+            InputField::Year => "year out of the range of the century base",
+            InputField::CenturyBase => "century base not a multiple of 100 up to 65300",
+            InputField::SramRange => "data does not fit in the SRAM",
+            InputField::RecordSize => "record too large",
+            InputField::EventLogCapacity => "event log capacity is zero",
+            InputField::EventCode => "reserved custom event code",
+            InputField::EventTimestamp => "event timestamp out of range [2000-2136]",
+            InputField::EraseRange => "erase range not aligned to the erase size",
+            InputField::Register => "register not available on the device",
+        };
+        f.write_str(description)
+    }
+}
+
+impl<E: Debug> Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Comm(e) => write!(f, "communication error: {:?}", e),
+            Error::InvalidInputData { field } => write!(f, "invalid input data: {}", field),
+            Error::InvalidDeviceState { register } => {
+                write!(f, "invalid device state in register 0x{:02X}", register)
+            }
+            Error::VerificationFailed { register } => {
+                write!(f, "verification failed in register 0x{:02X}", register)
+            }
+            Error::SramVerificationFailed { offset } => {
+                write!(f, "verification failed at SRAM address 0x{:02X}", offset)
+            }
+        }
+    }
+}
+
+impl<E: Debug> core::error::Error for Error<E> {}
+
+/// Classification of an error to decide whether to retry an operation
+///
+/// See [`Error::i2c_class()`] and [`Error::spi_class()`].
+///
+/// There is no timeout class since `embedded-hal` has no timeout error kind.
+/// Bus implementations report timeouts with their own error kinds, which are
+/// classified as [`ErrorClass::Other`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ErrorClass {
+    /// The device did not acknowledge its address (I²C).
+    ///
+    /// The device may be absent or temporarily unavailable.
+    NoAcknowledge,
+    /// The device did not acknowledge some data (I²C).
+    ///
+    /// Part of the data may already have been written.
+    DataNotAcknowledged,
+    /// Another controller won the arbitration of the bus (I²C).
+    ArbitrationLoss,
+    /// Bus problem such as a bus error or an overrun
+    Bus,
+    /// Other communication error
+    Other,
+    /// Invalid input data. Retrying with the same input fails again.
+    InvalidInput,
+    /// Invalid device state. The device probably needs to be initialized.
+    InvalidDeviceState,
+    /// A register did not contain the written value when read back.
+    VerificationFailed,
+}
+
+impl ErrorClass {
+    /// Whether retrying the operation may succeed without side effects
+    ///
+    /// This is the case for a missing address acknowledgement and an
+    /// arbitration loss. A missing data acknowledgement is only retryable
+    /// if the operation did not write to the device (`is_write` is `false`),
+    /// since part of the data may already have been written. Bus problems
+    /// may have corrupted a transfer partway and are not retryable.
+    ///
+    /// The retry policy of the I²C interface follows the same rule.
+    /// See [`RetryPolicy`](crate::interface::RetryPolicy).
+    pub fn is_retryable(self, is_write: bool) -> bool {
+        match self {
+            ErrorClass::NoAcknowledge | ErrorClass::ArbitrationLoss => true,
+            ErrorClass::DataNotAcknowledged => !is_write,
+            _ => false,
+        }
+    }
+}
+
+// Classify an I²C error kind.
+pub(crate) fn i2c_kind_class(kind: i2c::ErrorKind) -> ErrorClass {
+    match kind {
+        i2c::ErrorKind::NoAcknowledge(i2c::NoAcknowledgeSource::Data) => {
+            ErrorClass::DataNotAcknowledged
+        }
+        i2c::ErrorKind::NoAcknowledge(_) => ErrorClass::NoAcknowledge,
+        i2c::ErrorKind::ArbitrationLoss => ErrorClass::ArbitrationLoss,
+        i2c::ErrorKind::Bus | i2c::ErrorKind::Overrun => ErrorClass::Bus,
+        _ => ErrorClass::Other,
+    }
+}
+
+impl<E> Error<E> {
+    fn class(&self, comm: impl FnOnce(&E) -> ErrorClass) -> ErrorClass {
+        match self {
+            Error::Comm(e) => comm(e),
+            Error::InvalidInputData { .. } => ErrorClass::InvalidInput,
+            Error::InvalidDeviceState { .. } => ErrorClass::InvalidDeviceState,
+            Error::VerificationFailed { .. } | Error::SramVerificationFailed { .. } => {
+                ErrorClass::VerificationFailed
+            }
+        }
+    }
+}
+
+impl<E: i2c::Error> Error<E> {
+    /// Classify an error of a device connected through I²C.
+    pub fn i2c_class(&self) -> ErrorClass {
+        self.class(|e| i2c_kind_class(e.kind()))
+    }
+}
+
+impl<E: spi::Error> Error<E> {
+    /// Classify an error of a device connected through SPI.
+    pub fn spi_class(&self) -> ErrorClass {
+        self.class(|e| match e.kind() {
+            spi::ErrorKind::Overrun
+            | spi::ErrorKind::ModeFault
+            | spi::ErrorKind::FrameFormat
+            | spi::ErrorKind::ChipSelectFault => ErrorClass::Bus,
+            _ => ErrorClass::Other,
+        })
+    }
+}
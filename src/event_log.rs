@@ -0,0 +1,336 @@
+//! Circular event log in the battery-backed SRAM
+
+use crate::{crc::crc8, record::Sram, DateTimeAccess, Error, InputField, NaiveDate, NaiveDateTime};
+use chrono::TimeDelta;
+
+/// Event stored in an [`EventLog`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    /// The system booted
+    Boot,
+    /// A brownout was detected
+    Brownout,
+    /// The system was reset by the watchdog
+    WatchdogReset,
+    /// An alarm fired
+    AlarmFired,
+    /// Application-defined event.
+    ///
+    /// The code must be at least 0x10. Lower codes are reserved.
+    Custom(u8),
+}
+
+impl Event {
+    fn to_code(self) -> Option<u8> {
+        match self {
+            Event::Boot => Some(1),
+            Event::Brownout => Some(2),
+            Event::WatchdogReset => Some(3),
+            Event::AlarmFired => Some(4),
+            Event::Custom(code) if code >= FIRST_CUSTOM_CODE => Some(code),
+            Event::Custom(_) => None,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(Event::Boot),
+            2 => Some(Event::Brownout),
+            3 => Some(Event::WatchdogReset),
+            4 => Some(Event::AlarmFired),
+            FIRST_CUSTOM_CODE..=0xFF => Some(Event::Custom(code)),
+            _ => None,
+        }
+    }
+}
+
+/// Entry of an [`EventLog`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LogEntry {
+    /// Time at which the event happened
+    pub timestamp: NaiveDateTime,
+    /// Event
+    pub event: Event,
+}
+
+const FIRST_CUSTOM_CODE: u8 = 0x10;
+const ENTRY_SIZE: usize = 8;
+
+/// Circular log of timestamped events in the battery-backed SRAM
+///
+/// Each entry takes 8 bytes and contains a sequence number, the timestamp
+/// with a resolution of one second, the event and a CRC-8. Timestamps
+/// from 2000 until 2136 can be stored.
+/// When the log is full, the oldest entry is overwritten.
+///
+/// No head pointer is stored. The position of the newest entry is
+/// recovered from the sequence numbers when opening the log, so a
+/// corrupted entry only causes the loss of that entry and the entries older
+/// than it.
+///
+/// The log holds the SRAM instance, which can be a mutable reference to the
+/// device, and returns it with [`destroy()`](#method.destroy).
+///
+/// ```no_run
+/// use ds323x::{Ds323x, Event, EventLog};
+/// use linux_embedded_hal::I2cdev;
+///
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let mut rtc = Ds323x::new_ds3232(dev);
+/// // 16 entries starting at SRAM offset 64
+/// let mut log = EventLog::open(&mut rtc, 64, 16).unwrap();
+/// log.append_now(Event::Boot).unwrap();
+/// for entry in log.iter() {
+///     let entry = entry.unwrap();
+///     println!("{}: {:?}", entry.timestamp, entry.event);
+/// }
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EventLog<S> {
+    sram: S,
+    offset: u8,
+    capacity: u8,
+    head: u8,
+    len: u8,
+    next_seq: u16,
+}
+
+impl<S, E> EventLog<S>
+where
+    S: Sram<Error = Error<E>>,
+{
+    /// Open the event log stored in `capacity` entries starting at the
+    /// SRAM `offset` and recover the position of the newest entry.
+    ///
+    /// Will return `Error::InvalidInputData` if the capacity is zero or the
+    /// log does not fit in the SRAM.
+    pub fn open(sram: S, offset: u8, capacity: u8) -> Result<Self, Error<E>> {
+        if capacity == 0 {
+            return Err(Error::InvalidInputData {
+                field: InputField::EventLogCapacity,
+            });
+        }
+        if usize::from(offset) + usize::from(capacity) * ENTRY_SIZE > sram.sram_size() {
+            return Err(Error::InvalidInputData {
+                field: InputField::SramRange,
+            });
+        }
+        let mut log = EventLog {
+            sram,
+            offset,
+            capacity,
+            head: 0,
+            len: 0,
+            next_seq: 0,
+        };
+        let mut newest: Option<(u8, u16)> = None;
+        for index in 0..capacity {
+            if let Some((seq, _)) = log.read_entry(index)? {
+                match newest {
+                    Some((_, newest_seq)) if !is_newer(seq, newest_seq) => (),
+                    _ => newest = Some((index, seq)),
+                }
+            }
+        }
+        if let Some((index, seq)) = newest {
+            log.head = (index + 1) % capacity;
+            log.next_seq = seq.wrapping_add(1);
+            log.len = 1;
+            // Count the consecutive entries before the newest one.
+            while log.len < capacity {
+                let index = log.index_from_newest(log.len);
+                match log.read_entry(index)? {
+                    Some((s, _)) if s == seq.wrapping_sub(u16::from(log.len)) => log.len += 1,
+                    _ => break,
+                }
+            }
+        }
+        Ok(log)
+    }
+
+    /// Destroy the event log and return the SRAM instance.
+    pub fn destroy(self) -> S {
+        self.sram
+    }
+
+    /// Number of entries in the log
+    pub fn len(&self) -> usize {
+        usize::from(self.len)
+    }
+
+    /// Whether the log is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Maximum number of entries in the log
+    pub fn capacity(&self) -> usize {
+        usize::from(self.capacity)
+    }
+
+    /// Append an event, overwriting the oldest entry if the log is full.
+    ///
+    /// Will return `Error::InvalidInputData` if the timestamp cannot be
+    /// stored or the code of a custom event is reserved.
+    pub fn append(&mut self, timestamp: NaiveDateTime, event: Event) -> Result<(), Error<E>> {
+        let code = event.to_code().ok_or(Error::InvalidInputData {
+            field: InputField::EventCode,
+        })?;
+        let seconds = (timestamp - epoch()).num_seconds();
+        if !(0..=i64::from(u32::MAX)).contains(&seconds) {
+            return Err(Error::InvalidInputData {
+                field: InputField::EventTimestamp,
+            });
+        }
+        let mut data = [0; ENTRY_SIZE];
+        data[..2].copy_from_slice(&self.next_seq.to_be_bytes());
+        data[2..6].copy_from_slice(&(seconds as u32).to_be_bytes());
+        data[6] = code;
+        data[7] = crc8(&data[..7]);
+        self.sram.write_sram(self.entry_offset(self.head), &data)?;
+        self.head = (self.head + 1) % self.capacity;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        if self.len < self.capacity {
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Append an event with the current date and time read from the device.
+    ///
+    /// See [`append()`](#method.append).
+    pub fn append_now(&mut self, event: Event) -> Result<(), Error<E>>
+    where
+        S: DateTimeAccess<Error = Error<E>>,
+    {
+        let timestamp = self.sram.datetime()?;
+        self.append(timestamp, event)
+    }
+
+    /// Iterate over the entries from the newest to the oldest.
+    ///
+    /// The iteration stops early if an entry has been corrupted since the
+    /// log was opened.
+    pub fn iter(&mut self) -> EventLogIter<'_, S> {
+        EventLogIter {
+            log: self,
+            position: 0,
+        }
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) -> Result<(), Error<E>> {
+        let size = self.capacity() * ENTRY_SIZE;
+        self.sram.write_sram(self.offset, &[0; 256][..size])?;
+        self.head = 0;
+        self.len = 0;
+        Ok(())
+    }
+
+    fn entry_offset(&self, index: u8) -> u8 {
+        self.offset + index * ENTRY_SIZE as u8
+    }
+
+    // Index of the entry `age` positions before the newest one.
+    fn index_from_newest(&self, age: u8) -> u8 {
+        let capacity = u16::from(self.capacity);
+        ((u16::from(self.head) + 2 * capacity - 1 - u16::from(age)) % capacity) as u8
+    }
+
+    // Read an entry and return its sequence number and contents if it is valid.
+    fn read_entry(&mut self, index: u8) -> Result<Option<(u16, LogEntry)>, Error<E>> {
+        let mut data = [0; ENTRY_SIZE];
+        self.sram.read_sram(self.entry_offset(index), &mut data)?;
+        if crc8(&data[..7]) != data[7] {
+            return Ok(None);
+        }
+        let event = match Event::from_code(data[6]) {
+            Some(event) => event,
+            None => return Ok(None),
+        };
+        let seconds = u32::from_be_bytes([data[2], data[3], data[4], data[5]]);
+        let timestamp = epoch() + TimeDelta::seconds(i64::from(seconds));
+        let seq = u16::from_be_bytes([data[0], data[1]]);
+        Ok(Some((seq, LogEntry { timestamp, event })))
+    }
+}
+
+/// Iterator over the entries of an [`EventLog`] from the newest to the oldest
+#[derive(Debug)]
+pub struct EventLogIter<'a, S> {
+    log: &'a mut EventLog<S>,
+    position: u8,
+}
+
+impl<S, E> Iterator for EventLogIter<'_, S>
+where
+    S: Sram<Error = Error<E>>,
+{
+    type Item = Result<LogEntry, Error<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.log.len {
+            return None;
+        }
+        let index = self.log.index_from_newest(self.position);
+        let expected_seq = self
+            .log
+            .next_seq
+            .wrapping_sub(1)
+            .wrapping_sub(u16::from(self.position));
+        match self.log.read_entry(index) {
+            Ok(Some((seq, entry))) if seq == expected_seq => {
+                self.position += 1;
+                Some(Ok(entry))
+            }
+            Ok(_) => {
+                self.position = self.log.len;
+                None
+            }
+            Err(e) => {
+                self.position = self.log.len;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+// Whether the sequence number `a` is newer than `b`, allowing for wrap-around.
+fn is_newer(a: u16, b: u16) -> bool {
+    (a.wrapping_sub(b) as i16) > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_numbers_wrap_around() {
+        assert!(is_newer(1, 0));
+        assert!(!is_newer(0, 1));
+        assert!(is_newer(0, 0xFFFF));
+        assert!(!is_newer(0xFFFF, 0));
+    }
+
+    #[test]
+    fn event_codes() {
+        for code in 0..=0xFF {
+            if let Some(event) = Event::from_code(code) {
+                assert_eq!(Some(code), event.to_code());
+            }
+        }
+        assert_eq!(None, Event::from_code(0));
+        assert_eq!(None, Event::from_code(5));
+        assert_eq!(None, Event::Custom(0x0F).to_code());
+    }
+}
@@ -1,13 +1,120 @@
 //! I2C/SPI interfaces
 
-use crate::{private, Error, DEVICE_ADDRESS};
-use embedded_hal::{i2c, spi};
+use crate::{error::i2c_kind_class, private, Error, DEVICE_ADDRESS};
+use embedded_hal::{
+    delay::DelayNs,
+    i2c::{self, Operation},
+    spi,
+};
 
 /// I2C interface
+///
+/// Bus operations failing with a transient error are retried according to
+/// the [`RetryPolicy`], waiting between attempts with the delay `D`.
 #[derive(Debug, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct I2cInterface<I2C> {
+pub struct I2cInterface<I2C, D = NoDelay> {
     pub(crate) i2c: I2C,
+    pub(crate) delay: D,
+    pub(crate) retry_policy: RetryPolicy,
+}
+
+impl<I2C> I2cInterface<I2C> {
+    pub(crate) fn new(i2c: I2C) -> Self {
+        I2cInterface {
+            i2c,
+            delay: NoDelay,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
+
+/// Retry policy for transient I²C bus errors
+///
+/// An operation is retried if [`ErrorClass::is_retryable()`](crate::ErrorClass::is_retryable)
+/// is true for its error: if the device did not acknowledge it or if the
+/// arbitration was lost. A write is not retried if the device did not
+/// acknowledge the data, since part of it may already have been written.
+///
+/// The default policy makes a single attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RetryPolicy {
+    /// Maximum number of attempts of each bus operation, including the first one
+    pub attempts: u8,
+    /// Time to wait before each retry in microseconds
+    pub backoff_us: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 1,
+            backoff_us: 0,
+        }
+    }
+}
+
+/// Delay that does not wait
+///
+/// Used when no delay was provided for the backoff between retries.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoDelay;
+
+impl DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+// Whether an operation that failed with this error may be retried.
+fn is_transient<E: i2c::Error>(error: &E, is_write: bool) -> bool {
+    i2c_kind_class(error.kind()).is_retryable(is_write)
+}
+
+impl<I2C, D> I2cInterface<I2C, D>
+where
+    I2C: i2c::I2c,
+    D: DelayNs,
+{
+    fn with_retries<T>(
+        &mut self,
+        is_write: bool,
+        mut operation: impl FnMut(&mut I2C) -> Result<T, I2C::Error>,
+    ) -> Result<T, Error<I2C::Error>> {
+        let mut attempt = 1;
+        loop {
+            match operation(&mut self.i2c) {
+                Err(e) if attempt < self.retry_policy.attempts && is_transient(&e, is_write) => {
+                    attempt += 1;
+                    if self.retry_policy.backoff_us > 0 {
+                        self.delay.delay_us(self.retry_policy.backoff_us);
+                    }
+                }
+                result => return result.map_err(Error::Comm),
+            }
+        }
+    }
+
+    // Read data starting at the register directly into the buffer.
+    pub(crate) fn read_at(
+        &mut self,
+        register: u8,
+        data: &mut [u8],
+    ) -> Result<(), Error<I2C::Error>> {
+        self.with_retries(false, |i2c| {
+            i2c.write_read(DEVICE_ADDRESS, &[register], data)
+        })
+    }
+
+    // Write data starting at the register directly from the buffer.
+    pub(crate) fn write_at(&mut self, register: u8, data: &[u8]) -> Result<(), Error<I2C::Error>> {
+        self.with_retries(true, |i2c| {
+            i2c.transaction(
+                DEVICE_ADDRESS,
+                &mut [Operation::Write(&[register]), Operation::Write(data)],
+            )
+        })
+    }
 }
 
 /// SPI interface
@@ -27,20 +134,19 @@ pub trait WriteData: private::Sealed {
     fn write_data(&mut self, payload: &mut [u8]) -> Result<(), Self::Error>;
 }
 
-impl<I2C, E> WriteData for I2cInterface<I2C>
+impl<I2C, D, E> WriteData for I2cInterface<I2C, D>
 where
     I2C: i2c::I2c<Error = E>,
+    D: DelayNs,
 {
     type Error = Error<E>;
     fn write_register(&mut self, register: u8, data: u8) -> Result<(), Self::Error> {
         let payload: [u8; 2] = [register, data];
-        self.i2c
-            .write(DEVICE_ADDRESS, &payload)
-            .map_err(Error::Comm)
+        self.with_retries(true, |i2c| i2c.write(DEVICE_ADDRESS, &payload))
     }
 
     fn write_data(&mut self, payload: &mut [u8]) -> Result<(), Self::Error> {
-        self.i2c.write(DEVICE_ADDRESS, payload).map_err(Error::Comm)
+        self.with_retries(true, |i2c| i2c.write(DEVICE_ADDRESS, payload))
     }
 }
 
@@ -70,24 +176,23 @@ pub trait ReadData: private::Sealed {
     fn read_data(&mut self, payload: &mut [u8]) -> Result<(), Self::Error>;
 }
 
-impl<I2C, E> ReadData for I2cInterface<I2C>
+impl<I2C, D, E> ReadData for I2cInterface<I2C, D>
 where
     I2C: i2c::I2c<Error = E>,
+    D: DelayNs,
 {
     type Error = Error<E>;
     fn read_register(&mut self, register: u8) -> Result<u8, Self::Error> {
         let mut data = [0];
-        self.i2c
-            .write_read(DEVICE_ADDRESS, &[register], &mut data)
-            .map_err(Error::Comm)
-            .and(Ok(data[0]))
+        self.with_retries(false, |i2c| {
+            i2c.write_read(DEVICE_ADDRESS, &[register], &mut data)
+        })
+        .and(Ok(data[0]))
     }
 
     fn read_data(&mut self, payload: &mut [u8]) -> Result<(), Self::Error> {
-        let len = payload.len();
-        self.i2c
-            .write_read(DEVICE_ADDRESS, &[payload[0]], &mut payload[1..len])
-            .map_err(Error::Comm)
+        let (register, data) = payload.split_at_mut(1);
+        self.with_retries(false, |i2c| i2c.write_read(DEVICE_ADDRESS, register, data))
     }
 }
 
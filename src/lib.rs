@@ -6,18 +6,43 @@
 //! This driver allows you to:
 //! - Read and set date and time in 12-hour and 24-hour format. See: [`datetime`].
 //! - Read and set date and time individual elements. For example, see: [`year`].
+//! - Read and set date and time as `DateTime<Utc>`. See: [`datetime_utc`].
+//!   With the `time` and `jiff` features, the types of these crates are
+//!   also supported. See: `primitive_datetime` and `civil_datetime`.
+//! - Configure the range of years stored in the device. See: [`set_century_base`].
+//! - Correct the non-existent 29th of February of years like 2100. See: [`correct_leap_day`].
+//! - Select the 12-hour or 24-hour format. See: [`set_hour_mode`].
+//! - Select the numbering of the days of the week. See: [`set_weekday_numbering`].
+//! - Verify and repair the day of the week. See: [`verify_weekday`].
 //! - Enable and disable the real-time clock. See: [`enable`].
 //! - Read the busy status. See [`busy`].
 //! - Read whether the oscillator is or has been stopped. See [`has_been_stopped`].
 //! - Clear the has-been-stopped flag. See [`clear_has_been_stopped_flag`].
+//! - Assess whether the date and time can be trusted. See [`time_validity`].
 //! - Set and read the aging offset. See [`set_aging_offset`].
 //! - Select the function of the INT/SQW output pin. See [`use_int_sqw_output_as_interrupt`].
+//! - Apply and read the whole device configuration at once. See [`apply`].
+//! - Track the INT/SQW output pin mode at compile time. See [`into_interrupt_mode`].
+//! - Minimize the current drawn from the battery. See [`prepare_for_battery_backup`].
+//! - Retry I²C operations failing with transient bus errors. See [`set_retry_policy`].
+//! - Read back and verify the written configuration. See [`enable_write_verification`].
+//! - Trace and record the register operations on the bus. See [`TracingI2c`].
+//! - Run a self-test for production testing. See [`self_test`].
+//! - Reset the device to the power-on defaults. See [`reset_to_defaults`].
+//! - Read and write any register for features not covered by the driver. See [`write_reg`].
+//! - Share the driver between interrupts and tasks. See `SharedDs323x`
+//!   (requires the `critical-section` feature).
+//! - Stamp `embedded-sdmmc` files with the date and time. See `Ds323xTimeSource`
+//!   (requires the `embedded-sdmmc` feature).
+//! - Serialize and deserialize the configuration and alarm types with validation
+//!   (requires the `serde` feature).
 //! - Alarms:
 //!     - Set alarms 1 and 2 with several matching policies. See [`set_alarm1_day`].
 //!     - Set alarms 1 and 2 for a time. See [`set_alarm1_hms`].
 //!     - Read whether alarms 1 or 2 have matched. See [`has_alarm1_matched`].
 //!     - Clear flag indicating that alarms 1 or 2 have matched. See [`clear_alarm1_matched_flag`].
 //!     - Enable and disable alarms 1 and 2 interrupt generation. See [`enable_alarm1_interrupts`].
+//!     - Wait for alarm interrupts on the INT/SQW pin. See [`wait_for_alarm_blocking`].
 //! - Wave generation:
 //!     - Enable and disable the square-wave generation. See [`enable_square_wave`].
 //!     - Select the square-wave frequency. See [`set_square_wave_frequency`].
@@ -28,18 +53,42 @@
 //!     - Force a temperature conversion and time compensation. See [`convert_temperature`].
 //!     - Set the temperature conversion rate. See [`set_temperature_conversion_rate`].
 //!     - Enable and disable the temperature conversions when battery-powered. See [`enable_temperature_conversions_on_battery`].
+//!     - Track the minimum, maximum and average temperature and threshold crossings. See [`TemperatureMonitor`].
+//! - Battery-backed SRAM:
+//!     - Read and write the SRAM. See [`read_sram`].
+//!     - Store versioned, CRC-protected records. See [`RecordStore`].
+//!     - Keep a circular log of timestamped events. See [`EventLog`].
+//!     - Keep the temperature statistics. See [`PersistentTemperatureMonitor`].
+//!     - Use the SRAM through the `embedded-storage` traits (requires the
+//!       `embedded-storage` feature).
 //!
 //! [`datetime`]: Ds323x::datetime
 //! [`year`]: Ds323x::year
+//! [`set_century_base`]: Ds323x::set_century_base
+//! [`correct_leap_day`]: Ds323x::correct_leap_day
+//! [`set_hour_mode`]: Ds323x::set_hour_mode
+//! [`set_weekday_numbering`]: Ds323x::set_weekday_numbering
+//! [`verify_weekday`]: Ds323x::verify_weekday
 //! [`enable`]: Ds323x::enable
 //! [`temperature`]: Ds323x::temperature
 //! [`convert_temperature`]: Ds323x::convert_temperature
 //! [`busy`]: Ds323x::busy
 //! [`has_been_stopped`]: Ds323x::has_been_stopped
 //! [`clear_has_been_stopped_flag`]: Ds323x::clear_has_been_stopped_flag
+//! [`datetime_utc`]: Ds323x::datetime_utc
+//! [`time_validity`]: Ds323x::time_validity
 //! [`set_aging_offset`]: Ds323x::set_aging_offset
 //! [`enable_32khz_output`]: Ds323x::enable_32khz_output
 //! [`use_int_sqw_output_as_interrupt`]: Ds323x::use_int_sqw_output_as_interrupt
+//! [`apply`]: Ds323x::apply
+//! [`into_interrupt_mode`]: Ds323x::into_interrupt_mode
+//! [`prepare_for_battery_backup`]: Ds323x::prepare_for_battery_backup
+//! [`set_retry_policy`]: Ds323x::set_retry_policy
+//! [`enable_write_verification`]: Ds323x::enable_write_verification
+//! [`TracingI2c`]: trace::TracingI2c
+//! [`self_test`]: Ds323x::self_test
+//! [`reset_to_defaults`]: Ds323x::reset_to_defaults
+//! [`write_reg`]: Ds323x::write_reg
 //! [`enable_square_wave`]: Ds323x::enable_square_wave
 //! [`set_square_wave_frequency`]: Ds323x::set_square_wave_frequency
 //! [`set_alarm1_day`]: Ds323x::set_alarm1_day
@@ -47,9 +96,11 @@
 //! [`has_alarm1_matched`]: Ds323x::has_alarm1_matched
 //! [`clear_alarm1_matched_flag`]: Ds323x::clear_alarm1_matched_flag
 //! [`enable_alarm1_interrupts`]: Ds323x::enable_alarm1_interrupts
+//! [`wait_for_alarm_blocking`]: Ds323x::wait_for_alarm_blocking
 //! [`enable_32khz_output_on_battery`]: Ds323x::enable_32khz_output_on_battery
 //! [`set_temperature_conversion_rate`]: Ds323x::set_temperature_conversion_rate
 //! [`enable_temperature_conversions_on_battery`]: Ds323x::enable_temperature_conversions_on_battery
+//! [`read_sram`]: Ds323x::read_sram
 //!
 //! ## The devices
 //!
@@ -112,6 +163,61 @@
 //! - [DS3232](https://datasheets.maximintegrated.com/en/ds/DS3232.pdf)
 //! - [DS3234](https://datasheets.maximintegrated.com/en/ds/DS3234.pdf)
 //!
+//! ## Configuration, reset and self-test
+//!
+//! The following behaviour is common to the three devices. The
+//! device-specific differences are noted on each method.
+//!
+//! ### Applying a configuration
+//!
+//! [`apply()`](Ds323x::apply) only writes the control, status and aging
+//! offset registers whose value differs from the [`Config`]. The comparison
+//! is done against the values cached by the driver, which correspond to the
+//! power-on reset state of the device until
+//! [`current_config()`](Ds323x::current_config) is called. This is only
+//! available in the [`mode::Unchecked`] mode, since the configuration
+//! includes the INT/SQW pin mode.
+//!
+//! ### Resetting to the defaults
+//!
+//! [`reset_to_defaults()`](Ds323x::reset_to_defaults) writes the power-on
+//! reset values of the control, status, aging offset and alarm registers.
+//! The alarm registers, which have no defined power-on reset value, and the
+//! alarm matched flags are cleared. The cleared alarm hours use the 24-hour
+//! format regardless of the format set with
+//! [`set_hour_mode()`](Ds323x::set_hour_mode), which is kept since the time
+//! registers are not modified. Setting an alarm writes its hour in the
+//! configured format again. The oscillator-stop flag can not be set again
+//! and is kept. The date and time are not modified. The values cached by the
+//! driver are updated.
+//!
+//! If `stop_oscillator_on_battery` is set in the [`ResetOptions`], the
+//! oscillator keeps running while powered from VCC but stops when
+//! battery-powered. This is only available in the [`mode::Unchecked`] mode,
+//! since the INT/SQW pin is reset to the interrupt output.
+//!
+//! ### Preparing for battery backup
+//!
+//! [`prepare_for_battery_backup()`](Ds323x::prepare_for_battery_backup)
+//! applies the configuration with the lowest current consumption when
+//! battery-powered. The battery-backed square-wave output is disabled unless
+//! it is needed for alarm interrupts and the alarms are not modified. The
+//! previous values of the changed settings are returned as a
+//! [`PowerProfile`], which can be restored with
+//! [`restore_power_profile()`](Ds323x::restore_power_profile).
+//!
+//! ### Running the self-test
+//!
+//! [`self_test()`](Ds323x::self_test) checks that the device responds, that
+//! the oscillator runs, that a forced temperature conversion gives a
+//! plausible temperature and that the aging offset and alarm registers can
+//! be written and read back. The delay is used to wait for the time to
+//! advance and for the end of the temperature conversion, so this takes
+//! about 1.2 seconds. The original contents of the written registers are
+//! restored afterwards and write verification is disabled during the test.
+//! See [`SelfTestReport`] for the checks. A bus error fails the running
+//! check and the remaining checks are skipped.
+//!
 //! ## Usage examples (see also examples folder)
 //!
 //! To use this driver, import this crate and an `embedded_hal` implementation,
@@ -299,6 +405,27 @@
 //! rtc.set_aging_offset(-15).unwrap();
 //! ```
 //!
+//! ### Apply a configuration in one call
+//!
+//! Only the registers that differ from the configuration are written.
+//!
+//! ```no_run
+//! use ds323x::{Config, Ds323x, IntSqwMode, TempConvRate};
+//! use linux_embedded_hal::I2cdev;
+//!
+//! let dev = I2cdev::new("/dev/i2c-1").unwrap();
+//! let mut rtc = Ds323x::new_ds3232(dev);
+//! let config = Config {
+//!     int_sqw_mode: IntSqwMode::Interrupt,
+//!     output_32khz: false,
+//!     alarm1_interrupts: true,
+//!     temperature_conversion_rate: TempConvRate::_256s,
+//!     ..Config::default()
+//! };
+//! rtc.apply(config).unwrap();
+//! assert_eq!(config, rtc.current_config().unwrap());
+//! ```
+//!
 //! ### Set the temperature conversion rate to once every 128 seconds
 //!
 //! This is only available for the devices DS3232 and DS3234.
@@ -312,16 +439,33 @@
 //! rtc.set_temperature_conversion_rate(TempConvRate::_128s).unwrap();
 //! ```
 //!
+//! ### Store data in the battery-backed SRAM
+//!
+//! This is only available for the devices DS3232 and DS3234.
+//! With the `embedded-storage` feature enabled, the SRAM can also be used
+//! through the `embedded-storage` traits.
+//!
+//! ```no_run
+//! use ds323x::Ds323x;
+//! use linux_embedded_hal::I2cdev;
+//!
+//! let dev = I2cdev::new("/dev/i2c-1").unwrap();
+//! let mut rtc = Ds323x::new_ds3232(dev);
+//! rtc.write_sram(0, &[1, 2, 3]).unwrap();
+//! let mut data = [0; 3];
+//! rtc.read_sram(0, &mut data).unwrap();
+//! ```
+//!
 //! ### Set the Alarm1 to each week on a week day at a specific time
 //!
 //! ```no_run
-//! use ds323x::{Ds323x, Hours, WeekdayAlarm1, Alarm1Matching};
+//! use ds323x::{Ds323x, Hours, Weekday, WeekdayAlarm1, Alarm1Matching};
 //! use linux_embedded_hal::I2cdev;
 //!
 //! let dev = I2cdev::new("/dev/i2c-1").unwrap();
 //! let mut rtc = Ds323x::new_ds3231(dev);
 //! let alarm1 = WeekdayAlarm1 {
-//!     weekday: 1,
+//!     weekday: Weekday::Mon,
 //!     hour: Hours::H24(7),
 //!     minute: 2,
 //!     second: 15
@@ -349,6 +493,41 @@
 //! rtc.enable_alarm2_interrupts().unwrap();
 //! ```
 //!
+//! ### Wait for the Alarm2 interrupt on the INT/SQW pin
+//!
+//! With the `async` feature enabled, `wait_for_alarm()` does the same
+//! for an `embedded_hal_async::digital::Wait` pin.
+//!
+//! ```no_run
+//! use ds323x::{AlarmEvent, Ds323x, NaiveTime};
+//! use linux_embedded_hal::{I2cdev, SysfsPin};
+//!
+//! let dev = I2cdev::new("/dev/i2c-1").unwrap();
+//! let mut int_pin = SysfsPin::new(24).into_input_pin().unwrap();
+//! let mut rtc = Ds323x::new_ds3231(dev);
+//! rtc.set_alarm2_hm(NaiveTime::from_hms_opt(7, 30, 0).unwrap()).unwrap();
+//! rtc.use_int_sqw_output_as_interrupt().unwrap();
+//! rtc.enable_alarm2_interrupts().unwrap();
+//! let event = rtc.wait_for_alarm_blocking(&mut int_pin).unwrap();
+//! assert_eq!(AlarmEvent::Alarm2, event);
+//! ```
+//!
+//! ### Track the INT/SQW pin mode at compile time
+//!
+//! In interrupt mode, the square-wave frequency cannot be selected and in
+//! square-wave mode, the alarm interrupts cannot be enabled.
+//!
+//! ```no_run
+//! use ds323x::{Ds323x, SqWFreq};
+//! use linux_embedded_hal::I2cdev;
+//!
+//! let dev = I2cdev::new("/dev/i2c-1").unwrap();
+//! let mut rtc = Ds323x::new_ds3231(dev).into_interrupt_mode().unwrap();
+//! rtc.enable_alarm1_interrupts().unwrap();
+//! let mut rtc = rtc.into_square_wave_mode().unwrap();
+//! rtc.set_square_wave_frequency(SqWFreq::_1Hz).unwrap();
+//! ```
+//!
 //! ### Set the Alarm1 to a specific time
 //!
 //! ```no_run
@@ -364,6 +543,7 @@
 #![deny(unsafe_code, missing_docs)]
 #![no_std]
 
+pub use chrono::{DateTime, Utc, Weekday};
 use core::marker::PhantomData;
 use embedded_hal::spi::{Mode, MODE_1, MODE_3};
 pub use rtcc::{
@@ -382,17 +562,43 @@ pub enum Error<E> {
     /// I²C/SPI bus error
     Comm(E),
     /// Invalid input data provided
-    InvalidInputData,
+    InvalidInputData {
+        /// Input parameter that was out of range
+        field: InputField,
+    },
     /// Internal device state is invalid.
     ///
-    /// It was not possible to read a valid date and/or time.
-    /// The device is probably missing initialization.
-    InvalidDeviceState,
+    /// A date or time register contained a value that is not valid packed
+    /// BCD or is outside of the range for that register, or the date read
+    /// does not exist. The device is probably missing initialization.
+    InvalidDeviceState {
+        /// Address of the register holding the invalid value
+        register: u8,
+    },
+    /// A register did not contain the written value when read back.
+    ///
+    /// This is only returned with write verification enabled. See
+    /// [`enable_write_verification()`](Ds323x::enable_write_verification).
+    VerificationFailed {
+        /// Address of the first register whose value differed
+        register: u8,
+    },
+    /// The SRAM of the DS3234 did not contain the written data when read
+    /// back.
+    ///
+    /// This is only returned with write verification enabled. The SRAM of
+    /// the DS3232 is mapped to registers and reported with
+    /// `VerificationFailed`.
+    SramVerificationFailed {
+        /// SRAM address of the first byte that differed
+        offset: u8,
+    },
 }
 
 /// Square-wave output frequency
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SqWFreq {
     /// 1 Hz (default)
     _1Hz,
@@ -404,11 +610,178 @@ pub enum SqWFreq {
     _8_192Hz,
 }
 
+/// Function of the INT/SQW output pin
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntSqwMode {
+    /// Interrupt output for the alarms (default)
+    Interrupt,
+    /// Square-wave output
+    SquareWave,
+}
+
+/// Device configuration
+///
+/// This covers the settings of the control, status and aging offset
+/// registers. It can be applied at once with `apply()` and read back with
+/// `current_config()`. The default values correspond to the power-on reset
+/// state of the device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Config {
+    /// Whether the oscillator is running (default: `true`)
+    pub oscillator_enabled: bool,
+    /// Function of the INT/SQW output pin (default: `IntSqwMode::Interrupt`)
+    pub int_sqw_mode: IntSqwMode,
+    /// Square-wave output frequency (default: `SqWFreq::_8_192Hz`)
+    pub square_wave_frequency: SqWFreq,
+    /// Whether the square wave is generated when battery-powered (default: `false`)
+    pub square_wave_on_battery: bool,
+    /// Whether the 32kHz output is enabled (default: `true`)
+    pub output_32khz: bool,
+    /// Whether the 32kHz output is enabled when battery-powered (default: `true`)
+    ///
+    /// This is only available on the DS3232 and DS3234 devices and ignored otherwise.
+    pub output_32khz_on_battery: bool,
+    /// Whether Alarm1 interrupts are enabled (default: `false`)
+    pub alarm1_interrupts: bool,
+    /// Whether Alarm2 interrupts are enabled (default: `false`)
+    pub alarm2_interrupts: bool,
+    /// Temperature conversion rate (default: `TempConvRate::_64s`)
+    ///
+    /// This is only available on the DS3232 and DS3234 devices and ignored otherwise.
+    pub temperature_conversion_rate: TempConvRate,
+    /// Aging offset (default: 0)
+    pub aging_offset: i8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            oscillator_enabled: true,
+            int_sqw_mode: IntSqwMode::Interrupt,
+            square_wave_frequency: SqWFreq::_8_192Hz,
+            square_wave_on_battery: false,
+            output_32khz: true,
+            output_32khz_on_battery: true,
+            alarm1_interrupts: false,
+            alarm2_interrupts: false,
+            temperature_conversion_rate: TempConvRate::_64s,
+            aging_offset: 0,
+        }
+    }
+}
+
+/// Battery-related settings changed by `prepare_for_battery_backup()`
+///
+/// Each field contains the previous value of a setting that was changed to
+/// reduce the current drawn from the battery or `None` if it was kept.
+/// This can be passed to `restore_power_profile()` once on main power again.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerProfile {
+    /// Battery-backed square-wave/interrupt output
+    pub square_wave_on_battery: Option<bool>,
+    /// 32kHz output (only changed on the DS3231, which has no separate
+    /// setting for battery power)
+    pub output_32khz: Option<bool>,
+    /// 32kHz output when battery-powered (DS3232 and DS3234)
+    pub output_32khz_on_battery: Option<bool>,
+    /// Temperature conversion rate (DS3232 and DS3234)
+    pub temperature_conversion_rate: Option<TempConvRate>,
+    /// Temperature conversions when battery-powered (DS3234)
+    pub temperature_conversions_on_battery: Option<bool>,
+}
+
+/// Options of `reset_to_defaults()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResetOptions {
+    /// Whether to stop the oscillator when battery-powered (default: `false`)
+    ///
+    /// This sets the EOSC bit, which is cleared in the power-on reset state.
+    /// The oscillator keeps running while the device is powered from VCC and
+    /// stops when it switches to the battery, so the time does not advance
+    /// while on battery.
+    pub stop_oscillator_on_battery: bool,
+    /// Whether to clear the battery-backed SRAM (default: `false`)
+    ///
+    /// This is only available on the DS3232 and DS3234 devices and ignored otherwise.
+    pub clear_sram: bool,
+}
+
+/// Hour format used by the device
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HourMode {
+    /// 24-hour format (default)
+    #[default]
+    H24,
+    /// 12-hour format with AM/PM indicator
+    H12,
+}
+
+/// Numbering of the days of the week in the day-of-week register
+///
+/// The device only requires the values to be sequential. This setting
+/// defines which day corresponds to the value 1.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WeekdayNumbering {
+    /// Sunday is 1 and Saturday is 7 (default)
+    #[default]
+    SundayFirst,
+    /// Monday is 1 and Sunday is 7
+    MondayFirst,
+}
+
+/// Assessment of whether the date and time kept by the device can be trusted
+///
+/// See [`Ds323x::time_validity()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeValidity {
+    /// No reason to distrust the date and time was found
+    Valid,
+    /// The time-set marker in the SRAM is missing or corrupted, so the date
+    /// and time have never been set (DS3232 and DS3234 with the marker enabled)
+    NeverSet,
+    /// The oscillator is stopped or has been stopped at some point (OSF flag set)
+    OscillatorStopped,
+    /// The oscillator is disabled when battery-powered (EOSC bit set), so the
+    /// time does not advance while on battery
+    OscillatorDisabled,
+    /// The date and time registers do not contain a valid date and time or
+    /// the date and time are earlier than when they were last set
+    ImplausibleDate,
+}
+
+// Location of the time-set marker in the SRAM
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum TimeSetMarker {
+    // Start register, directly addressable (DS3232)
+    Register(u8),
+    // SRAM offset, accessed through the SRAM address and data registers (DS3234)
+    Indirect(u8),
+}
+
+// Size of the time-set marker in bytes
+const TIME_SET_MARKER_SIZE: usize = 9;
+
 /// Temperature conversion rate
 ///
 /// This is only available on the DS3232 and DS3234 devices.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TempConvRate {
     /// Once every 64 seconds (default)
     _64s,
@@ -431,12 +804,21 @@ impl Register {
     const MONTH: u8 = 0x05;
     const YEAR: u8 = 0x06;
     const ALARM1_SECONDS: u8 = 0x07;
+    const ALARM1_MINUTES: u8 = 0x08;
+    const ALARM1_HOURS: u8 = 0x09;
+    const ALARM1_DAY_DATE: u8 = 0x0A;
     const ALARM2_MINUTES: u8 = 0x0B;
+    const ALARM2_HOURS: u8 = 0x0C;
+    const ALARM2_DAY_DATE: u8 = 0x0D;
     const CONTROL: u8 = 0x0E;
     const STATUS: u8 = 0x0F;
     const AGING_OFFSET: u8 = 0x10;
     const TEMP_MSB: u8 = 0x11;
+    const TEMP_LSB: u8 = 0x12;
     const TEMP_CONV: u8 = 0x13;
+    const SRAM_START: u8 = 0x14;
+    const SRAM_ADDRESS: u8 = 0x18;
+    const SRAM_DATA: u8 = 0x19;
 }
 
 struct BitFlags;
@@ -468,6 +850,62 @@ impl BitFlags {
 
 const DEVICE_ADDRESS: u8 = 0b110_1000;
 const CONTROL_POR_VALUE: u8 = 0b0001_1100;
+const DEFAULT_CENTURY_BASE: u16 = 2000;
+
+/// INT/SQW pin mode markers
+///
+/// The mode is part of the type of the driver so that the alarm interrupt
+/// methods are only available in interrupt mode and the square-wave frequency
+/// selection only in square-wave mode. The driver starts in the `Unchecked`
+/// mode, where all methods are available and the mode is not tracked.
+/// See `into_interrupt_mode()` and `into_square_wave_mode()`.
+///
+/// ```compile_fail
+/// use ds323x::{Ds323x, SqWFreq};
+/// use linux_embedded_hal::I2cdev;
+///
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let mut rtc = Ds323x::new_ds3231(dev).into_interrupt_mode().unwrap();
+/// rtc.set_square_wave_frequency(SqWFreq::_1Hz).unwrap();
+/// ```
+pub mod mode {
+    use crate::private;
+
+    /// The INT/SQW pin mode is not tracked (default)
+    pub struct Unchecked;
+    /// The INT/SQW pin is used as interrupt output
+    pub struct Interrupt;
+    /// The INT/SQW pin is used as square-wave output
+    pub struct SquareWave;
+
+    /// Modes in which the alarm interrupts can be configured
+    pub trait AlarmInterruptMode: private::Sealed {}
+    impl AlarmInterruptMode for Unchecked {}
+    impl AlarmInterruptMode for Interrupt {}
+
+    /// Modes in which the square-wave frequency can be configured
+    pub trait SquareWaveMode: private::Sealed {}
+    impl SquareWaveMode for Unchecked {}
+    impl SquareWaveMode for SquareWave {}
+}
+
+/// Error type for mode changes
+///
+/// This allows to retrieve the unchanged device in case of an error.
+pub struct ModeChangeError<E, DEV> {
+    /// Error that occurred while changing the mode
+    pub error: Error<E>,
+    /// Device with the mode unchanged
+    pub dev: DEV,
+}
+
+impl<E: core::fmt::Debug, DEV> core::fmt::Debug for ModeChangeError<E, DEV> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ModeChangeError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
 
 /// IC markers
 pub mod ic {
@@ -482,30 +920,88 @@ pub mod ic {
 /// DS3231, DS3232 and DS3234 RTC driver
 #[derive(Debug, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct Ds323x<DI, IC> {
+pub struct Ds323x<DI, IC, MODE = mode::Unchecked> {
     iface: DI,
     control: u8,
     status: u8,
+    aging_offset: i8,
+    century_base: u16,
+    hour_mode: HourMode,
+    weekday_numbering: WeekdayNumbering,
+    weekday_auto_repair: bool,
+    write_verification: bool,
+    time_set_marker: Option<TimeSetMarker>,
+    time_set: bool,
     _ic: PhantomData<IC>,
+    _mode: PhantomData<MODE>,
 }
 
+mod crc;
 mod ds323x;
+mod error;
+mod event_log;
 pub mod interface;
+mod record;
+mod temperature;
+pub mod trace;
+pub use crate::error::{ErrorClass, InputField};
+pub use crate::event_log::{Event, EventLog, EventLogIter, LogEntry};
+pub use crate::interface::RetryPolicy;
+pub use crate::record::{Record, RecordState, RecordStore, Sram};
+pub use crate::temperature::{
+    PersistentTemperatureMonitor, TemperatureEvent, TemperatureMonitor, TemperatureSensor,
+    TemperatureStats, TemperatureThresholds,
+};
+#[cfg(feature = "critical-section")]
+mod shared;
 pub use crate::ds323x::{
-    Alarm1Matching, Alarm2Matching, DayAlarm1, DayAlarm2, WeekdayAlarm1, WeekdayAlarm2,
+    Alarm1Matching, Alarm2Matching, AlarmEvent, CheckResult, DayAlarm1, DayAlarm2, Reg,
+    SelfTestReport, WaitError, WeekdayAlarm1, WeekdayAlarm2,
 };
+#[cfg(feature = "critical-section")]
+pub use crate::shared::SharedDs323x;
+#[cfg(feature = "embedded-sdmmc")]
+mod time_source;
+#[cfg(feature = "embedded-sdmmc")]
+pub use crate::time_source::Ds323xTimeSource;
 mod ds3231;
 mod ds3232;
 mod ds3234;
+#[cfg(feature = "embedded-storage")]
+mod storage;
 
 mod private {
-    use super::{ic, interface};
+    use super::{ic, interface, mode};
     pub trait Sealed {}
 
     impl<SPI> Sealed for interface::SpiInterface<SPI> {}
-    impl<I2C> Sealed for interface::I2cInterface<I2C> {}
+    impl<I2C, D> Sealed for interface::I2cInterface<I2C, D> {}
 
     impl Sealed for ic::DS3231 {}
     impl Sealed for ic::DS3232 {}
     impl Sealed for ic::DS3234 {}
+
+    // Registers available on each device
+    pub trait RegisterMap {
+        // Whether the registers only available on the DS3234 are present
+        const DS3234_REGISTERS: bool;
+    }
+
+    impl RegisterMap for ic::DS3231 {
+        const DS3234_REGISTERS: bool = false;
+    }
+    impl RegisterMap for ic::DS3232 {
+        const DS3234_REGISTERS: bool = false;
+    }
+    impl RegisterMap for ic::DS3234 {
+        const DS3234_REGISTERS: bool = true;
+    }
+
+    impl Sealed for mode::Unchecked {}
+    impl Sealed for mode::Interrupt {}
+    impl Sealed for mode::SquareWave {}
+
+    impl<I2C, D, MODE> Sealed for super::Ds323x<interface::I2cInterface<I2C, D>, ic::DS3232, MODE> {}
+    impl<SPI, MODE> Sealed for super::Ds323x<interface::SpiInterface<SPI>, ic::DS3234, MODE> {}
+    impl<T: Sealed> Sealed for &mut T {}
 }
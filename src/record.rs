@@ -0,0 +1,345 @@
+//! CRC-protected records in the battery-backed SRAM
+
+use crate::{
+    crc::crc16,
+    ds3232, ds3234, ic,
+    interface::{I2cInterface, SpiInterface},
+    private, Ds323x, Error, InputField,
+};
+use embedded_hal::{delay::DelayNs, i2c, spi};
+
+/// Access to the battery-backed SRAM
+///
+/// This is implemented for the DS3232 and DS3234 devices and for mutable
+/// references to them.
+pub trait Sram: private::Sealed {
+    /// Error type
+    type Error;
+
+    /// Size of the SRAM in bytes
+    fn sram_size(&self) -> usize;
+
+    /// Read data from the SRAM starting at `offset`.
+    fn read_sram(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Write data to the SRAM starting at `offset`.
+    fn write_sram(&mut self, offset: u8, data: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<I2C, D, MODE, E> Sram for Ds323x<I2cInterface<I2C, D>, ic::DS3232, MODE>
+where
+    I2C: i2c::I2c<Error = E>,
+    D: DelayNs,
+{
+    type Error = Error<E>;
+
+    fn sram_size(&self) -> usize {
+        ds3232::SRAM_SIZE
+    }
+
+    fn read_sram(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+        Self::read_sram(self, offset, data)
+    }
+
+    fn write_sram(&mut self, offset: u8, data: &[u8]) -> Result<(), Self::Error> {
+        Self::write_sram(self, offset, data)
+    }
+}
+
+impl<SPI, MODE, E> Sram for Ds323x<SpiInterface<SPI>, ic::DS3234, MODE>
+where
+    SPI: spi::SpiDevice<u8, Error = E>,
+{
+    type Error = Error<E>;
+
+    fn sram_size(&self) -> usize {
+        ds3234::SRAM_SIZE
+    }
+
+    fn read_sram(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+        Self::read_sram(self, offset, data)
+    }
+
+    fn write_sram(&mut self, offset: u8, data: &[u8]) -> Result<(), Self::Error> {
+        Self::write_sram(self, offset, data)
+    }
+}
+
+impl<T: Sram> Sram for &mut T {
+    type Error = T::Error;
+
+    fn sram_size(&self) -> usize {
+        (**self).sram_size()
+    }
+
+    fn read_sram(&mut self, offset: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).read_sram(offset, data)
+    }
+
+    fn write_sram(&mut self, offset: u8, data: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_sram(offset, data)
+    }
+}
+
+/// Fixed-size record that can be stored in a [`RecordStore`]
+pub trait Record: Sized {
+    /// Size of the serialized record in bytes.
+    ///
+    /// At most [`RecordStore::MAX_RECORD_SIZE`] bytes are supported.
+    const SIZE: usize;
+
+    /// Version of the record layout.
+    ///
+    /// Records stored with a different version are reported as invalid.
+    const VERSION: u8;
+
+    /// Serialize the record into `data`, which has exactly `SIZE` bytes.
+    fn to_bytes(&self, data: &mut [u8]);
+
+    /// Deserialize a record from `data`, which has exactly `SIZE` bytes.
+    ///
+    /// Returns `None` if the data does not contain a valid record.
+    fn from_bytes(data: &[u8]) -> Option<Self>;
+}
+
+/// Result of loading a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecordState<T> {
+    /// A valid record was found
+    Valid(T),
+    /// Both slots are erased as done by [`RecordStore::erase()`]
+    Empty,
+    /// The stored records are corrupted or have a different version or size.
+    /// This includes random SRAM contents after the backup battery was lost.
+    Invalid,
+}
+
+const MAGIC: [u8; 2] = [0x52, 0x53];
+const HEADER_SIZE: usize = 5;
+const CRC_SIZE: usize = 2;
+const SLOT_OVERHEAD: usize = HEADER_SIZE + CRC_SIZE;
+const MAX_SLOT_SIZE: usize = 128;
+
+/// Versioned, CRC-protected record storage in the battery-backed SRAM
+///
+/// The record is stored in two slots placed one after the other starting
+/// at the configured SRAM offset. Each slot contains a magic number, the
+/// record version, a sequence number, the record size, the record data
+/// and a CRC-16 over all of them.
+///
+/// New records are always written to the slot that does not contain the
+/// newest valid record, so if the power is lost during a write, the
+/// previous record stays intact and is returned by [`load()`](#method.load).
+///
+/// A slot is only considered empty if all its bytes are zero, as written
+/// by [`erase()`](#method.erase). Any other contents without a valid record
+/// are reported as invalid, so a store whose SRAM lost its contents is never
+/// mistaken for an empty one. A new store can be prepared with `erase()`.
+///
+/// ```no_run
+/// use ds323x::{Ds323x, Record, RecordState, RecordStore};
+/// use linux_embedded_hal::I2cdev;
+///
+/// struct Counter(u32);
+///
+/// impl Record for Counter {
+///     const SIZE: usize = 4;
+///     const VERSION: u8 = 1;
+///     fn to_bytes(&self, data: &mut [u8]) {
+///         data.copy_from_slice(&self.0.to_le_bytes());
+///     }
+///     fn from_bytes(data: &[u8]) -> Option<Self> {
+///         Some(Counter(u32::from_le_bytes([data[0], data[1], data[2], data[3]])))
+///     }
+/// }
+///
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let mut rtc = Ds323x::new_ds3232(dev);
+/// let mut store = RecordStore::new(&mut rtc, 0);
+/// let counter = match store.load::<Counter>().unwrap() {
+///     RecordState::Valid(counter) => counter,
+///     RecordState::Empty | RecordState::Invalid => Counter(0),
+/// };
+/// store.store(&Counter(counter.0 + 1)).unwrap();
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecordStore<S> {
+    sram: S,
+    offset: u8,
+}
+
+// Contents of a slot after validation
+enum Slot {
+    Valid { seq: u8 },
+    Empty,
+    Invalid,
+}
+
+impl<S, E> RecordStore<S>
+where
+    S: Sram<Error = Error<E>>,
+{
+    /// Maximum record size in bytes
+    pub const MAX_RECORD_SIZE: usize = MAX_SLOT_SIZE - SLOT_OVERHEAD;
+
+    /// Create a new record store using the SRAM starting at `offset`.
+    ///
+    /// Records of type `T` use `2 * (T::SIZE + 7)` bytes of SRAM.
+    pub fn new(sram: S, offset: u8) -> Self {
+        RecordStore { sram, offset }
+    }
+
+    /// Destroy the record store and return the SRAM instance.
+    pub fn destroy(self) -> S {
+        self.sram
+    }
+
+    // SRAM instance, which may also give access to the rest of the device.
+    pub(crate) fn sram_mut(&mut self) -> &mut S {
+        &mut self.sram
+    }
+
+    /// Load the newest valid record.
+    ///
+    /// If the newest record has a valid CRC but is rejected by
+    /// [`Record::from_bytes()`], the record in the other slot is returned if
+    /// it is valid.
+    ///
+    /// Will return `Error::InvalidInputData` if the record does not fit in
+    /// the SRAM.
+    pub fn load<T: Record>(&mut self) -> Result<RecordState<T>, Error<E>> {
+        let mut buffer = [[0; MAX_SLOT_SIZE]; 2];
+        let (first, second) = self.read_slots::<T>(&mut buffer)?;
+        let order = match (&first, &second) {
+            (Slot::Valid { seq: a }, Slot::Valid { seq: b }) => {
+                if is_newer(*b, *a) {
+                    [Some(1), Some(0)]
+                } else {
+                    [Some(0), Some(1)]
+                }
+            }
+            (Slot::Valid { .. }, _) => [Some(0), None],
+            (_, Slot::Valid { .. }) => [Some(1), None],
+            (Slot::Empty, Slot::Empty) => return Ok(RecordState::Empty),
+            _ => return Ok(RecordState::Invalid),
+        };
+        let record = order
+            .iter()
+            .flatten()
+            .find_map(|&slot| T::from_bytes(&buffer[slot][HEADER_SIZE..HEADER_SIZE + T::SIZE]));
+        Ok(record.map_or(RecordState::Invalid, RecordState::Valid))
+    }
+
+    /// Store a record.
+    ///
+    /// The record is written to the slot that does not contain the newest
+    /// valid record.
+    ///
+    /// Will return `Error::InvalidInputData` if the record does not fit in
+    /// the SRAM.
+    pub fn store<T: Record>(&mut self, record: &T) -> Result<(), Error<E>> {
+        let mut buffer = [[0; MAX_SLOT_SIZE]; 2];
+        let (first, second) = self.read_slots::<T>(&mut buffer)?;
+        let (slot, seq) = match (first, second) {
+            (Slot::Valid { seq: a }, Slot::Valid { seq: b }) => {
+                if is_newer(b, a) {
+                    (0, b.wrapping_add(1))
+                } else {
+                    (1, a.wrapping_add(1))
+                }
+            }
+            (Slot::Valid { seq }, _) => (1, seq.wrapping_add(1)),
+            (_, Slot::Valid { seq }) => (0, seq.wrapping_add(1)),
+            _ => (0, 0),
+        };
+        let slot_size = T::SIZE + SLOT_OVERHEAD;
+        let data = &mut buffer[0][..slot_size];
+        data[..2].copy_from_slice(&MAGIC);
+        data[2] = T::VERSION;
+        data[3] = seq;
+        data[4] = T::SIZE as u8;
+        record.to_bytes(&mut data[HEADER_SIZE..HEADER_SIZE + T::SIZE]);
+        let crc = crc16(&data[..HEADER_SIZE + T::SIZE]);
+        data[HEADER_SIZE + T::SIZE..].copy_from_slice(&crc.to_be_bytes());
+        let offset = self.offset + (slot * slot_size) as u8;
+        self.sram.write_sram(offset, data)
+    }
+
+    /// Erase both slots so that [`load()`](#method.load) reports the
+    /// record as empty.
+    ///
+    /// Will return `Error::InvalidInputData` if the record does not fit in
+    /// the SRAM.
+    pub fn erase<T: Record>(&mut self) -> Result<(), Error<E>> {
+        let slot_size = self.check_size::<T>()?;
+        self.sram
+            .write_sram(self.offset, &[0; 2 * MAX_SLOT_SIZE][..2 * slot_size])
+    }
+
+    // Check that both slots fit in the SRAM and return the slot size.
+    fn check_size<T: Record>(&self) -> Result<usize, Error<E>> {
+        let slot_size = T::SIZE + SLOT_OVERHEAD;
+        if T::SIZE > Self::MAX_RECORD_SIZE {
+            return Err(Error::InvalidInputData {
+                field: InputField::RecordSize,
+            });
+        }
+        if usize::from(self.offset) + 2 * slot_size > self.sram.sram_size() {
+            return Err(Error::InvalidInputData {
+                field: InputField::SramRange,
+            });
+        }
+        Ok(slot_size)
+    }
+
+    fn read_slots<T: Record>(
+        &mut self,
+        buffer: &mut [[u8; MAX_SLOT_SIZE]; 2],
+    ) -> Result<(Slot, Slot), Error<E>> {
+        let slot_size = self.check_size::<T>()?;
+        let [first, second] = buffer;
+        self.sram.read_sram(self.offset, &mut first[..slot_size])?;
+        self.sram
+            .read_sram(self.offset + slot_size as u8, &mut second[..slot_size])?;
+        Ok((
+            check_slot::<T>(&first[..slot_size]),
+            check_slot::<T>(&second[..slot_size]),
+        ))
+    }
+}
+
+fn check_slot<T: Record>(data: &[u8]) -> Slot {
+    if data.iter().all(|&value| value == 0) {
+        return Slot::Empty;
+    }
+    let (content, crc) = data.split_at(data.len() - CRC_SIZE);
+    if data[..2] != MAGIC
+        || data[2] != T::VERSION
+        || usize::from(data[4]) != T::SIZE
+        || crc16(content).to_be_bytes() != crc
+    {
+        return Slot::Invalid;
+    }
+    Slot::Valid { seq: data[3] }
+}
+
+// Whether the sequence number `a` is newer than `b`, allowing for wrap-around.
+fn is_newer(a: u8, b: u8) -> bool {
+    (a.wrapping_sub(b) as i8) > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_numbers_wrap_around() {
+        assert!(is_newer(1, 0));
+        assert!(!is_newer(0, 1));
+        assert!(is_newer(0, 255));
+        assert!(!is_newer(255, 0));
+        assert!(!is_newer(5, 5));
+    }
+}
@@ -0,0 +1,57 @@
+//! Shared access to the driver from interrupts and multiple tasks
+
+use crate::{mode, Ds323x};
+use core::cell::RefCell;
+use critical_section::Mutex;
+
+/// Driver instance that can be shared between interrupts and tasks
+///
+/// Every access to the driver happens inside a critical section, so that
+/// the register values cached by the driver stay coherent with the device.
+/// The handle can be placed in a `static` and used through a shared reference.
+///
+/// Calls to [`lock()`](#method.lock) must not be nested, otherwise it will panic.
+///
+/// ```no_run
+/// use ds323x::{DateTimeAccess, Ds323x, SharedDs323x};
+/// use linux_embedded_hal::I2cdev;
+///
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let rtc = SharedDs323x::new(Ds323x::new_ds3231(dev));
+/// // In the main loop
+/// let datetime = rtc.lock(|rtc| rtc.datetime()).unwrap();
+/// // In the interrupt handler
+/// rtc.lock(|rtc| rtc.clear_alarm1_matched_flag()).unwrap();
+/// ```
+pub struct SharedDs323x<DI, IC, MODE = mode::Unchecked> {
+    dev: Mutex<RefCell<Ds323x<DI, IC, MODE>>>,
+}
+
+impl<DI, IC, MODE> SharedDs323x<DI, IC, MODE> {
+    /// Create a new shared handle for a driver instance.
+    pub const fn new(dev: Ds323x<DI, IC, MODE>) -> Self {
+        SharedDs323x {
+            dev: Mutex::new(RefCell::new(dev)),
+        }
+    }
+
+    /// Run a closure with exclusive access to the driver inside a critical section.
+    ///
+    /// Returns the value returned by the closure.
+    pub fn lock<R>(&self, f: impl FnOnce(&mut Ds323x<DI, IC, MODE>) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.dev.borrow_ref_mut(cs)))
+    }
+
+    /// Get exclusive access to the driver without a critical section.
+    ///
+    /// This is possible since the mutable borrow guarantees that there are no
+    /// other references to the handle.
+    pub fn get_mut(&mut self) -> &mut Ds323x<DI, IC, MODE> {
+        self.dev.get_mut().get_mut()
+    }
+
+    /// Destroy the shared handle and return the driver instance.
+    pub fn into_inner(self) -> Ds323x<DI, IC, MODE> {
+        self.dev.into_inner().into_inner()
+    }
+}
@@ -0,0 +1,170 @@
+//! `embedded-storage` implementation for the battery-backed SRAM
+//!
+//! The SRAM can be used through the `ReadStorage`/`Storage` traits and
+//! through the NOR flash traits. Since the SRAM can be freely rewritten,
+//! the NOR flash implementation has a read and write size of 1 byte and
+//! erasing simply sets the bytes to `0xFF`. The erase size is a power of
+//! two dividing the SRAM size, as expected by crates building on these
+//! traits like `sequential-storage`: 4 bytes on the DS3232 and 64 bytes on
+//! the DS3234.
+
+use crate::{
+    ds3232, ds3234, ic,
+    interface::{I2cInterface, SpiInterface},
+    Ds323x, Error, InputField,
+};
+use core::fmt::Debug;
+use embedded_hal::{delay::DelayNs, i2c, spi};
+use embedded_storage::{
+    nor_flash::{
+        ErrorType, MultiwriteNorFlash, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+    },
+    ReadStorage, Storage,
+};
+
+impl<E: Debug> NorFlashError for Error<E> {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            Error::InvalidInputData {
+                field: InputField::EraseRange,
+            } => NorFlashErrorKind::NotAligned,
+            Error::InvalidInputData { .. } => NorFlashErrorKind::OutOfBounds,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+// Maximum number of bytes set by each write when erasing
+const ERASE_CHUNK_SIZE: usize = 64;
+
+// Convert a storage offset into an SRAM offset checking that the data fits.
+fn sram_offset<E>(offset: u32, len: usize, size: usize) -> Result<u8, Error<E>> {
+    if offset < size as u32 && len <= size - offset as usize {
+        Ok(offset as u8)
+    } else {
+        Err(Error::InvalidInputData {
+            field: InputField::SramRange,
+        })
+    }
+}
+
+macro_rules! impl_storage {
+    ([$($param:ident),+], $iface:ty, { $($bounds:tt)+ }, $ic:ident, $size:expr, $erase_size:expr) => {
+        impl<$($param),+, MODE, E> ReadStorage for Ds323x<$iface, ic::$ic, MODE>
+        where
+            $($bounds)+
+        {
+            type Error = Error<E>;
+
+            fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+                if bytes.is_empty() {
+                    return Ok(());
+                }
+                let offset = sram_offset(offset, bytes.len(), $size)?;
+                self.read_sram(offset, bytes)
+            }
+
+            fn capacity(&self) -> usize {
+                $size
+            }
+        }
+
+        impl<$($param),+, MODE, E> Storage for Ds323x<$iface, ic::$ic, MODE>
+        where
+            $($bounds)+
+        {
+            fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+                if bytes.is_empty() {
+                    return Ok(());
+                }
+                let offset = sram_offset(offset, bytes.len(), $size)?;
+                self.write_sram(offset, bytes)
+            }
+        }
+
+        impl<$($param),+, MODE, E> ErrorType for Ds323x<$iface, ic::$ic, MODE>
+        where
+            $($bounds)+
+            E: Debug,
+        {
+            type Error = Error<E>;
+        }
+
+        impl<$($param),+, MODE, E> ReadNorFlash for Ds323x<$iface, ic::$ic, MODE>
+        where
+            $($bounds)+
+            E: Debug,
+        {
+            const READ_SIZE: usize = 1;
+
+            fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+                ReadStorage::read(self, offset, bytes)
+            }
+
+            fn capacity(&self) -> usize {
+                $size
+            }
+        }
+
+        impl<$($param),+, MODE, E> NorFlash for Ds323x<$iface, ic::$ic, MODE>
+        where
+            $($bounds)+
+            E: Debug,
+        {
+            const WRITE_SIZE: usize = 1;
+            const ERASE_SIZE: usize = $erase_size;
+
+            fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+                if to as usize > $size {
+                    return Err(Error::InvalidInputData {
+                        field: InputField::SramRange,
+                    });
+                }
+                if from > to
+                    || from as usize % Self::ERASE_SIZE != 0
+                    || to as usize % Self::ERASE_SIZE != 0
+                {
+                    return Err(Error::InvalidInputData {
+                        field: InputField::EraseRange,
+                    });
+                }
+                let erased = [0xFF; ERASE_CHUNK_SIZE];
+                for start in (from..to).step_by(ERASE_CHUNK_SIZE) {
+                    let len = (to - start).min(ERASE_CHUNK_SIZE as u32);
+                    Storage::write(self, start, &erased[..len as usize])?;
+                }
+                Ok(())
+            }
+
+            fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+                Storage::write(self, offset, bytes)
+            }
+        }
+
+        impl<$($param),+, MODE, E> MultiwriteNorFlash for Ds323x<$iface, ic::$ic, MODE>
+        where
+            $($bounds)+
+            E: Debug,
+        {
+        }
+    };
+}
+
+// The 236 bytes of the DS3232 are divided into 59 erase blocks of 4 bytes.
+impl_storage!(
+    [I2C, D],
+    I2cInterface<I2C, D>,
+    { I2C: i2c::I2c<Error = E>, D: DelayNs, },
+    DS3232,
+    ds3232::SRAM_SIZE,
+    4
+);
+// The 256 bytes of the DS3234 are divided into 4 erase blocks of 64 bytes.
+impl_storage!(
+    [SPI],
+    SpiInterface<SPI>,
+    { SPI: spi::SpiDevice<u8, Error = E>, },
+    DS3234,
+    ds3234::SRAM_SIZE,
+    64
+);
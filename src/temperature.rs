@@ -0,0 +1,447 @@
+//! Temperature monitoring
+
+use crate::{
+    ic,
+    interface::{I2cInterface, ReadData, SpiInterface, WriteData},
+    private,
+    record::Sram,
+    Ds323x, Error, Record, RecordState, RecordStore,
+};
+use embedded_hal::{delay::DelayNs, i2c, spi};
+
+/// Access to the temperature sensor
+///
+/// This is implemented for the DS3232 and DS3234 devices and for mutable
+/// references to them, so that a [`PersistentTemperatureMonitor`] can read
+/// the temperature from the device holding its SRAM.
+pub trait TemperatureSensor: private::Sealed {
+    /// Error type
+    type Error;
+
+    /// Read the temperature in degrees Celsius.
+    fn read_temperature(&mut self) -> Result<f32, Self::Error>;
+}
+
+impl<I2C, D, MODE, E> TemperatureSensor for Ds323x<I2cInterface<I2C, D>, ic::DS3232, MODE>
+where
+    I2C: i2c::I2c<Error = E>,
+    D: DelayNs,
+{
+    type Error = Error<E>;
+
+    fn read_temperature(&mut self) -> Result<f32, Self::Error> {
+        self.temperature()
+    }
+}
+
+impl<SPI, MODE, E> TemperatureSensor for Ds323x<SpiInterface<SPI>, ic::DS3234, MODE>
+where
+    SPI: spi::SpiDevice<u8, Error = E>,
+{
+    type Error = Error<E>;
+
+    fn read_temperature(&mut self) -> Result<f32, Self::Error> {
+        self.temperature()
+    }
+}
+
+impl<T: TemperatureSensor> TemperatureSensor for &mut T {
+    type Error = T::Error;
+
+    fn read_temperature(&mut self) -> Result<f32, Self::Error> {
+        (**self).read_temperature()
+    }
+}
+
+/// Temperature statistics of a [`TemperatureMonitor`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureStats {
+    /// Minimum temperature in degrees Celsius
+    pub min: f32,
+    /// Maximum temperature in degrees Celsius
+    pub max: f32,
+    /// Average temperature in degrees Celsius
+    pub average: f32,
+    /// Number of samples
+    pub samples: u32,
+}
+
+/// Temperature thresholds of a [`TemperatureMonitor`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureThresholds {
+    /// The temperature is too low at or below this value in degrees Celsius
+    pub low: f32,
+    /// The temperature is too high at or above this value in degrees Celsius
+    pub high: f32,
+    /// How far in degrees Celsius the temperature must get back from a
+    /// threshold before it is considered cleared
+    pub hysteresis: f32,
+}
+
+/// Threshold crossing reported by a [`TemperatureMonitor`]
+///
+/// Each variant contains the sampled temperature in degrees Celsius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TemperatureEvent {
+    /// The temperature reached the high threshold
+    AboveHigh(f32),
+    /// The temperature went back below the high threshold minus the hysteresis
+    HighCleared(f32),
+    /// The temperature reached the low threshold
+    BelowLow(f32),
+    /// The temperature went back above the low threshold plus the hysteresis
+    LowCleared(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum ThresholdState {
+    Normal,
+    High,
+    Low,
+}
+
+// Statistics in quarters of a degree Celsius, as stored in the SRAM
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Stats {
+    min: i16,
+    max: i16,
+    sum: i64,
+    count: u32,
+}
+
+impl Record for Stats {
+    const SIZE: usize = 16;
+    const VERSION: u8 = 1;
+
+    fn to_bytes(&self, data: &mut [u8]) {
+        data[0..2].copy_from_slice(&self.min.to_be_bytes());
+        data[2..4].copy_from_slice(&self.max.to_be_bytes());
+        data[4..12].copy_from_slice(&self.sum.to_be_bytes());
+        data[12..16].copy_from_slice(&self.count.to_be_bytes());
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut sum = [0; 8];
+        sum.copy_from_slice(&data[4..12]);
+        let stats = Stats {
+            min: i16::from_be_bytes([data[0], data[1]]),
+            max: i16::from_be_bytes([data[2], data[3]]),
+            sum: i64::from_be_bytes(sum),
+            count: u32::from_be_bytes([data[12], data[13], data[14], data[15]]),
+        };
+        if stats.count > 0 && stats.min > stats.max {
+            None
+        } else {
+            Some(stats)
+        }
+    }
+}
+
+const EVENT_QUEUE_SIZE: usize = 4;
+
+/// Temperature monitor with statistics and threshold tracking
+///
+/// Each call to [`sample()`](#method.sample) reads the temperature, updates
+/// the minimum, maximum and average since the last reset and checks the
+/// thresholds. It is meant to be called periodically, for example after each
+/// temperature conversion or on each alarm.
+///
+/// Threshold crossings are queued as [`TemperatureEvent`]s, which can be
+/// retrieved with [`poll_event()`](#method.poll_event). Up to 4 events are
+/// kept. When the queue is full, the oldest event is dropped.
+///
+/// On the DS3232 and DS3234, a [`PersistentTemperatureMonitor`] keeps the
+/// statistics in the SRAM automatically. This monitor only saves them when
+/// [`save()`](#method.save) is called, which allows saving less often than
+/// on every sample to reduce the bus traffic. The samples taken since the
+/// last call to `save()` are then lost on a reset or power loss.
+/// [`restore()`](#method.restore) continues with the saved statistics.
+///
+/// ```no_run
+/// use ds323x::{Ds323x, TemperatureMonitor, TemperatureThresholds};
+/// use linux_embedded_hal::I2cdev;
+///
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let mut rtc = Ds323x::new_ds3232(dev);
+/// let mut monitor = TemperatureMonitor::new(Some(TemperatureThresholds {
+///     low: 0.0,
+///     high: 60.0,
+///     hysteresis: 2.0,
+/// }));
+/// monitor.restore(&mut rtc, 0).unwrap();
+/// monitor.sample(&mut rtc).unwrap();
+/// while let Some(event) = monitor.poll_event() {
+///     println!("{:?}", event);
+/// }
+/// monitor.save(&mut rtc, 0).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureMonitor {
+    stats: Stats,
+    thresholds: Option<TemperatureThresholds>,
+    state: ThresholdState,
+    events: [Option<TemperatureEvent>; EVENT_QUEUE_SIZE],
+    event_count: usize,
+}
+
+impl TemperatureMonitor {
+    /// Create a new temperature monitor, optionally checking thresholds.
+    pub fn new(thresholds: Option<TemperatureThresholds>) -> Self {
+        TemperatureMonitor {
+            stats: Stats {
+                min: 0,
+                max: 0,
+                sum: 0,
+                count: 0,
+            },
+            thresholds,
+            state: ThresholdState::Normal,
+            events: [None; EVENT_QUEUE_SIZE],
+            event_count: 0,
+        }
+    }
+
+    /// Set the thresholds or disable the threshold checks with `None`.
+    ///
+    /// The threshold state is reset so that the next sample is checked
+    /// against the new thresholds from scratch.
+    pub fn set_thresholds(&mut self, thresholds: Option<TemperatureThresholds>) {
+        self.thresholds = thresholds;
+        self.state = ThresholdState::Normal;
+    }
+
+    /// Read the temperature from the device and record it.
+    ///
+    /// Returns the temperature in degrees Celsius. The statistics are not
+    /// saved in the SRAM; see [`save()`](#method.save).
+    pub fn sample<DI, IC, MODE, E>(
+        &mut self,
+        dev: &mut Ds323x<DI, IC, MODE>,
+    ) -> Result<f32, Error<E>>
+    where
+        DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+    {
+        let temperature = dev.temperature()?;
+        self.record(temperature);
+        Ok(temperature)
+    }
+
+    /// Record a temperature in degrees Celsius read by other means.
+    ///
+    /// The temperature is truncated to the 0.25°C resolution of the devices.
+    /// The statistics are not saved in the SRAM; see [`save()`](#method.save).
+    pub fn record(&mut self, temperature: f32) {
+        let quarters = (temperature * 4.0) as i16;
+        let stats = &mut self.stats;
+        if stats.count == 0 {
+            stats.min = quarters;
+            stats.max = quarters;
+        } else {
+            stats.min = stats.min.min(quarters);
+            stats.max = stats.max.max(quarters);
+        }
+        stats.sum += i64::from(quarters);
+        stats.count = stats.count.saturating_add(1);
+        self.check_thresholds(f32::from(quarters) / 4.0);
+    }
+
+    /// Statistics since the last reset or `None` if there are no samples.
+    pub fn stats(&self) -> Option<TemperatureStats> {
+        let stats = &self.stats;
+        if stats.count == 0 {
+            return None;
+        }
+        Some(TemperatureStats {
+            min: f32::from(stats.min) / 4.0,
+            max: f32::from(stats.max) / 4.0,
+            average: (stats.sum as f64 / f64::from(stats.count) / 4.0) as f32,
+            samples: stats.count,
+        })
+    }
+
+    /// Reset the statistics.
+    pub fn reset(&mut self) {
+        self.stats = TemperatureMonitor::new(None).stats;
+    }
+
+    /// Get the oldest threshold crossing event that has not been polled yet.
+    pub fn poll_event(&mut self) -> Option<TemperatureEvent> {
+        if self.event_count == 0 {
+            return None;
+        }
+        let event = self.events[0];
+        self.events.rotate_left(1);
+        self.events[EVENT_QUEUE_SIZE - 1] = None;
+        self.event_count -= 1;
+        event
+    }
+
+    /// Save the statistics in the SRAM starting at `offset`.
+    ///
+    /// This must be called by the application whenever the statistics
+    /// should survive a reset or power loss.
+    ///
+    /// The statistics are stored in a [`RecordStore`], which uses 46 bytes.
+    pub fn save<S, E>(&self, sram: S, offset: u8) -> Result<(), Error<E>>
+    where
+        S: Sram<Error = Error<E>>,
+    {
+        RecordStore::new(sram, offset).store(&self.stats)
+    }
+
+    /// Restore the statistics saved in the SRAM starting at `offset`.
+    ///
+    /// Returns whether valid statistics were found. Otherwise, the current
+    /// statistics are kept.
+    pub fn restore<S, E>(&mut self, sram: S, offset: u8) -> Result<bool, Error<E>>
+    where
+        S: Sram<Error = Error<E>>,
+    {
+        match RecordStore::new(sram, offset).load::<Stats>()? {
+            RecordState::Valid(stats) => {
+                self.stats = stats;
+                Ok(true)
+            }
+            RecordState::Empty | RecordState::Invalid => Ok(false),
+        }
+    }
+
+    fn check_thresholds(&mut self, temperature: f32) {
+        let thresholds = match self.thresholds {
+            Some(thresholds) => thresholds,
+            None => return,
+        };
+        match self.state {
+            ThresholdState::High if temperature <= thresholds.high - thresholds.hysteresis => {
+                self.state = ThresholdState::Normal;
+                self.push_event(TemperatureEvent::HighCleared(temperature));
+            }
+            ThresholdState::Low if temperature >= thresholds.low + thresholds.hysteresis => {
+                self.state = ThresholdState::Normal;
+                self.push_event(TemperatureEvent::LowCleared(temperature));
+            }
+            _ => (),
+        }
+        if self.state == ThresholdState::Normal {
+            if temperature >= thresholds.high {
+                self.state = ThresholdState::High;
+                self.push_event(TemperatureEvent::AboveHigh(temperature));
+            } else if temperature <= thresholds.low {
+                self.state = ThresholdState::Low;
+                self.push_event(TemperatureEvent::BelowLow(temperature));
+            }
+        }
+    }
+
+    fn push_event(&mut self, event: TemperatureEvent) {
+        if self.event_count == EVENT_QUEUE_SIZE {
+            self.poll_event();
+        }
+        self.events[self.event_count] = Some(event);
+        self.event_count += 1;
+    }
+}
+
+/// Temperature monitor keeping its statistics in the battery-backed SRAM
+///
+/// This is a [`TemperatureMonitor`] bound to the SRAM of a DS3232 or DS3234
+/// starting at a given offset. The saved statistics are restored when it is
+/// created and saved again after each sample, so they survive a reset or
+/// power loss. Saving takes two SRAM reads and one write of 23 bytes. The
+/// statistics use 46 bytes of SRAM.
+///
+/// ```no_run
+/// use ds323x::{Ds323x, PersistentTemperatureMonitor};
+/// use linux_embedded_hal::I2cdev;
+///
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let mut rtc = Ds323x::new_ds3232(dev);
+/// let mut monitor = PersistentTemperatureMonitor::new(&mut rtc, 0, None).unwrap();
+/// monitor.sample().unwrap();
+/// println!("{:?}", monitor.stats());
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PersistentTemperatureMonitor<S> {
+    monitor: TemperatureMonitor,
+    store: RecordStore<S>,
+}
+
+impl<S, E> PersistentTemperatureMonitor<S>
+where
+    S: Sram<Error = Error<E>>,
+{
+    /// Create a new temperature monitor using the SRAM starting at `offset`,
+    /// optionally checking thresholds.
+    ///
+    /// The statistics saved in the SRAM are restored. If there are none, the
+    /// monitor starts without samples.
+    pub fn new(
+        sram: S,
+        offset: u8,
+        thresholds: Option<TemperatureThresholds>,
+    ) -> Result<Self, Error<E>> {
+        let mut store = RecordStore::new(sram, offset);
+        let mut monitor = TemperatureMonitor::new(thresholds);
+        if let RecordState::Valid(stats) = store.load::<Stats>()? {
+            monitor.stats = stats;
+        }
+        Ok(PersistentTemperatureMonitor { monitor, store })
+    }
+
+    /// Destroy the monitor and return the SRAM instance.
+    pub fn destroy(self) -> S {
+        self.store.destroy()
+    }
+
+    /// Read the temperature from the device, record it and save the
+    /// statistics.
+    ///
+    /// Returns the temperature in degrees Celsius.
+    pub fn sample(&mut self) -> Result<f32, Error<E>>
+    where
+        S: TemperatureSensor<Error = Error<E>>,
+    {
+        let temperature = self.store.sram_mut().read_temperature()?;
+        self.record(temperature)?;
+        Ok(temperature)
+    }
+
+    /// Record a temperature in degrees Celsius read by other means and save
+    /// the statistics.
+    ///
+    /// See [`TemperatureMonitor::record()`].
+    pub fn record(&mut self, temperature: f32) -> Result<(), Error<E>> {
+        self.monitor.record(temperature);
+        self.store.store(&self.monitor.stats)
+    }
+
+    /// Reset the statistics, also in the SRAM.
+    pub fn reset(&mut self) -> Result<(), Error<E>> {
+        self.monitor.reset();
+        self.store.store(&self.monitor.stats)
+    }
+
+    /// Statistics since the last reset or `None` if there are no samples.
+    pub fn stats(&self) -> Option<TemperatureStats> {
+        self.monitor.stats()
+    }
+
+    /// Set the thresholds or disable the threshold checks with `None`.
+    ///
+    /// See [`TemperatureMonitor::set_thresholds()`].
+    pub fn set_thresholds(&mut self, thresholds: Option<TemperatureThresholds>) {
+        self.monitor.set_thresholds(thresholds);
+    }
+
+    /// Get the oldest threshold crossing event that has not been polled yet.
+    pub fn poll_event(&mut self) -> Option<TemperatureEvent> {
+        self.monitor.poll_event()
+    }
+}
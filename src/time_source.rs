@@ -0,0 +1,114 @@
+//! `embedded-sdmmc` time source
+
+use crate::{
+    interface::{ReadData, WriteData},
+    mode, DateTimeAccess, Datelike, Ds323x, Error, Timelike,
+};
+use core::cell::RefCell;
+use embedded_sdmmc::{TimeSource, Timestamp};
+
+/// Time source for the `embedded-sdmmc` FAT file system
+///
+/// The files are stamped with the date and time read from the device.
+/// The fallback timestamp is returned instead if the date and time cannot be
+/// trusted or represented in the FAT format, that is, if:
+/// - The communication with the device fails.
+/// - The date and time registers do not contain a valid date and time.
+/// - The oscillator is stopped or has been stopped at some point.
+/// - The year is outside of the range 1980-2107.
+/// - The driver is already borrowed by a nested call.
+///
+/// The default fallback is 1980-01-01 00:00:00, the earliest FAT timestamp.
+///
+/// Since `get_timestamp()` takes a shared reference, the driver is kept in
+/// a `RefCell`.
+///
+/// ```no_run
+/// use ds323x::{Ds323x, Ds323xTimeSource};
+/// use embedded_sdmmc::TimeSource;
+/// use linux_embedded_hal::I2cdev;
+///
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let time_source = Ds323xTimeSource::new(Ds323x::new_ds3231(dev));
+/// // Pass the time source to `embedded_sdmmc::VolumeManager::new()`
+/// let timestamp = time_source.get_timestamp();
+/// ```
+pub struct Ds323xTimeSource<DI, IC, MODE = mode::Unchecked> {
+    dev: RefCell<Ds323x<DI, IC, MODE>>,
+    fallback: Timestamp,
+}
+
+impl<DI, IC, MODE> Ds323xTimeSource<DI, IC, MODE> {
+    /// Create a new time source for a driver instance.
+    pub fn new(dev: Ds323x<DI, IC, MODE>) -> Self {
+        Ds323xTimeSource {
+            dev: RefCell::new(dev),
+            fallback: Timestamp {
+                year_since_1970: 10,
+                zero_indexed_month: 0,
+                zero_indexed_day: 0,
+                hours: 0,
+                minutes: 0,
+                seconds: 0,
+            },
+        }
+    }
+
+    /// Set the timestamp returned when the date and time cannot be used.
+    pub fn set_fallback(&mut self, fallback: Timestamp) {
+        self.fallback = fallback;
+    }
+
+    /// Get the timestamp returned when the date and time cannot be used.
+    pub fn fallback(&self) -> Timestamp {
+        self.fallback
+    }
+
+    /// Run a closure with exclusive access to the driver.
+    ///
+    /// Returns the value returned by the closure.
+    /// Calls to [`lock()`](#method.lock) must not be nested, otherwise it will panic.
+    pub fn lock<R>(&self, f: impl FnOnce(&mut Ds323x<DI, IC, MODE>) -> R) -> R {
+        f(&mut self.dev.borrow_mut())
+    }
+
+    /// Get exclusive access to the driver.
+    pub fn get_mut(&mut self) -> &mut Ds323x<DI, IC, MODE> {
+        self.dev.get_mut()
+    }
+
+    /// Destroy the time source and return the driver instance.
+    pub fn into_inner(self) -> Ds323x<DI, IC, MODE> {
+        self.dev.into_inner()
+    }
+}
+
+impl<DI, IC, MODE, E> Ds323xTimeSource<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    fn read_timestamp(&self) -> Option<Timestamp> {
+        let mut dev = self.dev.try_borrow_mut().ok()?;
+        let datetime = dev.datetime().ok()?;
+        if dev.has_been_stopped().ok()? || !(1980..=2107).contains(&datetime.year()) {
+            return None;
+        }
+        Some(Timestamp {
+            year_since_1970: (datetime.year() - 1970) as u8,
+            zero_indexed_month: datetime.month0() as u8,
+            zero_indexed_day: datetime.day0() as u8,
+            hours: datetime.hour() as u8,
+            minutes: datetime.minute() as u8,
+            seconds: datetime.second() as u8,
+        })
+    }
+}
+
+impl<DI, IC, MODE, E> TimeSource for Ds323xTimeSource<DI, IC, MODE>
+where
+    DI: ReadData<Error = Error<E>> + WriteData<Error = Error<E>>,
+{
+    fn get_timestamp(&self) -> Timestamp {
+        self.read_timestamp().unwrap_or(self.fallback)
+    }
+}
@@ -0,0 +1,555 @@
+//! Tracing of the register operations on the bus
+//!
+//! [`TracingI2c`] and [`TracingSpi`] wrap the I²C bus or SPI device passed
+//! to the driver and decode every register read and write, including the
+//! register names and the meaning of the control and status bits.
+//!
+//! With the `log` or `defmt` features, each operation is logged at debug
+//! level. The operations can also be recorded into a fixed-size
+//! [`TraceBuffer`] that keeps the last operations. The recorded operations
+//! can be iterated, for example to convert them into transactions of a bus
+//! mock and replay them in tests.
+//!
+//! ```no_run
+//! # use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+//! # let i2c = I2cMock::new(&[]);
+//! use ds323x::{trace::TracingI2c, Ds323x};
+//!
+//! let bus: TracingI2c<_, 32> = TracingI2c::with_trace_buffer(i2c);
+//! let mut rtc = Ds323x::new_ds3231(bus);
+//! rtc.enable().unwrap();
+//! for entry in rtc.destroy_ds3231().trace().iter() {
+//!     println!("{}", entry);
+//! }
+//! ```
+
+use crate::{BitFlags, Reg, Register};
+use core::fmt;
+use embedded_hal::{i2c, spi};
+
+/// Maximum number of data bytes recorded for each operation
+pub const TRACE_DATA_SIZE: usize = 16;
+
+/// Direction of a traced operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TraceOp {
+    /// Registers were read
+    Read,
+    /// Registers were written
+    Write,
+}
+
+/// Bus on which an operation was traced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TraceBus {
+    /// I²C bus of a DS3231 or DS3232
+    I2c,
+    /// SPI device of a DS3234
+    Spi,
+}
+
+/// Register operation decoded from the bus traffic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    bus: TraceBus,
+    op: TraceOp,
+    register: u8,
+    data: [u8; TRACE_DATA_SIZE],
+    data_len: usize,
+    ok: bool,
+}
+
+impl TraceEntry {
+    fn new(bus: TraceBus, op: TraceOp, register: u8, ok: bool) -> Self {
+        TraceEntry {
+            bus,
+            op,
+            register,
+            data: [0; TRACE_DATA_SIZE],
+            data_len: 0,
+            ok,
+        }
+    }
+
+    fn extend(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            if self.data_len < TRACE_DATA_SIZE {
+                self.data[self.data_len] = *byte;
+            }
+            self.data_len += 1;
+        }
+    }
+
+    /// Bus on which the operation was done
+    pub fn bus(&self) -> TraceBus {
+        self.bus
+    }
+
+    /// Direction of the operation
+    pub fn op(&self) -> TraceOp {
+        self.op
+    }
+
+    /// Address of the first register read or written
+    pub fn register(&self) -> u8 {
+        self.register
+    }
+
+    /// Name of the first register read or written
+    pub fn register_name(&self) -> &'static str {
+        register_name(self.bus, self.register)
+    }
+
+    /// Data read or written.
+    ///
+    /// Only the first [`TRACE_DATA_SIZE`] bytes are recorded.
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.data_len.min(TRACE_DATA_SIZE)]
+    }
+
+    /// Number of bytes read or written
+    pub fn data_len(&self) -> usize {
+        self.data_len
+    }
+
+    /// Whether only part of the data was recorded
+    pub fn is_truncated(&self) -> bool {
+        self.data_len > TRACE_DATA_SIZE
+    }
+
+    /// Whether the bus operation succeeded
+    pub fn is_ok(&self) -> bool {
+        self.ok
+    }
+
+    // Flags of the register holding the byte at `index` of the data.
+    fn flags(&self, index: usize) -> &'static [(u8, &'static str)] {
+        let indirect = self.bus == TraceBus::Spi && self.register == Register::SRAM_DATA;
+        if indirect {
+            return &[];
+        }
+        register_flags(self.bus, self.register.wrapping_add(index as u8))
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            TraceOp::Read => "read",
+            TraceOp::Write => "write",
+        };
+        write!(
+            f,
+            "{} {} (0x{:02X}):",
+            op,
+            self.register_name(),
+            self.register
+        )?;
+        for (index, byte) in self.data().iter().enumerate() {
+            write!(f, " {:02X}", byte)?;
+            let flags = self.flags(index);
+            if !flags.is_empty() {
+                f.write_str(" [")?;
+                let mut separator = "";
+                for (_, name) in flags.iter().filter(|(mask, _)| byte & mask != 0) {
+                    write!(f, "{}{}", separator, name)?;
+                    separator = " ";
+                }
+                f.write_str("]")?;
+            }
+        }
+        if self.is_truncated() {
+            write!(f, " ... ({} bytes)", self.data_len)?;
+        }
+        if !self.ok {
+            f.write_str(" failed")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for TraceEntry {
+    fn format(&self, f: defmt::Formatter) {
+        let op = match self.op {
+            TraceOp::Read => "read",
+            TraceOp::Write => "write",
+        };
+        defmt::write!(
+            f,
+            "{=str} {=str} (0x{=u8:02X}):",
+            op,
+            self.register_name(),
+            self.register
+        );
+        for (index, byte) in self.data().iter().enumerate() {
+            defmt::write!(f, " {=u8:02X}", byte);
+            let flags = self.flags(index);
+            if !flags.is_empty() {
+                defmt::write!(f, " [");
+                let mut separator = "";
+                for (_, name) in flags.iter().filter(|(mask, _)| byte & mask != 0) {
+                    defmt::write!(f, "{=str}{=str}", separator, name);
+                    separator = " ";
+                }
+                defmt::write!(f, "]");
+            }
+        }
+        if self.is_truncated() {
+            defmt::write!(f, " ... ({=usize} bytes)", self.data_len);
+        }
+        if !self.ok {
+            defmt::write!(f, " failed");
+        }
+    }
+}
+
+fn register_name(bus: TraceBus, register: u8) -> &'static str {
+    match Reg::from_address(register, bus == TraceBus::Spi) {
+        Some(reg) => reg.name(),
+        None if bus == TraceBus::I2c && register >= Register::SRAM_START => "SRAM",
+        None => "RESERVED",
+    }
+}
+
+const CONTROL_FLAGS: [(u8, &str); 8] = [
+    (BitFlags::EOSC, "EOSC"),
+    (BitFlags::BBSQW, "BBSQW"),
+    (BitFlags::TEMP_CONV, "CONV"),
+    (BitFlags::RS2, "RS2"),
+    (BitFlags::RS1, "RS1"),
+    (BitFlags::INTCN, "INTCN"),
+    (BitFlags::ALARM2_INT_EN, "A2IE"),
+    (BitFlags::ALARM1_INT_EN, "A1IE"),
+];
+
+const STATUS_FLAGS: [(u8, &str); 8] = [
+    (BitFlags::OSC_STOP, "OSF"),
+    (BitFlags::BB32KHZ, "BB32KHZ"),
+    (BitFlags::CRATE1, "CRATE1"),
+    (BitFlags::CRATE0, "CRATE0"),
+    (BitFlags::EN32KHZ, "EN32KHZ"),
+    (BitFlags::BUSY, "BSY"),
+    (BitFlags::ALARM2F, "A2F"),
+    (BitFlags::ALARM1F, "A1F"),
+];
+
+const TEMP_CONV_FLAGS: [(u8, &str); 1] = [(BitFlags::TEMP_CONV_BAT, "BB_TD")];
+
+fn register_flags(bus: TraceBus, register: u8) -> &'static [(u8, &'static str)] {
+    match register {
+        Register::CONTROL => &CONTROL_FLAGS,
+        Register::STATUS => &STATUS_FLAGS,
+        Register::TEMP_CONV if bus == TraceBus::Spi => &TEMP_CONV_FLAGS,
+        _ => &[],
+    }
+}
+
+/// Fixed-size buffer keeping the last `N` traced operations
+#[derive(Debug, Clone)]
+pub struct TraceBuffer<const N: usize> {
+    entries: [Option<TraceEntry>; N],
+    next: usize,
+    dropped: usize,
+}
+
+impl<const N: usize> Default for TraceBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> TraceBuffer<N> {
+    /// Create an empty buffer.
+    pub const fn new() -> Self {
+        TraceBuffer {
+            entries: [None; N],
+            next: 0,
+            dropped: 0,
+        }
+    }
+
+    fn push(&mut self, entry: TraceEntry) {
+        if N == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.entries[self.next].replace(entry).is_some() {
+            self.dropped += 1;
+        }
+        self.next = (self.next + 1) % N;
+    }
+
+    /// Number of recorded operations
+    pub fn len(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.is_some()).count()
+    }
+
+    /// Whether no operation has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of operations that were overwritten or not recorded because
+    /// the buffer was full
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Iterate over the recorded operations from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &TraceEntry> {
+        let (newest, oldest) = self.entries.split_at(self.next);
+        oldest.iter().chain(newest).flatten()
+    }
+
+    /// Remove all recorded operations.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Whether all operations were recorded completely.
+    ///
+    /// This is the case if no operation was dropped or failed and all the
+    /// data was recorded (see [`TraceEntry::is_truncated()`]), so that the
+    /// operations can for example be replayed against a bus mock.
+    pub fn is_complete(&self) -> bool {
+        self.dropped == 0 && self.iter().all(|entry| entry.ok && !entry.is_truncated())
+    }
+}
+
+fn log_entry(entry: &TraceEntry) {
+    #[cfg(feature = "log")]
+    log::debug!("ds323x: {}", entry);
+    #[cfg(feature = "defmt")]
+    defmt::debug!("ds323x: {}", entry);
+    #[cfg(not(any(feature = "log", feature = "defmt")))]
+    let _ = entry;
+}
+
+/// I²C bus wrapper tracing the register operations of a DS3231 or DS3232
+///
+/// Operations that do not start by writing the register address are
+/// forwarded without being traced.
+#[derive(Debug)]
+pub struct TracingI2c<I2C, const N: usize = 0> {
+    i2c: I2C,
+    trace: TraceBuffer<N>,
+}
+
+impl<I2C> TracingI2c<I2C> {
+    /// Wrap the I²C bus without recording the operations.
+    pub fn new(i2c: I2C) -> Self {
+        TracingI2c {
+            i2c,
+            trace: TraceBuffer::new(),
+        }
+    }
+}
+
+impl<I2C, const N: usize> TracingI2c<I2C, N> {
+    /// Wrap the I²C bus and record the last `N` operations.
+    pub fn with_trace_buffer(i2c: I2C) -> Self {
+        TracingI2c {
+            i2c,
+            trace: TraceBuffer::new(),
+        }
+    }
+
+    /// Recorded operations
+    pub fn trace(&self) -> &TraceBuffer<N> {
+        &self.trace
+    }
+
+    /// Recorded operations, for example to clear them
+    pub fn trace_mut(&mut self) -> &mut TraceBuffer<N> {
+        &mut self.trace
+    }
+
+    /// Return the wrapped I²C bus.
+    pub fn into_inner(self) -> I2C {
+        self.i2c
+    }
+
+    // The first byte written is the register address. If there are reads,
+    // the operation reads the registers starting there.
+    fn record(&mut self, operations: &[i2c::Operation<'_>], ok: bool) {
+        let register = operations.iter().find_map(|operation| match operation {
+            i2c::Operation::Write(data) => data.first(),
+            i2c::Operation::Read(_) => None,
+        });
+        let register = match register {
+            Some(register) => *register,
+            None => return,
+        };
+        let is_read = operations
+            .iter()
+            .any(|operation| matches!(operation, i2c::Operation::Read(_)));
+        let op = if is_read {
+            TraceOp::Read
+        } else {
+            TraceOp::Write
+        };
+        let mut entry = TraceEntry::new(TraceBus::I2c, op, register, ok);
+        let mut address_skipped = false;
+        for operation in operations {
+            match operation {
+                i2c::Operation::Write(data) if !is_read && !address_skipped => {
+                    if let Some((_, data)) = data.split_first() {
+                        entry.extend(data);
+                        address_skipped = true;
+                    }
+                }
+                i2c::Operation::Write(data) if !is_read => entry.extend(data),
+                i2c::Operation::Read(data) => entry.extend(data),
+                i2c::Operation::Write(_) => (),
+            }
+        }
+        log_entry(&entry);
+        self.trace.push(entry);
+    }
+}
+
+impl<I2C: i2c::ErrorType, const N: usize> i2c::ErrorType for TracingI2c<I2C, N> {
+    type Error = I2C::Error;
+}
+
+impl<I2C: i2c::I2c, const N: usize> i2c::I2c for TracingI2c<I2C, N> {
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c.read(address, read)
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.i2c.write(address, write);
+        self.record(&[i2c::Operation::Write(write)], result.is_ok());
+        result
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let result = self.i2c.write_read(address, write, read);
+        self.record(
+            &[i2c::Operation::Write(write), i2c::Operation::Read(read)],
+            result.is_ok(),
+        );
+        result
+    }
+
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.i2c.transaction(address, operations);
+        self.record(operations, result.is_ok());
+        result
+    }
+}
+
+/// SPI device wrapper tracing the register operations of a DS3234
+#[derive(Debug)]
+pub struct TracingSpi<SPI, const N: usize = 0> {
+    spi: SPI,
+    trace: TraceBuffer<N>,
+}
+
+impl<SPI> TracingSpi<SPI> {
+    /// Wrap the SPI device without recording the operations.
+    pub fn new(spi: SPI) -> Self {
+        TracingSpi {
+            spi,
+            trace: TraceBuffer::new(),
+        }
+    }
+}
+
+impl<SPI, const N: usize> TracingSpi<SPI, N> {
+    /// Wrap the SPI device and record the last `N` operations.
+    pub fn with_trace_buffer(spi: SPI) -> Self {
+        TracingSpi {
+            spi,
+            trace: TraceBuffer::new(),
+        }
+    }
+
+    /// Recorded operations
+    pub fn trace(&self) -> &TraceBuffer<N> {
+        &self.trace
+    }
+
+    /// Recorded operations, for example to clear them
+    pub fn trace_mut(&mut self) -> &mut TraceBuffer<N> {
+        &mut self.trace
+    }
+
+    /// Return the wrapped SPI device.
+    pub fn into_inner(self) -> SPI {
+        self.spi
+    }
+
+    // The first byte sent is the register address, with the most
+    // significant bit set for writes. The data follows it.
+    fn record(&mut self, address: u8, operations: &[spi::Operation<'_, u8>], ok: bool) {
+        let op = if address & 0x80 != 0 {
+            TraceOp::Write
+        } else {
+            TraceOp::Read
+        };
+        let mut entry = TraceEntry::new(TraceBus::Spi, op, address & 0x7F, ok);
+        let mut position = 0;
+        for operation in operations {
+            let skip = if position == 0 { 1 } else { 0 };
+            let (sent, received, len): (&[u8], &[u8], usize) = match operation {
+                spi::Operation::Write(data) => (data, &[], data.len()),
+                spi::Operation::Read(data) => (&[], data, data.len()),
+                spi::Operation::Transfer(read, write) => (write, read, read.len().max(write.len())),
+                spi::Operation::TransferInPlace(data) => (&[], data, data.len()),
+                spi::Operation::DelayNs(_) => continue,
+            };
+            let data = match op {
+                TraceOp::Write => sent,
+                TraceOp::Read => received,
+            };
+            entry.extend(data.get(skip..).unwrap_or(&[]));
+            position += len;
+        }
+        log_entry(&entry);
+        self.trace.push(entry);
+    }
+}
+
+// First byte sent in the operations, which is the register address.
+fn first_sent(operations: &[spi::Operation<'_, u8>]) -> Option<u8> {
+    operations
+        .iter()
+        .find_map(|operation| match operation {
+            spi::Operation::Write(data) => data.first(),
+            spi::Operation::TransferInPlace(data) => data.first(),
+            spi::Operation::Transfer(_, write) => write.first(),
+            spi::Operation::Read(_) | spi::Operation::DelayNs(_) => None,
+        })
+        .copied()
+}
+
+impl<SPI: spi::ErrorType, const N: usize> spi::ErrorType for TracingSpi<SPI, N> {
+    type Error = SPI::Error;
+}
+
+impl<SPI: spi::SpiDevice<u8>, const N: usize> spi::SpiDevice<u8> for TracingSpi<SPI, N> {
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let address = first_sent(operations);
+        let result = self.spi.transaction(operations);
+        if let Some(address) = address {
+            self.record(address, operations, result.is_ok());
+        }
+        result
+    }
+}
@@ -6,7 +6,7 @@ use self::common::{
 };
 use ds323x::{
     Alarm1Matching as A1M, Alarm2Matching as A2M, DayAlarm1, DayAlarm2, Error, Hours, NaiveTime,
-    WeekdayAlarm1, WeekdayAlarm2,
+    Weekday, WeekdayAlarm1, WeekdayAlarm2, WeekdayNumbering,
 };
 
 #[macro_export]
@@ -189,7 +189,7 @@ mod alarm1 {
         wd_invalid_s,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(1),
             minute: 1,
             second: 60
@@ -200,7 +200,7 @@ mod alarm1 {
         wd_invalid_min,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(1),
             minute: 60,
             second: 1
@@ -211,7 +211,7 @@ mod alarm1 {
         wd_invalid_h,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(24),
             minute: 1,
             second: 1
@@ -222,7 +222,7 @@ mod alarm1 {
         wd_invalid_h_hmasm,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(24),
             minute: 1,
             second: 1
@@ -234,7 +234,7 @@ mod alarm1 {
         wd_invalid_am1,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::AM(0),
             minute: 1,
             second: 1
@@ -245,7 +245,7 @@ mod alarm1 {
         wd_invalid_am2,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::AM(13),
             minute: 1,
             second: 1
@@ -256,7 +256,7 @@ mod alarm1 {
         wd_invalid_pm1,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::PM(0),
             minute: 1,
             second: 1
@@ -267,40 +267,18 @@ mod alarm1 {
         wd_invalid_pm2,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::PM(13),
             minute: 1,
             second: 1
         },
         A1M::AllMatch
     );
-    set_invalid_alarm_test!(
-        wd_invalid_d1,
-        set_alarm1_weekday,
-        WeekdayAlarm1 {
-            weekday: 0,
-            hour: Hours::H24(1),
-            minute: 1,
-            second: 1
-        },
-        A1M::AllMatch
-    );
-    set_invalid_alarm_test!(
-        wd_invalid_d2,
-        set_alarm1_weekday,
-        WeekdayAlarm1 {
-            weekday: 32,
-            hour: Hours::H24(1),
-            minute: 1,
-            second: 1
-        },
-        A1M::AllMatch
-    );
     set_invalid_alarm_test!(
         wd_invalid_sec_sm,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(1),
             minute: 1,
             second: 60
@@ -311,7 +289,7 @@ mod alarm1 {
         wd_invalid_min_masm,
         set_alarm1_weekday,
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(1),
             minute: 60,
             second: 1
@@ -427,7 +405,7 @@ mod alarm2 {
         wd_invalid_min_mm,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(1),
             minute: 60
         },
@@ -437,7 +415,7 @@ mod alarm2 {
         wd_invalid_min,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(1),
             minute: 60
         },
@@ -447,7 +425,7 @@ mod alarm2 {
         wd_invalid_h,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(24),
             minute: 1
         },
@@ -457,7 +435,7 @@ mod alarm2 {
         wd_invalid_h_hmm,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(24),
             minute: 1
         },
@@ -467,7 +445,7 @@ mod alarm2 {
         wd_invalid_am1,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::AM(0),
             minute: 1
         },
@@ -477,7 +455,7 @@ mod alarm2 {
         wd_invalid_am2,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::AM(13),
             minute: 1
         },
@@ -487,7 +465,7 @@ mod alarm2 {
         wd_invalid_pm1,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::PM(0),
             minute: 1
         },
@@ -497,37 +475,17 @@ mod alarm2 {
         wd_invalid_pm2,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::PM(13),
             minute: 1
         },
         A2M::AllMatch
     );
-    set_invalid_alarm_test!(
-        wd_invalid_d1,
-        set_alarm2_weekday,
-        WeekdayAlarm2 {
-            weekday: 0,
-            hour: Hours::H24(1),
-            minute: 1
-        },
-        A2M::AllMatch
-    );
-    set_invalid_alarm_test!(
-        wd_invalid_d2,
-        set_alarm2_weekday,
-        WeekdayAlarm2 {
-            weekday: 32,
-            hour: Hours::H24(1),
-            minute: 1
-        },
-        A2M::AllMatch
-    );
     set_invalid_alarm_test!(
         wd_invalid_minute,
         set_alarm2_weekday,
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(1),
             minute: 60
         },
@@ -753,7 +711,7 @@ mod alarm1_weekday {
         ALARM1_SECONDS,
         [4, 3, 2, BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3,
             second: 4
@@ -766,7 +724,7 @@ mod alarm1_weekday {
         ALARM1_SECONDS,
         [4, 3, 0b0100_0010, BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::AM(2),
             minute: 3,
             second: 4
@@ -779,33 +737,20 @@ mod alarm1_weekday {
         ALARM1_SECONDS,
         [4, 3, 0b0110_0010, BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::PM(2),
             minute: 3,
             second: 4
         },
         A1M::AllMatch
     );
-    set_alarm_test!(
-        match_hms_ignore_incorrect_wd,
-        set_alarm1_weekday,
-        ALARM1_SECONDS,
-        [4, 3, 2, AM | BF::WEEKDAY | 1],
-        WeekdayAlarm1 {
-            weekday: 0,
-            hour: Hours::H24(2),
-            minute: 3,
-            second: 4
-        },
-        A1M::HoursMinutesAndSecondsMatch
-    );
     set_alarm_test!(
         match_hms,
         set_alarm1_weekday,
         ALARM1_SECONDS,
         [4, 3, 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3,
             second: 4
@@ -818,7 +763,7 @@ mod alarm1_weekday {
         ALARM1_SECONDS,
         [4, 3, AM | 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3,
             second: 4
@@ -831,7 +776,7 @@ mod alarm1_weekday {
         ALARM1_SECONDS,
         [4, AM | 3, AM | 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3,
             second: 4
@@ -844,7 +789,7 @@ mod alarm1_weekday {
         ALARM1_SECONDS,
         [4, AM, AM | 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 60,
             second: 4
@@ -857,7 +802,7 @@ mod alarm1_weekday {
         ALARM1_SECONDS,
         [AM | 4, AM | 3, AM | 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3,
             second: 4
@@ -870,7 +815,7 @@ mod alarm1_weekday {
         ALARM1_SECONDS,
         [AM, AM | 3, AM | 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm1 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3,
             second: 60
@@ -1006,7 +951,7 @@ mod alarm2_weekday {
         ALARM2_MINUTES,
         [3, 2, BF::WEEKDAY | 1],
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3
         },
@@ -1018,7 +963,7 @@ mod alarm2_weekday {
         ALARM2_MINUTES,
         [3, 0b0100_0010, BF::WEEKDAY | 1],
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::AM(2),
             minute: 3
         },
@@ -1030,7 +975,7 @@ mod alarm2_weekday {
         ALARM2_MINUTES,
         [3, 0b0110_0010, BF::WEEKDAY | 1],
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::PM(2),
             minute: 3
         },
@@ -1042,19 +987,7 @@ mod alarm2_weekday {
         ALARM2_MINUTES,
         [3, 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm2 {
-            weekday: 1,
-            hour: Hours::H24(2),
-            minute: 3
-        },
-        A2M::HoursAndMinutesMatch
-    );
-    set_alarm_test!(
-        match_hm_ignore_incorrect_wd,
-        set_alarm2_weekday,
-        ALARM2_MINUTES,
-        [3, 2, AM | BF::WEEKDAY | 1],
-        WeekdayAlarm2 {
-            weekday: 0,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3
         },
@@ -1066,7 +999,7 @@ mod alarm2_weekday {
         ALARM2_MINUTES,
         [3, AM | 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3
         },
@@ -1078,7 +1011,7 @@ mod alarm2_weekday {
         ALARM2_MINUTES,
         [3, AM, AM | BF::WEEKDAY | 1],
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(24),
             minute: 3
         },
@@ -1090,7 +1023,7 @@ mod alarm2_weekday {
         ALARM2_MINUTES,
         [AM | 3, AM | 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 3
         },
@@ -1102,10 +1035,125 @@ mod alarm2_weekday {
         ALARM2_MINUTES,
         [AM, AM | 2, AM | BF::WEEKDAY | 1],
         WeekdayAlarm2 {
-            weekday: 1,
+            weekday: Weekday::Sun,
             hour: Hours::H24(2),
             minute: 60
         },
         A2M::OncePerMinute
     );
 }
+
+macro_rules! set_alarm_monday_first_test {
+    ($name:ident, $method:ident, $register:ident, [ $( $registers:expr ),+ ], $( $value:expr ),+) => {
+        mod $name {
+            use super::*;
+            #[test]
+            fn can_set_ds3231() {
+                let trans = [I2cTrans::write(DEV_ADDR, vec![Register::$register, $( $registers ),*])];
+                let mut dev = new_ds3231(&trans);
+                dev.set_weekday_numbering(WeekdayNumbering::MondayFirst);
+                dev.$method($($value),*).unwrap();
+                destroy_ds3231(dev);
+            }
+            #[test]
+            fn can_set_ds3232() {
+                let trans = [I2cTrans::write(DEV_ADDR, vec![Register::$register, $( $registers ),*])];
+                let mut dev = new_ds3232(&trans);
+                dev.set_weekday_numbering(WeekdayNumbering::MondayFirst);
+                dev.$method($($value),*).unwrap();
+                destroy_ds3232(dev);
+            }
+            #[test]
+            fn can_set_ds3234() {
+                let trans = [
+                    SpiTrans::transaction_start(),
+                    SpiTrans::write_vec(vec![Register::$register + 0x80, $( $registers ),*]),
+                    SpiTrans::transaction_end(),
+                ];
+                let mut dev = new_ds3234(&trans);
+                dev.set_weekday_numbering(WeekdayNumbering::MondayFirst);
+                dev.$method($($value),*).unwrap();
+                destroy_ds3234(dev);
+            }
+        }
+    };
+}
+
+mod weekday_numbering {
+    use super::*;
+    set_alarm_test!(
+        alarm1_saturday_sunday_first,
+        set_alarm1_weekday,
+        ALARM1_SECONDS,
+        [4, 3, 2, BF::WEEKDAY | 7],
+        WeekdayAlarm1 {
+            weekday: Weekday::Sat,
+            hour: Hours::H24(2),
+            minute: 3,
+            second: 4
+        },
+        A1M::AllMatch
+    );
+    set_alarm_monday_first_test!(
+        alarm1_monday_monday_first,
+        set_alarm1_weekday,
+        ALARM1_SECONDS,
+        [4, 3, 2, BF::WEEKDAY | 1],
+        WeekdayAlarm1 {
+            weekday: Weekday::Mon,
+            hour: Hours::H24(2),
+            minute: 3,
+            second: 4
+        },
+        A1M::AllMatch
+    );
+    set_alarm_monday_first_test!(
+        alarm1_sunday_monday_first,
+        set_alarm1_weekday,
+        ALARM1_SECONDS,
+        [4, 3, 2, BF::WEEKDAY | 7],
+        WeekdayAlarm1 {
+            weekday: Weekday::Sun,
+            hour: Hours::H24(2),
+            minute: 3,
+            second: 4
+        },
+        A1M::AllMatch
+    );
+    set_alarm_test!(
+        alarm2_monday_sunday_first,
+        set_alarm2_weekday,
+        ALARM2_MINUTES,
+        [3, 2, BF::WEEKDAY | 2],
+        WeekdayAlarm2 {
+            weekday: Weekday::Mon,
+            hour: Hours::H24(2),
+            minute: 3
+        },
+        A2M::AllMatch
+    );
+    set_alarm_monday_first_test!(
+        alarm2_monday_monday_first,
+        set_alarm2_weekday,
+        ALARM2_MINUTES,
+        [3, 2, BF::WEEKDAY | 1],
+        WeekdayAlarm2 {
+            weekday: Weekday::Mon,
+            hour: Hours::H24(2),
+            minute: 3
+        },
+        A2M::AllMatch
+    );
+    set_alarm_monday_first_test!(
+        alarm2_sunday_monday_first,
+        set_alarm2_weekday,
+        ALARM2_MINUTES,
+        [3, 2, BF::WEEKDAY | 7],
+        WeekdayAlarm2 {
+            weekday: Weekday::Sun,
+            hour: Hours::H24(2),
+            minute: 3
+        },
+        A2M::AllMatch
+    );
+}
@@ -1,4 +1,5 @@
 use ds323x::{ic, interface, Ds323x};
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
 use embedded_hal_mock::eh1::{
     i2c::{Mock as I2cMock, Transaction as I2cTrans},
     spi::{Mock as SpiMock, Transaction as SpiTrans},
@@ -23,8 +24,11 @@ impl Register {
     pub const DOW: u8 = 0x03;
     pub const DOM: u8 = 0x04;
     pub const MONTH: u8 = 0x05;
+    pub const YEAR: u8 = 0x06;
     pub const ALARM1_SECONDS: u8 = 0x07;
+    pub const ALARM1_HOURS: u8 = 0x09;
     pub const ALARM2_MINUTES: u8 = 0x0B;
+    pub const ALARM2_HOURS: u8 = 0x0C;
     pub const CONTROL: u8 = 0x0E;
     pub const STATUS: u8 = 0x0F;
     pub const AGING_OFFSET: u8 = 0x10;
@@ -57,6 +61,58 @@ impl BitFlags {
     pub const WEEKDAY: u8 = 0b0100_0000;
 }
 
+/// Simulated register map of the DS3231/DS3232
+#[allow(unused)]
+pub struct FakeI2c {
+    pub registers: [u8; 256],
+}
+
+impl ErrorType for FakeI2c {
+    type Error = ErrorKind;
+}
+
+impl I2c for FakeI2c {
+    fn transaction(
+        &mut self,
+        _address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut pointer = None;
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => {
+                    for byte in data.iter() {
+                        match pointer {
+                            None => pointer = Some(*byte),
+                            Some(p) => {
+                                self.registers[usize::from(p)] = *byte;
+                                pointer = Some(p.wrapping_add(1));
+                            }
+                        }
+                    }
+                }
+                Operation::Read(data) => {
+                    let mut p = pointer.unwrap_or(0);
+                    for byte in data.iter_mut() {
+                        *byte = self.registers[usize::from(p)];
+                        p = p.wrapping_add(1);
+                    }
+                    pointer = Some(p);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for FakeI2c {
+    fn default() -> Self {
+        FakeI2c {
+            registers: [0; 256],
+        }
+    }
+}
+
 pub fn new_ds3231(
     transactions: &[I2cTrans],
 ) -> Ds323x<interface::I2cInterface<I2cMock>, ic::DS3231> {
@@ -87,6 +143,85 @@ pub fn destroy_ds3234(dev: Ds323x<interface::SpiInterface<SpiMock<u8>>, ic::DS32
     dev.destroy_ds3234().done();
 }
 
+#[allow(unused)]
+pub const DS3232_SRAM_START: u8 = 0x14;
+#[allow(unused)]
+pub const DS3234_SRAM_ADDRESS: u8 = 0x18;
+#[allow(unused)]
+pub const DS3234_SRAM_DATA: u8 = 0x19;
+
+#[allow(unused)]
+pub fn i2c_read(register: u8, value: u8) -> Vec<I2cTrans> {
+    i2c_read_data(register, vec![value])
+}
+
+#[allow(unused)]
+pub fn i2c_read_data(register: u8, data: Vec<u8>) -> Vec<I2cTrans> {
+    vec![I2cTrans::write_read(DEVICE_ADDRESS, vec![register], data)]
+}
+
+#[allow(unused)]
+pub fn i2c_write(register: u8, value: u8) -> Vec<I2cTrans> {
+    vec![I2cTrans::write(DEVICE_ADDRESS, vec![register, value])]
+}
+
+#[allow(unused)]
+pub fn spi_read(register: u8, value: u8) -> Vec<SpiTrans<u8>> {
+    vec![
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![register, 0], vec![register, value]),
+        SpiTrans::transaction_end(),
+    ]
+}
+
+#[allow(unused)]
+pub fn spi_write(register: u8, value: u8) -> Vec<SpiTrans<u8>> {
+    vec![
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![register + 0x80, value]),
+        SpiTrans::transaction_end(),
+    ]
+}
+
+#[allow(unused)]
+pub fn ds3232_sram_read(offset: u8, data: Vec<u8>) -> Vec<I2cTrans> {
+    i2c_read_data(DS3232_SRAM_START + offset, data)
+}
+
+#[allow(unused)]
+pub fn ds3232_sram_write(offset: u8, data: Vec<u8>) -> Vec<I2cTrans> {
+    vec![
+        I2cTrans::transaction_start(DEVICE_ADDRESS),
+        I2cTrans::write(DEVICE_ADDRESS, vec![DS3232_SRAM_START + offset]),
+        I2cTrans::write(DEVICE_ADDRESS, data),
+        I2cTrans::transaction_end(DEVICE_ADDRESS),
+    ]
+}
+
+#[allow(unused)]
+pub fn ds3234_sram_read(offset: u8, data: Vec<u8>) -> Vec<SpiTrans<u8>> {
+    let mut trans = spi_write(DS3234_SRAM_ADDRESS, offset);
+    trans.extend([
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![DS3234_SRAM_DATA]),
+        SpiTrans::read_vec(data),
+        SpiTrans::transaction_end(),
+    ]);
+    trans
+}
+
+#[allow(unused)]
+pub fn ds3234_sram_write(offset: u8, data: Vec<u8>) -> Vec<SpiTrans<u8>> {
+    let mut trans = spi_write(DS3234_SRAM_ADDRESS, offset);
+    trans.extend([
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![DS3234_SRAM_DATA + 0x80]),
+        SpiTrans::write_vec(data),
+        SpiTrans::transaction_end(),
+    ]);
+    trans
+}
+
 #[macro_export]
 macro_rules! get_test {
     ($name:ident, $method:ident, $create_method:ident, $destroy_method:ident, $expected:expr, $transactions:expr) => {
@@ -117,12 +252,32 @@ macro_rules! set_test {
 macro_rules! assert_invalid_input_data {
     ($result:expr) => {
         match $result {
-            Err(Error::InvalidInputData) => (),
+            Err(Error::InvalidInputData { .. }) => (),
+            _ => panic!("InvalidInputData error not returned."),
+        }
+    };
+    ($result:expr, $field:ident) => {
+        match $result {
+            Err(Error::InvalidInputData { field }) => {
+                assert_eq!(ds323x::InputField::$field, field)
+            }
             _ => panic!("InvalidInputData error not returned."),
         }
     };
 }
 
+#[macro_export]
+macro_rules! assert_invalid_device_state {
+    ($result:expr, $register:ident) => {
+        match $result {
+            Err(Error::InvalidDeviceState { register }) => {
+                assert_eq!(Register::$register, register)
+            }
+            _ => panic!("InvalidDeviceState error not returned."),
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! set_invalid_test {
     ($name:ident, $method:ident, $create_method:ident, $destroy_method:ident, $value:expr) => {
@@ -1,11 +1,13 @@
-use ds323x::SqWFreq;
+use ds323x::{
+    Config, Error, IntSqwMode, ModeChangeError, PowerProfile, ResetOptions, SqWFreq, TempConvRate,
+};
 use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
 
 mod common;
 use self::common::{
-    destroy_ds3231, destroy_ds3232, destroy_ds3234, new_ds3231, new_ds3232, new_ds3234,
-    BitFlags as BF, Register, CONTROL_POR_VALUE, DEVICE_ADDRESS as DEV_ADDR, DS3231_POR_STATUS,
-    DS323X_POR_STATUS,
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, i2c_write, new_ds3231, new_ds3232, new_ds3234,
+    spi_write, BitFlags as BF, Register, CONTROL_POR_VALUE, DEVICE_ADDRESS as DEV_ADDR,
+    DS3231_POR_STATUS, DS323X_POR_STATUS,
 };
 
 macro_rules! call_triple_test {
@@ -291,3 +293,491 @@ set_param_test!(
     SqWFreq::_8_192Hz,
     CONTROL_POR_VALUE | BF::RS2 | BF::RS1
 );
+
+fn i2c_read_config(control: u8, status: u8, aging_offset: u8) -> Vec<I2cTrans> {
+    vec![I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::CONTROL],
+        vec![control, status, aging_offset],
+    )]
+}
+
+fn spi_read_config(control: u8, status: u8, aging_offset: u8) -> Vec<SpiTrans<u8>> {
+    vec![
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(
+            vec![Register::CONTROL, 0, 0, 0],
+            vec![Register::CONTROL, control, status, aging_offset],
+        ),
+        SpiTrans::transaction_end(),
+    ]
+}
+
+const ALARM_FLAGS: u8 = BF::ALARM2F | BF::ALARM1F;
+
+macro_rules! config_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident, $write:ident, $read_config:ident,
+    $status:expr) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn applying_default_does_not_write() {
+                let mut dev = $create_method(&[]);
+                dev.apply(Config::default()).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn apply_control_only() {
+                let control = BF::EOSC | BF::BBSQW | BF::RS1 | BF::ALARM2_INT_EN;
+                let mut dev = $create_method(&$write(Register::CONTROL, control));
+                let config = Config {
+                    oscillator_enabled: false,
+                    int_sqw_mode: IntSqwMode::SquareWave,
+                    square_wave_frequency: SqWFreq::_1_024Hz,
+                    square_wave_on_battery: true,
+                    alarm2_interrupts: true,
+                    ..Config::default()
+                };
+                dev.apply(config).unwrap();
+                // applying it again does not write anything
+                dev.apply(config).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn apply_32khz_and_aging_offset() {
+                let mut trans = $write(Register::STATUS, ($status & !BF::EN32KHZ) | ALARM_FLAGS);
+                trans.extend($write(Register::AGING_OFFSET, 0b1111_0001));
+                let mut dev = $create_method(&trans);
+                let config = Config {
+                    output_32khz: false,
+                    aging_offset: -15,
+                    ..Config::default()
+                };
+                dev.apply(config).unwrap();
+                dev.apply(config).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn apply_after_individual_setters_uses_cache() {
+                let mut trans = $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM1_INT_EN);
+                trans.extend($write(Register::AGING_OFFSET, 3));
+                trans.extend($write(Register::CONTROL, CONTROL_POR_VALUE));
+                let mut dev = $create_method(&trans);
+                dev.enable_alarm1_interrupts().unwrap();
+                dev.set_aging_offset(3).unwrap();
+                dev.apply(Config {
+                    aging_offset: 3,
+                    ..Config::default()
+                })
+                .unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn current_config_of_por_state_is_default() {
+                let mut dev = $create_method(&$read_config(
+                    CONTROL_POR_VALUE | BF::TEMP_CONV,
+                    $status | BF::BUSY | ALARM_FLAGS,
+                    0,
+                ));
+                assert_eq!(Config::default(), dev.current_config().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn current_config_updates_cache() {
+                let control = BF::RS2 | BF::ALARM1_INT_EN;
+                let mut trans = $read_config(control, $status & !BF::EN32KHZ, 0xFF);
+                trans.extend($write(Register::CONTROL, control | BF::INTCN));
+                let mut dev = $create_method(&trans);
+                let config = Config {
+                    int_sqw_mode: IntSqwMode::SquareWave,
+                    square_wave_frequency: SqWFreq::_4_096Hz,
+                    output_32khz: false,
+                    alarm1_interrupts: true,
+                    aging_offset: -1,
+                    ..Config::default()
+                };
+                assert_eq!(config, dev.current_config().unwrap());
+                dev.apply(Config {
+                    int_sqw_mode: IntSqwMode::Interrupt,
+                    ..config
+                })
+                .unwrap();
+                $destroy_method(dev);
+            }
+        }
+    };
+}
+
+mod config {
+    use super::*;
+
+    config_test!(
+        for_ds3231,
+        new_ds3231,
+        destroy_ds3231,
+        i2c_write,
+        i2c_read_config,
+        DS3231_POR_STATUS
+    );
+    config_test!(
+        for_ds3232,
+        new_ds3232,
+        destroy_ds3232,
+        i2c_write,
+        i2c_read_config,
+        DS323X_POR_STATUS
+    );
+    config_test!(
+        for_ds3234,
+        new_ds3234,
+        destroy_ds3234,
+        spi_write,
+        spi_read_config,
+        DS323X_POR_STATUS
+    );
+
+    #[test]
+    fn ds3231_ignores_settings_not_available() {
+        let mut dev = new_ds3231(&[]);
+        dev.apply(Config {
+            output_32khz_on_battery: false,
+            temperature_conversion_rate: TempConvRate::_512s,
+            ..Config::default()
+        })
+        .unwrap();
+        destroy_ds3231(dev);
+    }
+
+    #[test]
+    fn ds3231_current_config_keeps_default_for_settings_not_available() {
+        let mut dev = new_ds3231(&i2c_read_config(CONTROL_POR_VALUE, 0, 0));
+        let config = Config {
+            output_32khz: false,
+            ..Config::default()
+        };
+        assert_eq!(config, dev.current_config().unwrap());
+        destroy_ds3231(dev);
+    }
+
+    #[test]
+    fn ds3232_apply_battery_settings() {
+        let status = BF::OSC_STOP | BF::CRATE1 | BF::CRATE0 | BF::EN32KHZ | ALARM_FLAGS;
+        let mut dev = new_ds3232(&i2c_write(Register::STATUS, status));
+        let config = Config {
+            output_32khz_on_battery: false,
+            temperature_conversion_rate: TempConvRate::_512s,
+            ..Config::default()
+        };
+        dev.apply(config).unwrap();
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn ds3234_current_config_battery_settings() {
+        let mut dev = new_ds3234(&spi_read_config(CONTROL_POR_VALUE, BF::CRATE0, 0));
+        let config = Config {
+            output_32khz: false,
+            output_32khz_on_battery: false,
+            temperature_conversion_rate: TempConvRate::_128s,
+            ..Config::default()
+        };
+        assert_eq!(config, dev.current_config().unwrap());
+        destroy_ds3234(dev);
+    }
+}
+
+mod battery_backup {
+    use super::*;
+
+    fn spi_read(register: u8, value: u8) -> Vec<SpiTrans<u8>> {
+        vec![
+            SpiTrans::transaction_start(),
+            SpiTrans::transfer_in_place(vec![register, 0], vec![register, value]),
+            SpiTrans::transaction_end(),
+        ]
+    }
+
+    #[test]
+    fn ds3231_disables_32khz_output() {
+        let status = DS3231_POR_STATUS & !BF::EN32KHZ;
+        let mut trans = i2c_read_config(CONTROL_POR_VALUE, DS3231_POR_STATUS, 0);
+        trans.extend(i2c_write(Register::STATUS, status | ALARM_FLAGS));
+        trans.extend(i2c_read_config(CONTROL_POR_VALUE, status, 0));
+        trans.extend(i2c_write(Register::STATUS, DS3231_POR_STATUS | ALARM_FLAGS));
+        let mut dev = new_ds3231(&trans);
+        let profile = dev.prepare_for_battery_backup().unwrap();
+        let expected = PowerProfile {
+            output_32khz: Some(true),
+            ..PowerProfile::default()
+        };
+        assert_eq!(expected, profile);
+        dev.restore_power_profile(profile).unwrap();
+        destroy_ds3231(dev);
+    }
+
+    #[test]
+    fn keeps_square_wave_on_battery_for_alarm_interrupts() {
+        let control = CONTROL_POR_VALUE | BF::BBSQW | BF::ALARM1_INT_EN;
+        let mut dev = new_ds3231(&i2c_read_config(control, BF::OSC_STOP, 0));
+        let profile = dev.prepare_for_battery_backup().unwrap();
+        assert_eq!(PowerProfile::default(), profile);
+        destroy_ds3231(dev);
+    }
+
+    #[test]
+    fn disables_square_wave_on_battery() {
+        let control = BF::BBSQW | BF::RS2 | BF::RS1 | BF::ALARM1_INT_EN;
+        let mut trans = i2c_read_config(control, BF::OSC_STOP, 0);
+        trans.extend(i2c_write(Register::CONTROL, control & !BF::BBSQW));
+        let mut dev = new_ds3231(&trans);
+        let profile = dev.prepare_for_battery_backup().unwrap();
+        let expected = PowerProfile {
+            square_wave_on_battery: Some(true),
+            ..PowerProfile::default()
+        };
+        assert_eq!(expected, profile);
+        destroy_ds3231(dev);
+    }
+
+    #[test]
+    fn ds3232_disables_32khz_output_on_battery_and_reduces_conversion_rate() {
+        let status = BF::OSC_STOP | BF::CRATE1 | BF::CRATE0 | BF::EN32KHZ;
+        let mut trans = i2c_read_config(CONTROL_POR_VALUE, DS323X_POR_STATUS | BF::CRATE0, 0);
+        trans.extend(i2c_write(Register::STATUS, status | ALARM_FLAGS));
+        trans.extend(i2c_read_config(CONTROL_POR_VALUE, status, 0));
+        trans.extend(i2c_write(
+            Register::STATUS,
+            DS323X_POR_STATUS | BF::CRATE0 | ALARM_FLAGS,
+        ));
+        let mut dev = new_ds3232(&trans);
+        let profile = dev.prepare_for_battery_backup().unwrap();
+        let expected = PowerProfile {
+            output_32khz_on_battery: Some(true),
+            temperature_conversion_rate: Some(TempConvRate::_128s),
+            ..PowerProfile::default()
+        };
+        assert_eq!(expected, profile);
+        dev.restore_power_profile(profile).unwrap();
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn ds3234_disables_temperature_conversions_on_battery() {
+        let status = BF::OSC_STOP | BF::CRATE1 | BF::CRATE0 | BF::EN32KHZ;
+        let mut trans = spi_read_config(CONTROL_POR_VALUE, DS323X_POR_STATUS, 0);
+        trans.extend(spi_write(Register::STATUS, status | ALARM_FLAGS));
+        trans.extend(spi_read(Register::TEMP_CONV, 0));
+        trans.extend(spi_write(Register::TEMP_CONV, BF::TEMP_CONV_BAT));
+        trans.extend(spi_read_config(CONTROL_POR_VALUE, status, 0));
+        trans.extend(spi_write(Register::STATUS, DS323X_POR_STATUS | ALARM_FLAGS));
+        trans.extend(spi_write(Register::TEMP_CONV, 0));
+        let mut dev = new_ds3234(&trans);
+        let profile = dev.prepare_for_battery_backup().unwrap();
+        let expected = PowerProfile {
+            output_32khz_on_battery: Some(true),
+            temperature_conversion_rate: Some(TempConvRate::_64s),
+            temperature_conversions_on_battery: Some(true),
+            ..PowerProfile::default()
+        };
+        assert_eq!(expected, profile);
+        dev.restore_power_profile(profile).unwrap();
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn ds3234_does_not_write_if_already_lowest_power() {
+        let status = BF::CRATE1 | BF::CRATE0 | BF::EN32KHZ;
+        let mut trans = spi_read_config(CONTROL_POR_VALUE, status, 0);
+        trans.extend(spi_read(Register::TEMP_CONV, BF::TEMP_CONV_BAT));
+        let mut dev = new_ds3234(&trans);
+        let profile = dev.prepare_for_battery_backup().unwrap();
+        assert_eq!(PowerProfile::default(), profile);
+        destroy_ds3234(dev);
+    }
+}
+
+mod typestate {
+    use super::*;
+    use embedded_hal::i2c::ErrorKind;
+
+    #[test]
+    fn into_interrupt_mode() {
+        let control = CONTROL_POR_VALUE & !BF::INTCN;
+        let mut trans = i2c_write(Register::CONTROL, control);
+        trans.extend(i2c_write(Register::CONTROL, control | BF::INTCN));
+        trans.extend(i2c_write(
+            Register::CONTROL,
+            control | BF::INTCN | BF::ALARM1_INT_EN,
+        ));
+        let mut dev = new_ds3231(&trans);
+        dev.use_int_sqw_output_as_square_wave().unwrap();
+        let mut dev = dev.into_interrupt_mode().unwrap();
+        dev.enable_alarm1_interrupts().unwrap();
+        dev.destroy_ds3231().done();
+    }
+
+    #[test]
+    fn into_square_wave_mode() {
+        let control = CONTROL_POR_VALUE & !BF::INTCN;
+        let mut trans = spi_write(Register::CONTROL, control);
+        trans.extend(spi_write(Register::CONTROL, control & !BF::RS2));
+        let dev = new_ds3234(&trans);
+        let mut dev = dev.into_square_wave_mode().unwrap();
+        dev.set_square_wave_frequency(SqWFreq::_1_024Hz).unwrap();
+        dev.destroy_ds3234().done();
+    }
+
+    #[test]
+    fn can_change_between_modes() {
+        let control = CONTROL_POR_VALUE & !BF::INTCN;
+        let mut trans = i2c_write(Register::CONTROL, control);
+        trans.extend(i2c_write(Register::CONTROL, control | BF::INTCN));
+        trans.extend(i2c_write(Register::CONTROL, control));
+        let dev = new_ds3232(&trans);
+        let dev = dev.into_square_wave_mode().unwrap();
+        let dev = dev.into_interrupt_mode().unwrap();
+        let mut dev = dev.into_unchecked_mode();
+        dev.use_int_sqw_output_as_square_wave().unwrap();
+        dev.destroy_ds3232().done();
+    }
+
+    #[test]
+    fn returns_device_on_error() {
+        let mut trans = i2c_write(Register::CONTROL, CONTROL_POR_VALUE & !BF::INTCN);
+        trans[0] = trans[0].clone().with_error(ErrorKind::Other);
+        trans.extend(i2c_write(
+            Register::CONTROL,
+            CONTROL_POR_VALUE | BF::ALARM2_INT_EN,
+        ));
+        let dev = new_ds3231(&trans);
+        let mut dev = match dev.into_square_wave_mode() {
+            Err(ModeChangeError {
+                error: Error::Comm(_),
+                dev,
+            }) => dev,
+            _ => panic!("ModeChangeError not returned."),
+        };
+        dev.enable_alarm2_interrupts().unwrap();
+        destroy_ds3231(dev);
+    }
+}
+
+mod reset {
+    use super::*;
+
+    fn i2c_reset(control: u8, status: u8) -> Vec<I2cTrans> {
+        let mut trans = i2c_write(Register::CONTROL, control);
+        trans.push(I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::ALARM1_SECONDS, 0, 0, 0, 0, 0, 0, 0],
+        ));
+        trans.extend(i2c_write(Register::STATUS, status));
+        trans.extend(i2c_write(Register::AGING_OFFSET, 0));
+        trans
+    }
+
+    fn spi_reset(control: u8) -> Vec<SpiTrans<u8>> {
+        let mut trans = spi_write(Register::CONTROL, control);
+        trans.extend([
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![Register::ALARM1_SECONDS + 0x80, 0, 0, 0, 0, 0, 0, 0]),
+            SpiTrans::transaction_end(),
+        ]);
+        trans.extend(spi_write(Register::STATUS, DS323X_POR_STATUS));
+        trans.extend(spi_write(Register::AGING_OFFSET, 0));
+        trans.extend(spi_write(Register::TEMP_CONV, 0));
+        trans
+    }
+
+    #[test]
+    fn ds3231_resets_registers_and_cache() {
+        let mut trans = i2c_write(Register::CONTROL, CONTROL_POR_VALUE | BF::EOSC);
+        trans.extend(i2c_write(Register::AGING_OFFSET, 5));
+        trans.extend(i2c_reset(CONTROL_POR_VALUE, DS3231_POR_STATUS));
+        trans.extend(i2c_write(Register::CONTROL, CONTROL_POR_VALUE | BF::BBSQW));
+        let mut dev = new_ds3231(&trans);
+        dev.disable().unwrap();
+        dev.set_aging_offset(5).unwrap();
+        dev.reset_to_defaults(ResetOptions::default()).unwrap();
+        // the cached values correspond to the power-on reset state
+        dev.apply(Config::default()).unwrap();
+        dev.enable_square_wave().unwrap();
+        destroy_ds3231(dev);
+    }
+
+    #[test]
+    fn ds3231_ignores_sram_option() {
+        let trans = i2c_reset(CONTROL_POR_VALUE, DS3231_POR_STATUS);
+        let mut dev = new_ds3231(&trans);
+        dev.reset_to_defaults(ResetOptions {
+            clear_sram: true,
+            ..ResetOptions::default()
+        })
+        .unwrap();
+        destroy_ds3231(dev);
+    }
+
+    #[test]
+    fn can_stop_oscillator_on_battery() {
+        let mut trans = i2c_reset(CONTROL_POR_VALUE | BF::EOSC, DS323X_POR_STATUS);
+        trans.extend(i2c_write(Register::CONTROL, CONTROL_POR_VALUE));
+        let mut dev = new_ds3232(&trans);
+        dev.reset_to_defaults(ResetOptions {
+            stop_oscillator_on_battery: true,
+            ..ResetOptions::default()
+        })
+        .unwrap();
+        dev.enable().unwrap();
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn ds3232_clears_sram() {
+        let mut trans = i2c_reset(CONTROL_POR_VALUE, DS323X_POR_STATUS);
+        trans.extend([
+            I2cTrans::transaction_start(DEV_ADDR),
+            I2cTrans::write(DEV_ADDR, vec![0x14]),
+            I2cTrans::write(DEV_ADDR, vec![0; 236]),
+            I2cTrans::transaction_end(DEV_ADDR),
+        ]);
+        let mut dev = new_ds3232(&trans);
+        dev.reset_to_defaults(ResetOptions {
+            clear_sram: true,
+            ..ResetOptions::default()
+        })
+        .unwrap();
+        destroy_ds3232(dev);
+    }
+
+    #[test]
+    fn ds3234_resets_temperature_conversions() {
+        let mut dev = new_ds3234(&spi_reset(CONTROL_POR_VALUE));
+        dev.reset_to_defaults(ResetOptions::default()).unwrap();
+        destroy_ds3234(dev);
+    }
+
+    #[test]
+    fn ds3234_clears_sram() {
+        let mut trans = spi_reset(CONTROL_POR_VALUE);
+        trans.extend(spi_write(0x18, 0));
+        trans.extend([
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![0x19 + 0x80]),
+            SpiTrans::write_vec(vec![0; 256]),
+            SpiTrans::transaction_end(),
+        ]);
+        let mut dev = new_ds3234(&trans);
+        dev.reset_to_defaults(ResetOptions {
+            clear_sram: true,
+            ..ResetOptions::default()
+        })
+        .unwrap();
+        destroy_ds3234(dev);
+    }
+}
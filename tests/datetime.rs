@@ -7,7 +7,9 @@ use self::common::{
 };
 #[allow(unused)] // Rust 1.31.0 is confused due to the macros
 use ds323x::Rtcc;
-use ds323x::{DateTimeAccess, Error, Hours, NaiveDate, NaiveTime};
+use ds323x::{
+    DateTimeAccess, Error, HourMode, Hours, NaiveDate, NaiveTime, Weekday, WeekdayNumbering,
+};
 
 fn new_datetime(y: i32, mo: u32, d: u32, h: u32, min: u32, s: u32) -> NaiveDateTime {
     NaiveDate::from_ymd_opt(y, mo, d)
@@ -217,7 +219,17 @@ mod year {
         0
     );
 
-    set_invalid_param_range_test!(invalid, set_year, 1999, 2101);
+    read_set_param_write_two_test!(
+        century1_end_set,
+        set_year,
+        2199,
+        MONTH,
+        0b0001_0010,
+        0b1001_0010,
+        0b1001_1001
+    );
+
+    set_invalid_param_range_test!(invalid, set_year, 1999, 2200);
 }
 
 macro_rules! invalid_dt_test {
@@ -233,7 +245,7 @@ macro_rules! invalid_dt_test {
             }
             #[test]
             fn datetime_too_big() {
-                let dt = new_datetime(2101, 1, 2, 3, 4, 5);
+                let dt = new_datetime(2200, 1, 2, 3, 4, 5);
                 let mut dev = $create_method(&[]);
                 assert_invalid_input_data!(dev.set_datetime(&dt));
                 $destroy_method(dev);
@@ -247,7 +259,7 @@ macro_rules! invalid_dt_test {
             }
             #[test]
             fn date_too_big() {
-                let d = new_date(2101, 1, 2);
+                let d = new_date(2200, 1, 2);
                 let mut dev = $create_method(&[]);
                 assert_invalid_input_data!(dev.set_date(&d));
                 $destroy_method(dev);
@@ -376,6 +388,798 @@ macro_rules! dt_test {
     };
 }
 
+macro_rules! century_base_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident,
+    $mac_trans_read:ident, $mac_trans_write:ident) => {
+        mod $name {
+            use super::*;
+            #[test]
+            fn default_century_base() {
+                let dev = $create_method(&[]);
+                assert_eq!(2000, dev.century_base());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn cannot_set_invalid_century_base() {
+                let mut dev = $create_method(&[]);
+                assert_invalid_input_data!(dev.set_century_base(2050));
+                assert_invalid_input_data!(dev.set_century_base(65400));
+                assert_eq!(2000, dev.century_base());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn get_year_with_century_base() {
+                let mut dev =
+                    $create_method(&$mac_trans_read!(MONTH, [0b1000_0001, 0b1001_1001], [0, 0]));
+                dev.set_century_base(2100).unwrap();
+                assert_eq!(2299, dev.year().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_date_with_century_base() {
+                let d = new_date(2250, 8, 13);
+                let mut dev = $create_method(&$mac_trans_write!(
+                    DOW,
+                    [0b0000_0011, 0b0001_0011, 0b1000_1000, 0b0101_0000]
+                ));
+                dev.set_century_base(2100).unwrap();
+                dev.set_date(&d).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn cannot_set_date_outside_century_base_range() {
+                let mut dev = $create_method(&[]);
+                dev.set_century_base(2100).unwrap();
+                assert_invalid_input_data!(dev.set_date(&new_date(2099, 12, 31)));
+                assert_invalid_input_data!(dev.set_date(&new_date(2300, 1, 1)));
+                assert_invalid_input_data!(dev.set_year(2099));
+                assert_invalid_input_data!(dev.set_year(2300));
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn get_valid_leap_day() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOM,
+                    [0b0010_1001, 0b0000_0010, 0],
+                    [0, 0, 0]
+                ));
+                assert_eq!(new_date(2000, 2, 29), dev.date().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn get_valid_leap_day_in_other_century() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOM,
+                    [0b0010_1001, 0b0000_0010, 0],
+                    [0, 0, 0]
+                ));
+                dev.set_century_base(2400).unwrap();
+                assert_eq!(new_date(2400, 2, 29), dev.date().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn get_non_leap_day_date_is_compensated() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOM,
+                    [0b0010_1001, 0b1000_0010, 0],
+                    [0, 0, 0]
+                ));
+                assert_eq!(new_date(2100, 3, 1), dev.date().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn get_non_leap_day_datetime_is_compensated() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    SECONDS,
+                    [
+                        0b0101_1000,
+                        0b0101_1001,
+                        0b0010_0011,
+                        0b0000_0010,
+                        0b0010_1001,
+                        0b1000_0010,
+                        0
+                    ],
+                    [0, 0, 0, 0, 0, 0, 0]
+                ));
+                assert_eq!(
+                    new_datetime(2100, 3, 1, 23, 59, 58),
+                    dev.datetime().unwrap()
+                );
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn get_non_leap_day_in_first_century_is_compensated() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOM,
+                    [0b0010_1001, 0b0000_0010, 0],
+                    [0, 0, 0]
+                ));
+                dev.set_century_base(2100).unwrap();
+                assert_eq!(new_date(2100, 3, 1), dev.date().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn can_correct_non_leap_day() {
+                let mut trans =
+                    $mac_trans_read!(DOM, [0b0010_1001, 0b1000_0010, 0], [0, 0, 0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_write!(DOM, [1, 0b1000_0011]));
+                let mut dev = $create_method(&trans);
+                assert!(dev.correct_leap_day().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn can_correct_non_leap_day_in_first_century() {
+                let mut trans =
+                    $mac_trans_read!(DOM, [0b0010_1001, 0b0000_0010, 0], [0, 0, 0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_write!(DOM, [1, 0b0000_0011]));
+                let mut dev = $create_method(&trans);
+                dev.set_century_base(2100).unwrap();
+                assert!(dev.correct_leap_day().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn does_not_correct_valid_leap_day() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOM,
+                    [0b0010_1001, 0b0000_0010, 0],
+                    [0, 0, 0]
+                ));
+                assert!(!dev.correct_leap_day().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn does_not_correct_other_days() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOM,
+                    [0b0010_1000, 0b1000_0010, 0],
+                    [0, 0, 0]
+                ));
+                assert!(!dev.correct_leap_day().unwrap());
+                $destroy_method(dev);
+            }
+        }
+    };
+}
+
+fn hours_register(hour: u8, mode: HourMode) -> u8 {
+    match mode {
+        HourMode::H24 => ((hour / 10) << 4) | (hour % 10),
+        HourMode::H12 => {
+            let (hour12, pm) = match hour {
+                0 => (12, false),
+                1..=11 => (hour, false),
+                12 => (12, true),
+                _ => (hour - 12, true),
+            };
+            let am_pm = if pm { 0b0010_0000 } else { 0 };
+            0b0100_0000 | am_pm | ((hour12 / 10) << 4) | (hour12 % 10)
+        }
+    }
+}
+
+macro_rules! hour_mode_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident,
+    $mac_trans_read:ident, $mac_trans_write:ident) => {
+        mod $name {
+            use super::*;
+
+            fn round_trip_all_hours(mode: HourMode) {
+                for hour in 0..24 {
+                    let reg = hours_register(hour, mode);
+                    let mut trans = Vec::new();
+                    if mode == HourMode::H12 {
+                        // device is already in 12-hour mode so nothing is written
+                        trans.extend_from_slice(&$mac_trans_read!(
+                            SECONDS,
+                            [0, 0, 0b0101_0010],
+                            [0, 0, 0]
+                        ));
+                        trans.extend_from_slice(&$mac_trans_read!(
+                            ALARM1_HOURS,
+                            [0b0101_0010],
+                            [0]
+                        ));
+                        trans.extend_from_slice(&$mac_trans_read!(
+                            ALARM2_HOURS,
+                            [0b0101_0010],
+                            [0]
+                        ));
+                    }
+                    trans.extend_from_slice(&$mac_trans_write!(
+                        SECONDS,
+                        [0b0101_1000, 0b0101_1001, reg]
+                    ));
+                    trans.extend_from_slice(&$mac_trans_read!(
+                        SECONDS,
+                        [0b0101_1000, 0b0101_1001, reg],
+                        [0, 0, 0]
+                    ));
+                    let mut dev = $create_method(&trans);
+                    if mode == HourMode::H12 {
+                        dev.set_hour_mode(mode).unwrap();
+                    }
+                    let t = NaiveTime::from_hms_opt(hour.into(), 59, 58).unwrap();
+                    dev.set_time(&t).unwrap();
+                    assert_eq!(t, dev.time().unwrap());
+                    $destroy_method(dev);
+                }
+            }
+
+            #[test]
+            fn round_trip_all_hours_24h() {
+                round_trip_all_hours(HourMode::H24);
+            }
+
+            #[test]
+            fn round_trip_all_hours_12h() {
+                round_trip_all_hours(HourMode::H12);
+            }
+
+            #[test]
+            fn get_datetime_around_midnight_and_noon_12h() {
+                for hour in [0, 1, 11, 12, 13, 23] {
+                    let reg = hours_register(hour, HourMode::H12);
+                    let mut dev = $create_method(&$mac_trans_read!(
+                        SECONDS,
+                        [
+                            0,
+                            0,
+                            reg,
+                            0b0000_0010,
+                            0b0001_0011,
+                            0b0000_1000,
+                            0b0001_1000
+                        ],
+                        [0, 0, 0, 0, 0, 0, 0]
+                    ));
+                    assert_eq!(
+                        new_datetime(2018, 8, 13, hour.into(), 0, 0),
+                        dev.datetime().unwrap()
+                    );
+                    $destroy_method(dev);
+                }
+            }
+
+            #[test]
+            fn set_hour_mode_12h_converts_time_and_alarms() {
+                let mut trans = Vec::new();
+                trans.extend_from_slice(&$mac_trans_read!(SECONDS, [0, 0, 0b0010_0011], [0, 0, 0]));
+                trans.extend_from_slice(&$mac_trans_write!(HOURS, [0b0111_0001]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM1_HOURS, [0b1000_0000], [0]));
+                trans.extend_from_slice(&$mac_trans_write!(ALARM1_HOURS, [0b1101_0010]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM2_HOURS, [0b0101_0010], [0]));
+                let mut dev = $create_method(&trans);
+                dev.set_hour_mode(HourMode::H12).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_hour_mode_24h_converts_time_and_alarms() {
+                let mut trans = Vec::new();
+                trans.extend_from_slice(&$mac_trans_read!(SECONDS, [0, 0, 0b0111_0010], [0, 0, 0]));
+                trans.extend_from_slice(&$mac_trans_write!(HOURS, [0b0001_0010]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM1_HOURS, [0b0010_0001], [0]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM2_HOURS, [0b1101_0010], [0]));
+                trans.extend_from_slice(&$mac_trans_write!(ALARM2_HOURS, [0b1000_0000]));
+                let mut dev = $create_method(&trans);
+                dev.set_hour_mode(HourMode::H24).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_hour_mode_writes_next_hour_after_rollover() {
+                let mut trans = Vec::new();
+                // 23:59:59
+                trans.extend_from_slice(&$mac_trans_read!(
+                    SECONDS,
+                    [0b0101_1001, 0b0101_1001, 0b0010_0011],
+                    [0, 0, 0]
+                ));
+                trans.extend_from_slice(&$mac_trans_write!(HOURS, [0b0111_0001]));
+                // the hours rolled over before being written
+                trans.extend_from_slice(&$mac_trans_read!(SECONDS, [0, 0, 0b0111_0001], [0, 0, 0]));
+                trans.extend_from_slice(&$mac_trans_write!(HOURS, [0b0101_0010]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM1_HOURS, [0b0101_0010], [0]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM2_HOURS, [0b0101_0010], [0]));
+                let mut dev = $create_method(&trans);
+                dev.set_hour_mode(HourMode::H12).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_hour_mode_without_rollover_in_last_second_of_hour() {
+                let mut trans = Vec::new();
+                // 11:59:59 PM
+                trans.extend_from_slice(&$mac_trans_read!(
+                    SECONDS,
+                    [0b0101_1001, 0b0101_1001, 0b0111_0001],
+                    [0, 0, 0]
+                ));
+                trans.extend_from_slice(&$mac_trans_write!(HOURS, [0b0010_0011]));
+                trans.extend_from_slice(&$mac_trans_read!(
+                    SECONDS,
+                    [0b0101_1001, 0b0101_1001, 0b0010_0011],
+                    [0, 0, 0]
+                ));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM1_HOURS, [0], [0]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM2_HOURS, [0], [0]));
+                let mut dev = $create_method(&trans);
+                dev.set_hour_mode(HourMode::H24).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn can_read_hour_mode() {
+                let mut trans = $mac_trans_read!(HOURS, [0b0110_0001], [0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_read!(HOURS, [0b0010_0001], [0]));
+                let mut dev = $create_method(&trans);
+                assert_eq!(HourMode::H12, dev.hour_mode().unwrap());
+                assert_eq!(HourMode::H24, dev.hour_mode().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_hours_updates_hour_mode() {
+                let mut trans = $mac_trans_write!(HOURS, [0b0110_0001]).to_vec();
+                trans.extend_from_slice(&$mac_trans_write!(SECONDS, [0, 0, 0b0101_0010]));
+                let mut dev = $create_method(&trans);
+                dev.set_hours(Hours::PM(1)).unwrap();
+                dev.set_time(&NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+                    .unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_datetime_in_12h_mode() {
+                let mut trans = $mac_trans_read!(SECONDS, [0, 0, 0b0101_0010], [0, 0, 0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_read!(ALARM1_HOURS, [0b0101_0010], [0]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM2_HOURS, [0b0101_0010], [0]));
+                trans.extend_from_slice(&$mac_trans_write!(
+                    SECONDS,
+                    [
+                        0b0101_1000,
+                        0b0101_1001,
+                        0b0111_0001,
+                        0b0000_0010,
+                        0b0001_0011,
+                        0b0000_1000,
+                        0b0001_1000
+                    ]
+                ));
+                let mut dev = $create_method(&trans);
+                dev.set_hour_mode(HourMode::H12).unwrap();
+                dev.set_datetime(&new_datetime(2018, 8, 13, 23, 59, 58))
+                    .unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_alarms_in_12h_mode() {
+                let mut trans = $mac_trans_read!(SECONDS, [0, 0, 0b0101_0010], [0, 0, 0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_read!(ALARM1_HOURS, [0b0101_0010], [0]));
+                trans.extend_from_slice(&$mac_trans_read!(ALARM2_HOURS, [0b0101_0010], [0]));
+                trans.extend_from_slice(&$mac_trans_write!(
+                    ALARM1_SECONDS,
+                    [0b0101_1000, 0b0101_1001, 0b0101_0010, 0b1000_0001]
+                ));
+                trans.extend_from_slice(&$mac_trans_write!(
+                    ALARM2_MINUTES,
+                    [0b0101_1001, 0b0111_0010, 0b1000_0001]
+                ));
+                let mut dev = $create_method(&trans);
+                dev.set_hour_mode(HourMode::H12).unwrap();
+                dev.set_alarm1_hms(NaiveTime::from_hms_opt(0, 59, 58).unwrap())
+                    .unwrap();
+                dev.set_alarm2_hm(NaiveTime::from_hms_opt(12, 59, 0).unwrap())
+                    .unwrap();
+                $destroy_method(dev);
+            }
+        }
+    };
+}
+
+macro_rules! weekday_numbering_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident,
+    $mac_trans_read:ident, $mac_trans_write:ident) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn default_numbering() {
+                let dev = $create_method(&[]);
+                assert_eq!(WeekdayNumbering::SundayFirst, dev.weekday_numbering());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn get_day_of_week() {
+                let mut trans = Vec::new();
+                for value in 1..=7 {
+                    trans.extend_from_slice(&$mac_trans_read!(DOW, [value], [0]));
+                }
+                for value in 1..=7 {
+                    trans.extend_from_slice(&$mac_trans_read!(DOW, [value], [0]));
+                }
+                let mut dev = $create_method(&trans);
+                let mut expected = Weekday::Sun;
+                for _ in 1..=7 {
+                    assert_eq!(expected, dev.day_of_week().unwrap());
+                    expected = expected.succ();
+                }
+                dev.set_weekday_numbering(WeekdayNumbering::MondayFirst);
+                let mut expected = Weekday::Mon;
+                for _ in 1..=7 {
+                    assert_eq!(expected, dev.day_of_week().unwrap());
+                    expected = expected.succ();
+                }
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn get_invalid_day_of_week() {
+                let mut trans = $mac_trans_read!(DOW, [0], [0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_read!(DOW, [8], [0]));
+                let mut dev = $create_method(&trans);
+                assert_invalid_device_state!(dev.day_of_week(), DOW);
+                assert_invalid_device_state!(dev.day_of_week(), DOW);
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_day_of_week() {
+                let mut trans = $mac_trans_write!(DOW, [1]).to_vec();
+                trans.extend_from_slice(&$mac_trans_write!(DOW, [7]));
+                let mut dev = $create_method(&trans);
+                dev.set_day_of_week(Weekday::Sun).unwrap();
+                dev.set_weekday_numbering(WeekdayNumbering::MondayFirst);
+                dev.set_day_of_week(Weekday::Sun).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_datetime_monday_first() {
+                // 2018-08-13 is a Monday
+                let dt = new_datetime(2018, 8, 13, 23, 59, 58);
+                let mut dev = $create_method(&$mac_trans_write!(
+                    SECONDS,
+                    [
+                        0b0101_1000,
+                        0b0101_1001,
+                        0b0010_0011,
+                        0b0000_0001,
+                        0b0001_0011,
+                        0b0000_1000,
+                        0b0001_1000
+                    ]
+                ));
+                dev.set_weekday_numbering(WeekdayNumbering::MondayFirst);
+                dev.set_datetime(&dt).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_date_monday_first() {
+                // 2018-08-19 is a Sunday
+                let d = new_date(2018, 8, 19);
+                let mut dev = $create_method(&$mac_trans_write!(
+                    DOW,
+                    [0b0000_0111, 0b0001_1001, 0b0000_1000, 0b0001_1000]
+                ));
+                dev.set_weekday_numbering(WeekdayNumbering::MondayFirst);
+                dev.set_date(&d).unwrap();
+                $destroy_method(dev);
+            }
+        }
+    };
+}
+
+macro_rules! weekday_check_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident,
+    $mac_trans_read:ident, $mac_trans_write:ident) => {
+        mod $name {
+            use super::*;
+
+            // 2018-08-13 is a Monday
+            #[test]
+            fn verify_matching() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOW,
+                    [2, 0b0001_0011, 0b0000_1000, 0b0001_1000],
+                    [0, 0, 0, 0]
+                ));
+                assert!(dev.verify_weekday().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn verify_mismatching() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOW,
+                    [5, 0b0001_0011, 0b0000_1000, 0b0001_1000],
+                    [0, 0, 0, 0]
+                ));
+                assert!(!dev.verify_weekday().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn verify_monday_first() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOW,
+                    [1, 0b0001_0011, 0b0000_1000, 0b0001_1000],
+                    [0, 0, 0, 0]
+                ));
+                dev.set_weekday_numbering(WeekdayNumbering::MondayFirst);
+                assert!(dev.verify_weekday().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn verify_invalid_date() {
+                // 2018-02-30
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOW,
+                    [1, 0b0011_0000, 0b0000_0010, 0b0001_1000],
+                    [0, 0, 0, 0]
+                ));
+                assert_invalid_device_state!(dev.verify_weekday(), DOM);
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn repair_matching_does_not_write() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    DOW,
+                    [2, 0b0001_0011, 0b0000_1000, 0b0001_1000],
+                    [0, 0, 0, 0]
+                ));
+                assert!(!dev.repair_weekday().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn repair_mismatching() {
+                let mut trans = $mac_trans_read!(
+                    DOW,
+                    [5, 0b0001_0011, 0b0000_1000, 0b0001_1000],
+                    [0, 0, 0, 0]
+                )
+                .to_vec();
+                trans.extend_from_slice(&$mac_trans_write!(DOW, [2]));
+                let mut dev = $create_method(&trans);
+                assert!(dev.repair_weekday().unwrap());
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_day_without_auto_repair() {
+                let mut dev = $create_method(&$mac_trans_write!(DOM, [0b0001_0011]));
+                dev.set_day(13).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_day_with_auto_repair() {
+                let mut trans = $mac_trans_write!(DOM, [0b0001_0011]).to_vec();
+                trans.extend_from_slice(&$mac_trans_read!(
+                    DOW,
+                    [5, 0b0001_0011, 0b0000_1000, 0b0001_1000],
+                    [0, 0, 0, 0]
+                ));
+                trans.extend_from_slice(&$mac_trans_write!(DOW, [2]));
+                let mut dev = $create_method(&trans);
+                dev.enable_weekday_auto_repair();
+                dev.set_day(13).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_month_with_auto_repair_matching() {
+                let mut trans = $mac_trans_read!(MONTH, [0b0000_0111], [0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_write!(MONTH, [0b0000_1000]));
+                trans.extend_from_slice(&$mac_trans_read!(
+                    DOW,
+                    [2, 0b0001_0011, 0b0000_1000, 0b0001_1000],
+                    [0, 0, 0, 0]
+                ));
+                let mut dev = $create_method(&trans);
+                dev.enable_weekday_auto_repair();
+                dev.set_month(8).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_month_with_auto_repair_invalid_date() {
+                // 2018-02-30 is not repaired and not reported as an error
+                let mut trans = $mac_trans_read!(MONTH, [0b0000_0001], [0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_write!(MONTH, [0b0000_0010]));
+                trans.extend_from_slice(&$mac_trans_read!(
+                    DOW,
+                    [1, 0b0011_0000, 0b0000_0010, 0b0001_1000],
+                    [0, 0, 0, 0]
+                ));
+                let mut dev = $create_method(&trans);
+                dev.enable_weekday_auto_repair();
+                dev.set_month(2).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn set_year_with_auto_repair() {
+                // 2019-08-13 is a Tuesday
+                let mut trans = $mac_trans_read!(MONTH, [0b0000_1000], [0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_write!(MONTH, [0b0000_1000, 0b0001_1001]));
+                trans.extend_from_slice(&$mac_trans_read!(
+                    DOW,
+                    [2, 0b0001_0011, 0b0000_1000, 0b0001_1001],
+                    [0, 0, 0, 0]
+                ));
+                trans.extend_from_slice(&$mac_trans_write!(DOW, [3]));
+                let mut dev = $create_method(&trans);
+                dev.enable_weekday_auto_repair();
+                dev.set_year(2019).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn disable_auto_repair() {
+                let mut dev = $create_method(&$mac_trans_write!(DOM, [0b0001_0011]));
+                dev.enable_weekday_auto_repair();
+                dev.disable_weekday_auto_repair();
+                dev.set_day(13).unwrap();
+                $destroy_method(dev);
+            }
+        }
+    };
+}
+
+macro_rules! get_invalid_test {
+    ($name:ident, $method:ident, $create_method:ident, $destroy_method:ident,
+    $mac_trans_read:ident, $register:ident, [ $( $value:expr ),+ ]) => {
+        #[test]
+        fn $name() {
+            let mut trans = Vec::new();
+            $(
+                trans.extend_from_slice(&$mac_trans_read!($register, [$value], [0]));
+            )+
+            let mut dev = $create_method(&trans);
+            $(
+                let _ = $value;
+                assert_invalid_device_state!(dev.$method(), $register);
+            )+
+            $destroy_method(dev);
+        }
+    };
+}
+
+macro_rules! invalid_state_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident,
+    $mac_trans_read:ident, $mac_trans_write:ident) => {
+        mod $name {
+            use super::*;
+
+            get_invalid_test!(
+                seconds,
+                seconds,
+                $create_method,
+                $destroy_method,
+                $mac_trans_read,
+                SECONDS,
+                [0x60, 0x0A, 0x3F, 0x80]
+            );
+            get_invalid_test!(
+                minutes,
+                minutes,
+                $create_method,
+                $destroy_method,
+                $mac_trans_read,
+                MINUTES,
+                [0x60, 0x0A, 0x3F, 0xFF]
+            );
+            get_invalid_test!(
+                hours,
+                hours,
+                $create_method,
+                $destroy_method,
+                $mac_trans_read,
+                HOURS,
+                [
+                    0x24,
+                    0x1A,
+                    0x3F,
+                    0b0100_0000,
+                    0b0101_0011,
+                    0b0110_0000,
+                    0x80
+                ]
+            );
+            get_invalid_test!(
+                weekday,
+                weekday,
+                $create_method,
+                $destroy_method,
+                $mac_trans_read,
+                DOW,
+                [0, 8, 0x11]
+            );
+            get_invalid_test!(
+                day,
+                day,
+                $create_method,
+                $destroy_method,
+                $mac_trans_read,
+                DOM,
+                [0, 0x32, 0x1A, 0xFF]
+            );
+            get_invalid_test!(
+                month,
+                month,
+                $create_method,
+                $destroy_method,
+                $mac_trans_read,
+                MONTH,
+                [0, 0x13, 0x0A, 0b1000_0000, 0x7F]
+            );
+
+            #[test]
+            fn year() {
+                let mut trans = $mac_trans_read!(MONTH, [0b1000_0001, 0x9A], [0, 0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_read!(MONTH, [0, 0xA0], [0, 0]));
+                let mut dev = $create_method(&trans);
+                assert_invalid_device_state!(dev.year(), YEAR);
+                assert_invalid_device_state!(dev.year(), YEAR);
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn time() {
+                let mut trans = $mac_trans_read!(SECONDS, [0x58, 0x5A, 0x23], [0, 0, 0]).to_vec();
+                trans.extend_from_slice(&$mac_trans_read!(SECONDS, [0x58, 0x59, 0x24], [0, 0, 0]));
+                let mut dev = $create_method(&trans);
+                assert_invalid_device_state!(dev.time(), MINUTES);
+                assert_invalid_device_state!(dev.time(), HOURS);
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn date() {
+                let mut trans = $mac_trans_read!(DOM, [0x1A, 0x08, 0x18], [0, 0, 0]).to_vec();
+                // 31st of April
+                trans.extend_from_slice(&$mac_trans_read!(DOM, [0x31, 0x04, 0x18], [0, 0, 0]));
+                let mut dev = $create_method(&trans);
+                assert_invalid_device_state!(dev.date(), DOM);
+                assert_invalid_device_state!(dev.date(), DOM);
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn datetime() {
+                let mut dev = $create_method(&$mac_trans_read!(
+                    SECONDS,
+                    [0x7F, 0x59, 0x23, 0x02, 0x13, 0x08, 0x18],
+                    [0, 0, 0, 0, 0, 0, 0]
+                ));
+                assert_invalid_device_state!(dev.datetime(), SECONDS);
+                $destroy_method(dev);
+            }
+        }
+    };
+}
+
 mod datetime {
     use super::*;
 
@@ -403,3 +1207,133 @@ mod datetime {
 
     for_all!(invalid_dt_test);
 }
+
+mod century_base {
+    use super::*;
+
+    century_base_test!(
+        for_ds3231,
+        new_ds3231,
+        destroy_ds3231,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    century_base_test!(
+        for_ds3232,
+        new_ds3232,
+        destroy_ds3232,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    century_base_test!(
+        for_ds3234,
+        new_ds3234,
+        destroy_ds3234,
+        transactions_spi_read,
+        transactions_spi_write
+    );
+}
+
+mod hour_mode {
+    use super::*;
+
+    hour_mode_test!(
+        for_ds3231,
+        new_ds3231,
+        destroy_ds3231,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    hour_mode_test!(
+        for_ds3232,
+        new_ds3232,
+        destroy_ds3232,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    hour_mode_test!(
+        for_ds3234,
+        new_ds3234,
+        destroy_ds3234,
+        transactions_spi_read,
+        transactions_spi_write
+    );
+}
+
+mod weekday_numbering {
+    use super::*;
+
+    weekday_numbering_test!(
+        for_ds3231,
+        new_ds3231,
+        destroy_ds3231,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    weekday_numbering_test!(
+        for_ds3232,
+        new_ds3232,
+        destroy_ds3232,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    weekday_numbering_test!(
+        for_ds3234,
+        new_ds3234,
+        destroy_ds3234,
+        transactions_spi_read,
+        transactions_spi_write
+    );
+}
+
+mod weekday_check {
+    use super::*;
+
+    weekday_check_test!(
+        for_ds3231,
+        new_ds3231,
+        destroy_ds3231,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    weekday_check_test!(
+        for_ds3232,
+        new_ds3232,
+        destroy_ds3232,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    weekday_check_test!(
+        for_ds3234,
+        new_ds3234,
+        destroy_ds3234,
+        transactions_spi_read,
+        transactions_spi_write
+    );
+}
+
+mod invalid_state {
+    use super::*;
+
+    invalid_state_test!(
+        for_ds3231,
+        new_ds3231,
+        destroy_ds3231,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    invalid_state_test!(
+        for_ds3232,
+        new_ds3232,
+        destroy_ds3232,
+        transactions_i2c_read,
+        transactions_i2c_write
+    );
+    invalid_state_test!(
+        for_ds3234,
+        new_ds3234,
+        destroy_ds3234,
+        transactions_spi_read,
+        transactions_spi_write
+    );
+}
@@ -0,0 +1,204 @@
+use ds323x::{
+    ic, interface::I2cInterface, DateTime, DateTimeAccess, Ds323x, Error, NaiveDate, NaiveDateTime,
+    Utc,
+};
+
+#[allow(unused)]
+mod common;
+use self::common::{FakeI2c, Register};
+
+type Dev = Ds323x<I2cInterface<FakeI2c>, ic::DS3232>;
+
+fn new() -> Dev {
+    Ds323x::new_ds3232(FakeI2c::default())
+}
+
+fn new_with_invalid_date() -> Dev {
+    let mut i2c = FakeI2c::default();
+    i2c.registers[usize::from(Register::MONTH)] = 0x13;
+    Ds323x::new_ds3232(i2c)
+}
+
+// 10000-01-01 00:00:00 with a century base of 9900
+#[cfg(any(feature = "time", feature = "jiff"))]
+fn new_after_year_9999() -> Dev {
+    let mut i2c = FakeI2c::default();
+    i2c.registers[usize::from(Register::DOM)] = 0x01;
+    i2c.registers[usize::from(Register::MONTH)] = 0x81;
+    let mut dev = Ds323x::new_ds3232(i2c);
+    dev.set_century_base(9900).unwrap();
+    dev
+}
+
+fn naive(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .unwrap()
+        .and_hms_opt(hour, minute, second)
+        .unwrap()
+}
+
+fn registers(dev: Dev) -> [u8; 7] {
+    let i2c = dev.destroy_ds3232();
+    let mut data = [0; 7];
+    data.copy_from_slice(&i2c.registers[..7]);
+    data
+}
+
+// 2024-02-29 23:59:58 (Thursday)
+const REGISTERS: [u8; 7] = [0x58, 0x59, 0x23, 5, 0x29, 0x02, 0x24];
+
+#[test]
+fn can_read_utc() {
+    let mut dev = new();
+    dev.set_datetime(&naive(2024, 2, 29, 23, 59, 58)).unwrap();
+    let expected: DateTime<Utc> = naive(2024, 2, 29, 23, 59, 58).and_utc();
+    assert_eq!(expected, dev.datetime_utc().unwrap());
+}
+
+#[test]
+fn can_set_utc() {
+    let mut dev = new();
+    let datetime = naive(2024, 2, 29, 23, 59, 58).and_utc();
+    dev.set_datetime_utc(&datetime).unwrap();
+    assert_eq!(REGISTERS, registers(dev));
+}
+
+#[test]
+fn utc_read_fails_with_invalid_date() {
+    let mut dev = new_with_invalid_date();
+    assert_invalid_device_state!(dev.datetime_utc(), MONTH);
+}
+
+#[test]
+fn cannot_set_utc_out_of_range() {
+    let mut dev = new();
+    let datetime = naive(1999, 12, 31, 23, 59, 59).and_utc();
+    assert_invalid_input_data!(dev.set_datetime_utc(&datetime));
+}
+
+#[cfg(feature = "time")]
+mod time_crate {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn can_read_primitive_datetime() {
+        let mut dev = new();
+        dev.set_datetime(&naive(2024, 2, 29, 23, 59, 58)).unwrap();
+        assert_eq!(
+            datetime!(2024-02-29 23:59:58),
+            dev.primitive_datetime().unwrap()
+        );
+    }
+
+    #[test]
+    fn can_set_primitive_datetime() {
+        let mut dev = new();
+        dev.set_primitive_datetime(&datetime!(2024-02-29 23:59:58.75))
+            .unwrap();
+        assert_eq!(REGISTERS, registers(dev));
+    }
+
+    #[test]
+    fn can_read_offset_datetime() {
+        let mut dev = new();
+        dev.set_datetime(&naive(2024, 2, 29, 23, 59, 58)).unwrap();
+        assert_eq!(
+            datetime!(2024-02-29 23:59:58 UTC),
+            dev.offset_datetime().unwrap()
+        );
+    }
+
+    #[test]
+    fn sets_offset_datetime_in_utc() {
+        let mut dev = new();
+        dev.set_offset_datetime(&datetime!(2024-03-01 01:59:58 +02:00))
+            .unwrap();
+        assert_eq!(REGISTERS, registers(dev));
+    }
+
+    #[test]
+    fn read_fails_with_invalid_date() {
+        let mut dev = new_with_invalid_date();
+        assert_invalid_device_state!(dev.primitive_datetime(), MONTH);
+        assert_invalid_device_state!(dev.offset_datetime(), MONTH);
+    }
+
+    #[test]
+    fn read_fails_after_year_9999() {
+        let mut dev = new_after_year_9999();
+        assert_eq!(naive(10000, 1, 1, 0, 0, 0), dev.datetime().unwrap());
+        assert_invalid_device_state!(dev.primitive_datetime(), YEAR);
+        assert_invalid_device_state!(dev.offset_datetime(), YEAR);
+    }
+
+    #[test]
+    fn cannot_set_out_of_range() {
+        let mut dev = new();
+        assert_invalid_input_data!(dev.set_primitive_datetime(&datetime!(2200-01-01 0:00)));
+        assert_invalid_input_data!(dev.set_offset_datetime(&datetime!(2000-01-01 0:30 +01:00)));
+    }
+}
+
+#[cfg(feature = "jiff")]
+mod jiff_crate {
+    use super::*;
+    use jiff::{civil, Timestamp};
+
+    #[test]
+    fn can_read_civil_datetime() {
+        let mut dev = new();
+        dev.set_datetime(&naive(2024, 2, 29, 23, 59, 58)).unwrap();
+        assert_eq!(
+            civil::datetime(2024, 2, 29, 23, 59, 58, 0),
+            dev.civil_datetime().unwrap()
+        );
+    }
+
+    #[test]
+    fn can_set_civil_datetime() {
+        let mut dev = new();
+        dev.set_civil_datetime(&civil::datetime(2024, 2, 29, 23, 59, 58, 750_000_000))
+            .unwrap();
+        assert_eq!(REGISTERS, registers(dev));
+    }
+
+    #[test]
+    fn can_read_timestamp() {
+        let mut dev = new();
+        dev.set_datetime(&naive(2024, 2, 29, 23, 59, 58)).unwrap();
+        let expected = Timestamp::from_second(1_709_251_198).unwrap();
+        assert_eq!(expected, dev.timestamp().unwrap());
+    }
+
+    #[test]
+    fn can_set_timestamp() {
+        let mut dev = new();
+        let timestamp = Timestamp::from_second(1_709_251_198).unwrap();
+        dev.set_timestamp(&timestamp).unwrap();
+        assert_eq!(REGISTERS, registers(dev));
+    }
+
+    #[test]
+    fn read_fails_with_invalid_date() {
+        let mut dev = new_with_invalid_date();
+        assert_invalid_device_state!(dev.civil_datetime(), MONTH);
+        assert_invalid_device_state!(dev.timestamp(), MONTH);
+    }
+
+    #[test]
+    fn read_fails_after_year_9999() {
+        let mut dev = new_after_year_9999();
+        assert_invalid_device_state!(dev.civil_datetime(), YEAR);
+        assert_invalid_device_state!(dev.timestamp(), YEAR);
+    }
+
+    #[test]
+    fn cannot_set_out_of_range() {
+        let mut dev = new();
+        let datetime = civil::datetime(1999, 12, 31, 23, 59, 59, 0);
+        assert_invalid_input_data!(dev.set_civil_datetime(&datetime));
+        let timestamp = Timestamp::from_second(946_684_799).unwrap();
+        assert_invalid_input_data!(dev.set_timestamp(&timestamp));
+    }
+}
@@ -0,0 +1,158 @@
+use ds323x::{
+    ic, interface::I2cInterface, Alarm1Matching, Alarm2Matching, DateTimeAccess, DayAlarm1,
+    DayAlarm2, Ds323x, Error, ErrorClass, EventLog, Hours, InputField, NaiveDate, Rtcc,
+};
+use embedded_hal::{i2c, spi};
+
+#[allow(unused)]
+mod common;
+use self::common::FakeI2c;
+
+type Dev = Ds323x<I2cInterface<FakeI2c>, ic::DS3232>;
+
+fn new() -> Dev {
+    Ds323x::new_ds3232(FakeI2c::default())
+}
+
+#[test]
+fn can_display_errors() {
+    let comm: Error<i2c::ErrorKind> = Error::Comm(i2c::ErrorKind::Bus);
+    assert_eq!("communication error: Bus", comm.to_string());
+    let input: Error<()> = Error::InvalidInputData {
+        field: InputField::Month,
+    };
+    assert_eq!(
+        "invalid input data: month out of range [1-12]",
+        input.to_string()
+    );
+    let state: Error<()> = Error::InvalidDeviceState { register: 0x05 };
+    assert_eq!("invalid device state in register 0x05", state.to_string());
+}
+
+#[test]
+fn implements_error_trait() {
+    fn source(e: &dyn core::error::Error) -> Option<&dyn core::error::Error> {
+        e.source()
+    }
+    let error: Error<()> = Error::InvalidInputData {
+        field: InputField::Second,
+    };
+    assert!(source(&error).is_none());
+}
+
+#[test]
+fn can_classify_i2c_errors() {
+    let class = |kind| Error::Comm(kind).i2c_class();
+    let nack = i2c::NoAcknowledgeSource::Address;
+    assert_eq!(
+        ErrorClass::NoAcknowledge,
+        class(i2c::ErrorKind::NoAcknowledge(nack))
+    );
+    assert_eq!(
+        ErrorClass::DataNotAcknowledged,
+        class(i2c::ErrorKind::NoAcknowledge(
+            i2c::NoAcknowledgeSource::Data
+        ))
+    );
+    assert_eq!(ErrorClass::Bus, class(i2c::ErrorKind::Bus));
+    assert_eq!(
+        ErrorClass::ArbitrationLoss,
+        class(i2c::ErrorKind::ArbitrationLoss)
+    );
+    assert_eq!(ErrorClass::Bus, class(i2c::ErrorKind::Overrun));
+    assert_eq!(ErrorClass::Other, class(i2c::ErrorKind::Other));
+}
+
+#[test]
+fn can_classify_spi_errors() {
+    let class = |kind| Error::Comm(kind).spi_class();
+    assert_eq!(ErrorClass::Bus, class(spi::ErrorKind::Overrun));
+    assert_eq!(ErrorClass::Bus, class(spi::ErrorKind::ModeFault));
+    assert_eq!(ErrorClass::Bus, class(spi::ErrorKind::FrameFormat));
+    assert_eq!(ErrorClass::Bus, class(spi::ErrorKind::ChipSelectFault));
+    assert_eq!(ErrorClass::Other, class(spi::ErrorKind::Other));
+}
+
+#[test]
+fn can_classify_driver_errors() {
+    let input: Error<i2c::ErrorKind> = Error::InvalidInputData {
+        field: InputField::Day,
+    };
+    assert_eq!(ErrorClass::InvalidInput, input.i2c_class());
+    let state: Error<spi::ErrorKind> = Error::InvalidDeviceState { register: 0x05 };
+    assert_eq!(ErrorClass::InvalidDeviceState, state.spi_class());
+}
+
+#[test]
+fn only_transient_bus_errors_are_retryable() {
+    for is_write in [false, true] {
+        assert!(ErrorClass::NoAcknowledge.is_retryable(is_write));
+        assert!(ErrorClass::ArbitrationLoss.is_retryable(is_write));
+        assert!(!ErrorClass::Bus.is_retryable(is_write));
+        assert!(!ErrorClass::Other.is_retryable(is_write));
+        assert!(!ErrorClass::InvalidInput.is_retryable(is_write));
+        assert!(!ErrorClass::InvalidDeviceState.is_retryable(is_write));
+    }
+}
+
+#[test]
+fn data_nack_is_only_retryable_for_reads() {
+    assert!(ErrorClass::DataNotAcknowledged.is_retryable(false));
+    assert!(!ErrorClass::DataNotAcknowledged.is_retryable(true));
+}
+
+#[test]
+fn reports_invalid_datetime_field() {
+    let mut dev = new();
+    assert_invalid_input_data!(dev.set_seconds(60), Second);
+    assert_invalid_input_data!(dev.set_minutes(60), Minute);
+    assert_invalid_input_data!(dev.set_hours(Hours::H24(24)), Hour);
+    assert_invalid_input_data!(dev.set_weekday(8), Weekday);
+    assert_invalid_input_data!(dev.set_day(32), Day);
+    assert_invalid_input_data!(dev.set_month(13), Month);
+    assert_invalid_input_data!(dev.set_year(2200), Year);
+    assert_invalid_input_data!(dev.set_century_base(2050), CenturyBase);
+    let datetime = NaiveDate::from_ymd_opt(1999, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    assert_invalid_input_data!(dev.set_datetime(&datetime), Year);
+}
+
+#[test]
+fn reports_invalid_alarm_field() {
+    let mut dev = new();
+    let alarm1 = DayAlarm1 {
+        day: 32,
+        hour: Hours::H24(24),
+        minute: 60,
+        second: 60,
+    };
+    assert_invalid_input_data!(dev.set_alarm1_day(alarm1, Alarm1Matching::AllMatch), Day);
+    assert_invalid_input_data!(
+        dev.set_alarm1_day(alarm1, Alarm1Matching::HoursMinutesAndSecondsMatch),
+        Hour
+    );
+    assert_invalid_input_data!(
+        dev.set_alarm1_day(alarm1, Alarm1Matching::MinutesAndSecondsMatch),
+        Minute
+    );
+    assert_invalid_input_data!(
+        dev.set_alarm1_day(alarm1, Alarm1Matching::SecondsMatch),
+        Second
+    );
+    let alarm2 = DayAlarm2 {
+        day: 1,
+        hour: Hours::H24(7),
+        minute: 60,
+    };
+    assert_invalid_input_data!(dev.set_alarm2_day(alarm2, Alarm2Matching::AllMatch), Minute);
+}
+
+#[test]
+fn reports_invalid_sram_field() {
+    let mut dev = new();
+    assert_invalid_input_data!(dev.write_sram(235, &[0; 2]), SramRange);
+    assert_invalid_input_data!(EventLog::open(&mut dev, 0, 0), EventLogCapacity);
+    assert_invalid_input_data!(EventLog::open(&mut dev, 0, 32), SramRange);
+}
@@ -0,0 +1,233 @@
+use ds323x::{
+    ic, interface::I2cInterface, DateTimeAccess, Ds323x, Error, Event, EventLog, LogEntry,
+    NaiveDate, NaiveDateTime, Sram,
+};
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+use std::{cell::RefCell, rc::Rc};
+
+#[allow(unused)]
+mod common;
+use self::common::FakeI2c;
+
+const OFFSET: u8 = 16;
+const ENTRY_SIZE: usize = 8;
+const DS3232_SRAM_START: usize = 0x14;
+
+type Dev = Ds323x<I2cInterface<FakeI2c>, ic::DS3232>;
+
+fn new() -> Dev {
+    Ds323x::new_ds3232(FakeI2c::default())
+}
+
+fn entry_register(index: usize) -> usize {
+    DS3232_SRAM_START + usize::from(OFFSET) + index * ENTRY_SIZE
+}
+
+fn corrupt(dev: Dev, index: usize) -> Dev {
+    let mut i2c = dev.destroy_ds3232();
+    i2c.registers[entry_register(index) + 3] ^= 0x01;
+    Ds323x::new_ds3232(i2c)
+}
+
+// Fake device whose registers can be modified while it is held by a log
+#[derive(Clone, Default)]
+struct SharedI2c(Rc<RefCell<FakeI2c>>);
+
+impl ErrorType for SharedI2c {
+    type Error = ErrorKind;
+}
+
+impl I2c for SharedI2c {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.0.borrow_mut().transaction(address, operations)
+    }
+}
+
+fn time(seconds: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 5, 17)
+        .unwrap()
+        .and_hms_opt(12, 0, seconds)
+        .unwrap()
+}
+
+fn entry(seconds: u32, event: Event) -> LogEntry {
+    LogEntry {
+        timestamp: time(seconds),
+        event,
+    }
+}
+
+fn entries<S, E>(log: &mut EventLog<S>) -> Vec<LogEntry>
+where
+    S: Sram<Error = Error<E>>,
+    E: core::fmt::Debug,
+{
+    log.iter().map(Result::unwrap).collect()
+}
+
+#[test]
+fn empty_after_power_up() {
+    let mut log = EventLog::open(new(), OFFSET, 4).unwrap();
+    assert!(log.is_empty());
+    assert_eq!(4, log.capacity());
+    assert_eq!(Vec::<LogEntry>::new(), entries(&mut log));
+}
+
+#[test]
+fn iterates_newest_first() {
+    let mut log = EventLog::open(new(), OFFSET, 4).unwrap();
+    log.append(time(1), Event::Boot).unwrap();
+    log.append(time(2), Event::AlarmFired).unwrap();
+    log.append(time(3), Event::Custom(0x42)).unwrap();
+    assert_eq!(3, log.len());
+    let expected = vec![
+        entry(3, Event::Custom(0x42)),
+        entry(2, Event::AlarmFired),
+        entry(1, Event::Boot),
+    ];
+    assert_eq!(expected, entries(&mut log));
+}
+
+#[test]
+fn overwrites_oldest_entries() {
+    let mut log = EventLog::open(new(), OFFSET, 3).unwrap();
+    for second in 1..=5 {
+        log.append(time(second), Event::Brownout).unwrap();
+    }
+    assert_eq!(3, log.len());
+    let expected = vec![
+        entry(5, Event::Brownout),
+        entry(4, Event::Brownout),
+        entry(3, Event::Brownout),
+    ];
+    assert_eq!(expected, entries(&mut log));
+}
+
+#[test]
+fn recovers_head_when_reopened() {
+    let mut log = EventLog::open(new(), OFFSET, 4).unwrap();
+    for second in 1..=6 {
+        log.append(time(second), Event::Boot).unwrap();
+    }
+    let mut log = EventLog::open(log.destroy(), OFFSET, 4).unwrap();
+    assert_eq!(4, log.len());
+    log.append(time(7), Event::WatchdogReset).unwrap();
+    let expected = vec![
+        entry(7, Event::WatchdogReset),
+        entry(6, Event::Boot),
+        entry(5, Event::Boot),
+        entry(4, Event::Boot),
+    ];
+    assert_eq!(expected, entries(&mut log));
+}
+
+#[test]
+fn recovers_after_corrupted_newest_entry() {
+    let mut log = EventLog::open(new(), OFFSET, 4).unwrap();
+    for second in 1..=3 {
+        log.append(time(second), Event::Boot).unwrap();
+    }
+    let dev = corrupt(log.destroy(), 2);
+    let mut log = EventLog::open(dev, OFFSET, 4).unwrap();
+    assert_eq!(2, log.len());
+    log.append(time(4), Event::Brownout).unwrap();
+    let expected = vec![
+        entry(4, Event::Brownout),
+        entry(2, Event::Boot),
+        entry(1, Event::Boot),
+    ];
+    assert_eq!(expected, entries(&mut log));
+}
+
+#[test]
+fn drops_entries_older_than_corrupted_entry() {
+    let mut log = EventLog::open(new(), OFFSET, 4).unwrap();
+    for second in 1..=4 {
+        log.append(time(second), Event::Boot).unwrap();
+    }
+    let dev = corrupt(log.destroy(), 1);
+    let mut log = EventLog::open(dev, OFFSET, 4).unwrap();
+    let expected = vec![entry(4, Event::Boot), entry(3, Event::Boot)];
+    assert_eq!(expected, entries(&mut log));
+}
+
+#[test]
+fn iteration_stops_at_entry_corrupted_after_opening() {
+    let i2c = SharedI2c::default();
+    let mut log = EventLog::open(Ds323x::new_ds3232(i2c.clone()), OFFSET, 4).unwrap();
+    for second in 1..=3 {
+        log.append(time(second), Event::Boot).unwrap();
+    }
+    i2c.0.borrow_mut().registers[entry_register(1) + 3] ^= 0x01;
+    assert_eq!(vec![entry(3, Event::Boot)], entries(&mut log));
+}
+
+#[test]
+fn can_clear() {
+    let mut log = EventLog::open(new(), OFFSET, 4).unwrap();
+    log.append(time(1), Event::Boot).unwrap();
+    log.clear().unwrap();
+    assert!(log.is_empty());
+    let mut log = EventLog::open(log.destroy(), OFFSET, 4).unwrap();
+    assert!(log.is_empty());
+    log.append(time(2), Event::Boot).unwrap();
+    assert_eq!(vec![entry(2, Event::Boot)], entries(&mut log));
+}
+
+#[test]
+fn can_append_current_time() {
+    let mut dev = new();
+    dev.set_datetime(&time(30)).unwrap();
+    let mut log = EventLog::open(&mut dev, OFFSET, 4).unwrap();
+    log.append_now(Event::AlarmFired).unwrap();
+    assert_eq!(vec![entry(30, Event::AlarmFired)], entries(&mut log));
+}
+
+#[test]
+fn can_append_current_time_of_owned_device() {
+    let mut dev = new();
+    dev.set_datetime(&time(30)).unwrap();
+    let mut log = EventLog::open(dev, OFFSET, 4).unwrap();
+    log.append_now(Event::Boot).unwrap();
+    assert_eq!(vec![entry(30, Event::Boot)], entries(&mut log));
+    assert_eq!(time(30), log.destroy().datetime().unwrap());
+}
+
+#[test]
+fn cannot_open_empty_log() {
+    let mut dev = new();
+    assert_invalid_input_data!(EventLog::open(&mut dev, OFFSET, 0));
+}
+
+#[test]
+fn cannot_open_log_not_fitting_in_sram() {
+    let mut dev = new();
+    assert_invalid_input_data!(EventLog::open(&mut dev, 0, 30));
+    assert_invalid_input_data!(EventLog::open(&mut dev, 229, 1));
+}
+
+#[test]
+fn cannot_append_reserved_custom_event() {
+    let mut log = EventLog::open(new(), OFFSET, 4).unwrap();
+    assert_invalid_input_data!(log.append(time(1), Event::Custom(0x0F)));
+    assert!(log.is_empty());
+}
+
+#[test]
+fn cannot_append_time_out_of_range() {
+    let mut log = EventLog::open(new(), OFFSET, 4).unwrap();
+    let before_2000 = NaiveDate::from_ymd_opt(1999, 12, 31)
+        .unwrap()
+        .and_hms_opt(23, 59, 59)
+        .unwrap();
+    assert_invalid_input_data!(log.append(before_2000, Event::Boot));
+    let after_2136 = NaiveDate::from_ymd_opt(2136, 3, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    assert_invalid_input_data!(log.append(after_2136, Event::Boot));
+}
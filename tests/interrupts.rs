@@ -0,0 +1,190 @@
+use ds323x::{AlarmEvent, WaitError};
+use embedded_hal_mock::eh1::{
+    digital::{Mock as PinMock, State, Transaction as PinTrans},
+    MockError,
+};
+use std::io::ErrorKind;
+
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, i2c_read, i2c_write, new_ds3231, new_ds3232,
+    new_ds3234, spi_read, spi_write, BitFlags as BF, Register, CONTROL_POR_VALUE,
+    DS3231_POR_STATUS, DS323X_POR_STATUS,
+};
+
+macro_rules! wait_for_alarm_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident, $read:ident, $write:ident,
+    $status:expr) => {
+        mod $name {
+            use super::*;
+
+            const ALARMS_INT_EN: u8 = BF::ALARM1_INT_EN | BF::ALARM2_INT_EN;
+
+            #[test]
+            fn alarm1() {
+                let mut trans = $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM1_INT_EN);
+                trans.extend($read(Register::STATUS, $status | BF::ALARM1F));
+                trans.extend($write(Register::STATUS, $status | BF::ALARM2F));
+                let mut dev = $create_method(&trans);
+                let mut pin = PinMock::new(&[
+                    PinTrans::get(State::High),
+                    PinTrans::get(State::High),
+                    PinTrans::get(State::Low),
+                ]);
+                dev.enable_alarm1_interrupts().unwrap();
+                let event = dev.wait_for_alarm_blocking(&mut pin).unwrap();
+                assert_eq!(AlarmEvent::Alarm1, event);
+                pin.done();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn alarm2() {
+                let mut trans = $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM2_INT_EN);
+                trans.extend($read(Register::STATUS, $status | BF::ALARM2F));
+                trans.extend($write(Register::STATUS, $status | BF::ALARM1F));
+                let mut dev = $create_method(&trans);
+                let mut pin = PinMock::new(&[PinTrans::get(State::Low)]);
+                dev.enable_alarm2_interrupts().unwrap();
+                let event = dev.wait_for_alarm_blocking(&mut pin).unwrap();
+                assert_eq!(AlarmEvent::Alarm2, event);
+                pin.done();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn both_alarms() {
+                let mut trans = $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM1_INT_EN);
+                trans.extend($write(Register::CONTROL, CONTROL_POR_VALUE | ALARMS_INT_EN));
+                trans.extend($read(Register::STATUS, $status | BF::ALARM1F | BF::ALARM2F));
+                trans.extend($write(Register::STATUS, $status));
+                let mut dev = $create_method(&trans);
+                let mut pin = PinMock::new(&[PinTrans::get(State::Low)]);
+                dev.enable_alarm1_interrupts().unwrap();
+                dev.enable_alarm2_interrupts().unwrap();
+                let event = dev.wait_for_alarm_blocking(&mut pin).unwrap();
+                assert_eq!(AlarmEvent::Both, event);
+                pin.done();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn ignores_alarm_without_enabled_interrupts() {
+                let mut trans = $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM1_INT_EN);
+                trans.extend($read(Register::STATUS, $status | BF::ALARM1F | BF::ALARM2F));
+                trans.extend($write(Register::STATUS, $status | BF::ALARM2F));
+                let mut dev = $create_method(&trans);
+                let mut pin = PinMock::new(&[PinTrans::get(State::Low)]);
+                dev.enable_alarm1_interrupts().unwrap();
+                let event = dev.wait_for_alarm_blocking(&mut pin).unwrap();
+                assert_eq!(AlarmEvent::Alarm1, event);
+                pin.done();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn no_alarm_matched() {
+                let mut trans = $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM1_INT_EN);
+                trans.extend($read(Register::STATUS, $status | BF::ALARM2F));
+                let mut dev = $create_method(&trans);
+                let mut pin = PinMock::new(&[PinTrans::get(State::Low)]);
+                dev.enable_alarm1_interrupts().unwrap();
+                match dev.wait_for_alarm_blocking(&mut pin) {
+                    Err(WaitError::NoAlarmMatched) => (),
+                    _ => panic!("NoAlarmMatched error not returned."),
+                }
+                pin.done();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn pin_error() {
+                let mut dev = $create_method(&[]);
+                let mut pin =
+                    PinMock::new(&[PinTrans::get(State::Low)
+                        .with_error(MockError::Io(ErrorKind::NotConnected))]);
+                match dev.wait_for_alarm_blocking(&mut pin) {
+                    Err(WaitError::Pin(_)) => (),
+                    _ => panic!("Pin error not returned."),
+                }
+                pin.done();
+                $destroy_method(dev);
+            }
+
+            #[cfg(feature = "async")]
+            mod async_wait {
+                use super::*;
+                use embassy_futures::block_on;
+
+                #[test]
+                fn alarm1() {
+                    let mut trans =
+                        $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM1_INT_EN);
+                    trans.extend($read(Register::STATUS, $status | BF::ALARM1F));
+                    trans.extend($write(Register::STATUS, $status | BF::ALARM2F));
+                    let mut dev = $create_method(&trans);
+                    let mut pin = PinMock::new(&[PinTrans::wait_for_state(State::Low)]);
+                    dev.enable_alarm1_interrupts().unwrap();
+                    let event = block_on(dev.wait_for_alarm(&mut pin)).unwrap();
+                    assert_eq!(AlarmEvent::Alarm1, event);
+                    pin.done();
+                    $destroy_method(dev);
+                }
+
+                #[test]
+                fn no_alarm_matched() {
+                    let mut trans =
+                        $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM2_INT_EN);
+                    trans.extend($read(Register::STATUS, $status | BF::ALARM1F));
+                    let mut dev = $create_method(&trans);
+                    let mut pin = PinMock::new(&[PinTrans::wait_for_state(State::Low)]);
+                    dev.enable_alarm2_interrupts().unwrap();
+                    match block_on(dev.wait_for_alarm(&mut pin)) {
+                        Err(WaitError::NoAlarmMatched) => (),
+                        _ => panic!("NoAlarmMatched error not returned."),
+                    }
+                    pin.done();
+                    $destroy_method(dev);
+                }
+
+                #[test]
+                fn pin_error() {
+                    let mut dev = $create_method(&[]);
+                    let mut pin = PinMock::new(&[PinTrans::wait_for_state(State::Low)
+                        .with_error(MockError::Io(ErrorKind::NotConnected))]);
+                    match block_on(dev.wait_for_alarm(&mut pin)) {
+                        Err(WaitError::Pin(_)) => (),
+                        _ => panic!("Pin error not returned."),
+                    }
+                    pin.done();
+                    $destroy_method(dev);
+                }
+            }
+        }
+    };
+}
+
+wait_for_alarm_test!(
+    for_ds3231,
+    new_ds3231,
+    destroy_ds3231,
+    i2c_read,
+    i2c_write,
+    DS3231_POR_STATUS
+);
+wait_for_alarm_test!(
+    for_ds3232,
+    new_ds3232,
+    destroy_ds3232,
+    i2c_read,
+    i2c_write,
+    DS323X_POR_STATUS
+);
+wait_for_alarm_test!(
+    for_ds3234,
+    new_ds3234,
+    destroy_ds3234,
+    spi_read,
+    spi_write,
+    DS323X_POR_STATUS
+);
@@ -0,0 +1,290 @@
+use ds323x::{Error, Record, RecordState, RecordStore};
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3232, destroy_ds3234, ds3232_sram_read, ds3232_sram_write, ds3234_sram_read,
+    ds3234_sram_write, new_ds3232, new_ds3234,
+};
+
+const OFFSET: u8 = 10;
+const SLOT_SIZE: usize = 11;
+
+#[derive(Debug, PartialEq)]
+struct Counter(u32);
+
+impl Record for Counter {
+    const SIZE: usize = 4;
+    const VERSION: u8 = 1;
+
+    fn to_bytes(&self, data: &mut [u8]) {
+        data.copy_from_slice(&self.0.to_le_bytes());
+    }
+
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        let value = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+        if value == u32::MAX {
+            None
+        } else {
+            Some(Counter(value))
+        }
+    }
+}
+
+struct TooLarge;
+
+impl Record for TooLarge {
+    const SIZE: usize = 122;
+    const VERSION: u8 = 1;
+
+    fn to_bytes(&self, _data: &mut [u8]) {}
+
+    fn from_bytes(_data: &[u8]) -> Option<Self> {
+        Some(TooLarge)
+    }
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if (crc & 0x8000) != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn slot_with_version(version: u8, seq: u8, value: u32) -> Vec<u8> {
+    let mut data = vec![0x52, 0x53, version, seq, 4];
+    data.extend_from_slice(&value.to_le_bytes());
+    let crc = crc16(&data);
+    data.extend_from_slice(&crc.to_be_bytes());
+    data
+}
+
+fn slot(seq: u8, value: u32) -> Vec<u8> {
+    slot_with_version(1, seq, value)
+}
+
+fn empty_slot() -> Vec<u8> {
+    vec![0; SLOT_SIZE]
+}
+
+macro_rules! record_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident, $read:ident, $write:ident,
+    $trans:ty) => {
+        mod $name {
+            use super::*;
+
+            fn read_slots(first: Vec<u8>, second: Vec<u8>) -> Vec<$trans> {
+                let mut trans = $read(OFFSET, first);
+                trans.extend($read(OFFSET + SLOT_SIZE as u8, second));
+                trans
+            }
+
+            fn load(first: Vec<u8>, second: Vec<u8>) -> RecordState<Counter> {
+                let trans = read_slots(first, second);
+                let mut dev = $create_method(&trans);
+                let state = RecordStore::new(&mut dev, OFFSET).load().unwrap();
+                $destroy_method(dev);
+                state
+            }
+
+            fn store(first: Vec<u8>, second: Vec<u8>, written: Vec<$trans>, value: u32) {
+                let mut trans = read_slots(first, second);
+                trans.extend(written);
+                let mut dev = $create_method(&trans);
+                RecordStore::new(&mut dev, OFFSET)
+                    .store(&Counter(value))
+                    .unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn load_empty() {
+                assert_eq!(RecordState::Empty, load(empty_slot(), empty_slot()));
+            }
+
+            #[test]
+            fn load_first_slot() {
+                let state = load(slot(3, 1234), empty_slot());
+                assert_eq!(RecordState::Valid(Counter(1234)), state);
+            }
+
+            #[test]
+            fn load_second_slot() {
+                let state = load(empty_slot(), slot(3, 1234));
+                assert_eq!(RecordState::Valid(Counter(1234)), state);
+            }
+
+            #[test]
+            fn load_newest() {
+                let state = load(slot(4, 1), slot(5, 2));
+                assert_eq!(RecordState::Valid(Counter(2)), state);
+                let state = load(slot(7, 1), slot(6, 2));
+                assert_eq!(RecordState::Valid(Counter(1)), state);
+            }
+
+            #[test]
+            fn load_newest_after_sequence_wrap_around() {
+                let state = load(slot(0, 1), slot(255, 2));
+                assert_eq!(RecordState::Valid(Counter(1)), state);
+            }
+
+            #[test]
+            fn load_corrupted() {
+                let mut corrupted = slot(1, 1234);
+                corrupted[6] ^= 0x10;
+                assert_eq!(RecordState::Invalid, load(corrupted, empty_slot()));
+            }
+
+            #[test]
+            fn load_random_contents() {
+                let mut first = vec![0; SLOT_SIZE];
+                let mut second = vec![0; SLOT_SIZE];
+                let mut value: u32 = 0x1234_5678;
+                for byte in first.iter_mut().chain(second.iter_mut()) {
+                    // xorshift
+                    value ^= value << 13;
+                    value ^= value >> 17;
+                    value ^= value << 5;
+                    *byte = value as u8;
+                }
+                assert_eq!(RecordState::Invalid, load(first, second));
+            }
+
+            #[test]
+            fn load_slot_without_magic() {
+                let mut slot = empty_slot();
+                slot[5] = 1;
+                assert_eq!(RecordState::Invalid, load(empty_slot(), slot));
+            }
+
+            #[test]
+            fn load_other_version() {
+                let state = load(slot_with_version(2, 1, 1234), empty_slot());
+                assert_eq!(RecordState::Invalid, state);
+            }
+
+            #[test]
+            fn load_rejected_by_record() {
+                assert_eq!(RecordState::Invalid, load(slot(1, u32::MAX), empty_slot()));
+            }
+
+            #[test]
+            fn load_older_copy_if_newest_rejected_by_record() {
+                let state = load(slot(1, 1234), slot(2, u32::MAX));
+                assert_eq!(RecordState::Valid(Counter(1234)), state);
+                let state = load(slot(2, u32::MAX), slot(1, 1234));
+                assert_eq!(RecordState::Valid(Counter(1234)), state);
+            }
+
+            #[test]
+            fn load_both_rejected_by_record() {
+                let state = load(slot(1, u32::MAX), slot(2, u32::MAX));
+                assert_eq!(RecordState::Invalid, state);
+            }
+
+            #[test]
+            fn load_valid_copy_next_to_corrupted() {
+                let mut corrupted = slot(2, 5678);
+                corrupted[9] ^= 0xFF;
+                let state = load(slot(1, 1234), corrupted);
+                assert_eq!(RecordState::Valid(Counter(1234)), state);
+            }
+
+            #[test]
+            fn load_previous_copy_after_torn_write() {
+                let old = slot(0, 1111);
+                let new = slot(2, 3333);
+                for written in 1..SLOT_SIZE {
+                    let mut torn = new[..written].to_vec();
+                    torn.extend_from_slice(&old[written..]);
+                    let state = load(slot(1, 2222), torn);
+                    assert_eq!(RecordState::Valid(Counter(2222)), state);
+                }
+            }
+
+            #[test]
+            fn store_in_empty() {
+                let written = $write(OFFSET, slot(0, 1234));
+                store(empty_slot(), empty_slot(), written, 1234);
+            }
+
+            #[test]
+            fn store_next_to_valid_copy() {
+                let written = $write(OFFSET + SLOT_SIZE as u8, slot(6, 1234));
+                store(slot(5, 1), empty_slot(), written, 1234);
+                let written = $write(OFFSET, slot(6, 1234));
+                store(empty_slot(), slot(5, 1), written, 1234);
+            }
+
+            #[test]
+            fn store_over_older_copy() {
+                let written = $write(OFFSET, slot(9, 1234));
+                store(slot(7, 1), slot(8, 2), written, 1234);
+                let written = $write(OFFSET + SLOT_SIZE as u8, slot(0, 1234));
+                store(slot(255, 1), slot(254, 2), written, 1234);
+            }
+
+            #[test]
+            fn store_over_corrupted_copy() {
+                let mut corrupted = slot(1, 1);
+                corrupted[0] = 0;
+                let written = $write(OFFSET, slot(5, 1234));
+                store(corrupted, slot(4, 2), written, 1234);
+            }
+
+            #[test]
+            fn erase() {
+                let trans = $write(OFFSET, vec![0; 2 * SLOT_SIZE]);
+                let mut dev = $create_method(&trans);
+                RecordStore::new(&mut dev, OFFSET)
+                    .erase::<Counter>()
+                    .unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn record_too_large() {
+                let mut dev = $create_method(&[]);
+                let mut store = RecordStore::new(&mut dev, 0);
+                assert_invalid_input_data!(store.load::<TooLarge>());
+                assert_invalid_input_data!(store.store(&TooLarge));
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn record_does_not_fit() {
+                let mut dev = $create_method(&[]);
+                let mut store = RecordStore::new(&mut dev, 240);
+                assert_invalid_input_data!(store.load::<Counter>());
+                assert_invalid_input_data!(store.erase::<Counter>());
+                $destroy_method(dev);
+            }
+        }
+    };
+}
+
+record_test!(
+    for_ds3232,
+    new_ds3232,
+    destroy_ds3232,
+    ds3232_sram_read,
+    ds3232_sram_write,
+    I2cTrans
+);
+record_test!(
+    for_ds3234,
+    new_ds3234,
+    destroy_ds3234,
+    ds3234_sram_read,
+    ds3234_sram_write,
+    SpiTrans<u8>
+);
@@ -0,0 +1,222 @@
+use ds323x::{Config, Error, NaiveTime, Reg, Rtcc};
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, i2c_read, i2c_write, new_ds3231, new_ds3232,
+    new_ds3234, spi_write, BitFlags as BF, Register, CONTROL_POR_VALUE, DEVICE_ADDRESS as DEV_ADDR,
+    DS3231_POR_STATUS, DS3234_SRAM_ADDRESS, DS3234_SRAM_DATA,
+};
+
+const ALARM_FLAGS: u8 = BF::ALARM2F | BF::ALARM1F;
+
+#[test]
+fn addresses_and_names_are_consistent() {
+    assert_eq!(0x00, Reg::Seconds.address());
+    assert_eq!(0x0A, Reg::Alarm1DayDate.address());
+    assert_eq!(Register::CONTROL, Reg::Control.address());
+    assert_eq!(0x12, Reg::TempLsb.address());
+    assert_eq!(0x19, Reg::SramData.address());
+    assert_eq!("ALARM2_DAY_DATE", Reg::Alarm2DayDate.name());
+    assert_eq!("TEMP_CONV", Reg::TempConv.name());
+    assert!(Reg::SramAddress.is_ds3234_only());
+    assert!(!Reg::TempLsb.is_ds3234_only());
+}
+
+#[test]
+fn can_read_and_write_registers() {
+    let trans = [
+        i2c_read(Register::ALARM1_HOURS, 0x12),
+        i2c_write(0x0D, 0x81),
+    ]
+    .concat();
+    let mut dev = new_ds3232(&trans);
+    assert_eq!(0x12, dev.read_reg(Reg::Alarm1Hours).unwrap());
+    dev.write_reg(Reg::Alarm2DayDate, 0x81).unwrap();
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn ds3234_only_registers_are_rejected_on_other_devices() {
+    let mut dev = new_ds3231(&[]);
+    assert_invalid_input_data!(dev.read_reg(Reg::TempConv), Register);
+    assert_invalid_input_data!(dev.write_reg(Reg::SramAddress, 0), Register);
+    destroy_ds3231(dev);
+
+    let mut dev = new_ds3232(&[]);
+    assert_invalid_input_data!(dev.modify_reg(Reg::SramData, |v| v), Register);
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn can_access_ds3234_registers() {
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![Register::TEMP_CONV, 0], vec![0, 0]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![Register::TEMP_CONV + 0x80, BF::TEMP_CONV_BAT]),
+        SpiTrans::transaction_end(),
+    ];
+    let mut dev = new_ds3234(&trans);
+    dev.modify_reg(Reg::TempConv, |v| v | BF::TEMP_CONV_BAT)
+        .unwrap();
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn reading_control_updates_cache() {
+    let control = CONTROL_POR_VALUE | BF::EOSC | BF::BBSQW;
+    let trans = [
+        i2c_read(Register::CONTROL, control | BF::TEMP_CONV),
+        i2c_write(Register::CONTROL, CONTROL_POR_VALUE | BF::BBSQW),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    assert_eq!(control | BF::TEMP_CONV, dev.read_reg(Reg::Control).unwrap());
+    dev.enable().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn writing_control_updates_cache() {
+    let control = CONTROL_POR_VALUE | BF::ALARM1_INT_EN;
+    let trans = [
+        i2c_write(Register::CONTROL, control | BF::TEMP_CONV),
+        // the conversion request is not repeated
+        i2c_write(Register::CONTROL, control | BF::BBSQW),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.write_reg(Reg::Control, control | BF::TEMP_CONV)
+        .unwrap();
+    dev.enable_square_wave().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn reading_status_updates_cache_without_flags() {
+    let status = DS3231_POR_STATUS & !BF::EN32KHZ;
+    let trans = [
+        i2c_read(Register::STATUS, status | BF::BUSY | BF::ALARM1F),
+        i2c_write(Register::STATUS, DS3231_POR_STATUS | ALARM_FLAGS),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.read_reg(Reg::Status).unwrap();
+    dev.enable_32khz_output().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn modifying_status_keeps_flags() {
+    let status = DS3231_POR_STATUS | BF::BUSY | BF::ALARM2F;
+    let trans = [
+        i2c_read(Register::STATUS, status),
+        i2c_write(Register::STATUS, BF::EN32KHZ | BF::ALARM2F),
+        i2c_write(Register::STATUS, ALARM_FLAGS),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.modify_reg(Reg::Status, |v| v & !BF::OSC_STOP).unwrap();
+    dev.disable_32khz_output().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn writing_aging_offset_updates_cache() {
+    let trans = [i2c_write(Register::AGING_OFFSET, 0xFE)].concat();
+    let mut dev = new_ds3231(&trans);
+    dev.write_reg(Reg::AgingOffset, 0xFE).unwrap();
+    // nothing is written as the cache contains the new value
+    dev.apply(Config {
+        aging_offset: -2,
+        ..Config::default()
+    })
+    .unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn verifies_written_registers() {
+    let trans = [
+        i2c_write(Register::ALARM2_HOURS, 0x23),
+        i2c_read(Register::ALARM2_HOURS, 0x03),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    match dev.write_reg(Reg::Alarm2Hours, 0x23) {
+        Err(Error::VerificationFailed { register }) => {
+            assert_eq!(Register::ALARM2_HOURS, register)
+        }
+        _ => panic!("VerificationFailed error not returned."),
+    }
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn does_not_verify_time_and_temperature_registers() {
+    let trans = [
+        i2c_write(Register::SECONDS, 0x30),
+        i2c_write(Register::YEAR, 0x24),
+        i2c_write(Register::TEMP_MSB, 0x19),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    dev.write_reg(Reg::Seconds, 0x30).unwrap();
+    dev.write_reg(Reg::Year, 0x24).unwrap();
+    dev.write_reg(Reg::TempMsb, 0x19).unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn does_not_verify_sram_registers() {
+    let trans = [
+        spi_write(DS3234_SRAM_ADDRESS, 0x10),
+        spi_write(DS3234_SRAM_DATA, 0xAB),
+        spi_write(DS3234_SRAM_DATA, 0xCD),
+    ]
+    .concat();
+    let mut dev = new_ds3234(&trans);
+    dev.enable_write_verification();
+    dev.write_reg(Reg::SramAddress, 0x10).unwrap();
+    dev.write_reg(Reg::SramData, 0xAB).unwrap();
+    dev.write_reg(Reg::SramData, 0xCD).unwrap();
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn writing_hours_updates_hour_mode() {
+    let trans = [
+        // 11 AM
+        i2c_write(Register::HOURS, 0b0101_0001),
+        // 1 PM
+        vec![I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::SECONDS, 0, 0, 0b0110_0001],
+        )],
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    dev.write_reg(Reg::Hours, 0b0101_0001).unwrap();
+    dev.set_time(&NaiveTime::from_hms_opt(13, 0, 0).unwrap())
+        .unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn can_read_registers_in_any_mode() {
+    let trans = [
+        i2c_write(Register::CONTROL, CONTROL_POR_VALUE),
+        i2c_read(Register::TEMP_MSB, 25),
+    ]
+    .concat();
+    let dev = new_ds3231(&trans);
+    let mut dev = dev.into_interrupt_mode().unwrap();
+    assert_eq!(25, dev.read_reg(Reg::TempMsb).unwrap());
+    dev.destroy_ds3231().done();
+}
@@ -0,0 +1,171 @@
+use ds323x::{ic, interface::I2cInterface, Ds323x, Error, RetryPolicy};
+use embedded_hal::{
+    delay::DelayNs,
+    i2c::{ErrorKind, NoAcknowledgeSource},
+};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+
+#[allow(unused)]
+mod common;
+use self::common::{Register, DEVICE_ADDRESS as DEV_ADDR};
+
+const NACK_ADDRESS: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+const NACK_DATA: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+
+#[derive(Default)]
+struct RecordingDelay {
+    delays_ns: Vec<u32>,
+}
+
+impl DelayNs for RecordingDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.delays_ns.push(ns);
+    }
+}
+
+fn new(transactions: &[I2cTrans], attempts: u8) -> Ds323x<I2cInterface<I2cMock>, ic::DS3231> {
+    let mut dev = Ds323x::new_ds3231(I2cMock::new(transactions));
+    dev.set_retry_policy(RetryPolicy {
+        attempts,
+        backoff_us: 0,
+    });
+    dev
+}
+
+fn read(error: Option<ErrorKind>) -> I2cTrans {
+    let trans = I2cTrans::write_read(DEV_ADDR, vec![Register::AGING_OFFSET], vec![5]);
+    match error {
+        Some(error) => trans.with_error(error),
+        None => trans,
+    }
+}
+
+fn write(error: Option<ErrorKind>) -> I2cTrans {
+    let trans = I2cTrans::write(DEV_ADDR, vec![Register::AGING_OFFSET, 5]);
+    match error {
+        Some(error) => trans.with_error(error),
+        None => trans,
+    }
+}
+
+fn assert_comm_error<T: core::fmt::Debug>(
+    expected: ErrorKind,
+    result: Result<T, Error<ErrorKind>>,
+) {
+    match result {
+        Err(Error::Comm(error)) => assert_eq!(expected, error),
+        _ => panic!("Comm error not returned: {:?}", result),
+    }
+}
+
+#[test]
+fn default_policy_makes_single_attempt() {
+    let dev = Ds323x::new_ds3231(I2cMock::new(&[]));
+    assert_eq!(RetryPolicy::default(), dev.retry_policy());
+    assert_eq!(1, dev.retry_policy().attempts);
+    dev.destroy_ds3231().done();
+
+    let mut dev = Ds323x::new_ds3231(I2cMock::new(&[read(Some(NACK_ADDRESS))]));
+    assert_comm_error(NACK_ADDRESS, dev.aging_offset());
+    dev.destroy_ds3231().done();
+}
+
+#[test]
+fn retries_read_on_nack() {
+    let trans = [read(Some(NACK_ADDRESS)), read(Some(NACK_DATA)), read(None)];
+    let mut dev = new(&trans, 3);
+    assert_eq!(5, dev.aging_offset().unwrap());
+    dev.destroy_ds3231().done();
+}
+
+#[test]
+fn retries_on_arbitration_loss() {
+    let trans = [read(Some(ErrorKind::ArbitrationLoss)), read(None)];
+    let mut dev = new(&trans, 2);
+    assert_eq!(5, dev.aging_offset().unwrap());
+    dev.destroy_ds3231().done();
+}
+
+#[test]
+fn does_not_retry_other_errors() {
+    for error in [ErrorKind::Bus, ErrorKind::Overrun, ErrorKind::Other] {
+        let mut dev = new(&[read(Some(error))], 3);
+        assert_comm_error(error, dev.aging_offset());
+        dev.destroy_ds3231().done();
+    }
+}
+
+#[test]
+fn returns_last_error_when_attempts_exhausted() {
+    let trans = [
+        read(Some(NACK_ADDRESS)),
+        read(Some(ErrorKind::ArbitrationLoss)),
+    ];
+    let mut dev = new(&trans, 2);
+    assert_comm_error(ErrorKind::ArbitrationLoss, dev.aging_offset());
+    dev.destroy_ds3231().done();
+}
+
+#[test]
+fn retries_write_on_address_nack() {
+    let trans = [write(Some(NACK_ADDRESS)), write(None)];
+    let mut dev = new(&trans, 3);
+    dev.set_aging_offset(5).unwrap();
+    dev.destroy_ds3231().done();
+}
+
+#[test]
+fn does_not_repeat_partially_written_data() {
+    let mut dev = new(&[write(Some(NACK_DATA))], 3);
+    assert_comm_error(NACK_DATA, dev.set_aging_offset(5));
+    dev.destroy_ds3231().done();
+}
+
+#[test]
+fn retries_sram_access() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![0x14], vec![1, 2]).with_error(NACK_ADDRESS),
+        I2cTrans::write_read(DEV_ADDR, vec![0x14], vec![1, 2]),
+    ];
+    let mut dev = Ds323x::new_ds3232(I2cMock::new(&trans));
+    dev.set_retry_policy(RetryPolicy {
+        attempts: 2,
+        backoff_us: 0,
+    });
+    let mut data = [0; 2];
+    dev.read_sram(0, &mut data).unwrap();
+    assert_eq!([1, 2], data);
+    dev.destroy_ds3232().done();
+}
+
+#[test]
+fn waits_backoff_between_attempts() {
+    let trans = [
+        read(Some(NACK_ADDRESS)),
+        read(Some(NACK_ADDRESS)),
+        read(None),
+    ];
+    let mut delay = RecordingDelay::default();
+    let mut dev = Ds323x::new_ds3231(I2cMock::new(&trans)).with_retry_delay(&mut delay);
+    dev.set_retry_policy(RetryPolicy {
+        attempts: 3,
+        backoff_us: 250,
+    });
+    assert_eq!(5, dev.aging_offset().unwrap());
+    dev.destroy_ds3231().done();
+    assert_eq!(2, delay.delays_ns.len());
+    assert_eq!(500_000, delay.delays_ns.iter().sum::<u32>());
+}
+
+#[test]
+fn does_not_wait_without_failures() {
+    let mut delay = RecordingDelay::default();
+    let mut dev = Ds323x::new_ds3231(I2cMock::new(&[read(None)])).with_retry_delay(&mut delay);
+    dev.set_retry_policy(RetryPolicy {
+        attempts: 3,
+        backoff_us: 250,
+    });
+    assert_eq!(5, dev.aging_offset().unwrap());
+    dev.destroy_ds3231().done();
+    assert!(delay.delays_ns.is_empty());
+}
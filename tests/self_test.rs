@@ -0,0 +1,385 @@
+use ds323x::{CheckResult, Ds323x, SelfTestReport};
+use embedded_hal::{
+    delay::DelayNs,
+    i2c::{self, ErrorKind, I2c},
+    spi::{self, Operation, SpiDevice},
+};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+use std::{cell::RefCell, rc::Rc};
+
+#[allow(unused)]
+mod common;
+use self::common::{BitFlags as BF, FakeI2c, Register, DEVICE_ADDRESS as DEV_ADDR};
+
+const ALARM1_SECONDS: usize = 0x07;
+const DS3232_SRAM_START: usize = 0x14;
+const DS3234_SRAM_ADDRESS: usize = 0x18;
+const DS3234_SRAM_DATA: usize = 0x19;
+
+// Simulated device registers. The DS3234 SRAM is kept separately.
+struct State {
+    registers: [u8; 256],
+    sram: [u8; 256],
+    // Bits of the registers which always read as zero
+    stuck_at_zero: [u8; 256],
+    oscillator_running: bool,
+    conversion_finishes: bool,
+    elapsed_ns: u64,
+}
+
+#[derive(Clone)]
+struct Device(Rc<RefCell<State>>);
+
+impl Device {
+    fn new() -> Self {
+        let mut registers = [0; 256];
+        // 2024-02-29 23:59:58
+        registers[..7].copy_from_slice(&[0x58, 0x59, 0x23, 5, 0x29, 0x02, 0x24]);
+        registers[ALARM1_SECONDS..ALARM1_SECONDS + 7].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7]);
+        registers[usize::from(Register::CONTROL)] = 0x1C;
+        registers[usize::from(Register::AGING_OFFSET)] = 0xFD;
+        // 25.25°C
+        registers[usize::from(Register::TEMP_MSB)] = 25;
+        registers[usize::from(Register::TEMP_MSB) + 1] = 0x40;
+        let mut sram = [0; 256];
+        for (i, value) in sram.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+        for (i, value) in registers[DS3232_SRAM_START..].iter_mut().enumerate() {
+            *value = i as u8;
+        }
+        Device(Rc::new(RefCell::new(State {
+            registers,
+            sram,
+            stuck_at_zero: [0; 256],
+            oscillator_running: true,
+            conversion_finishes: true,
+            elapsed_ns: 0,
+        })))
+    }
+
+    fn registers(&self) -> [u8; 256] {
+        self.0.borrow().registers
+    }
+
+    fn sram(&self) -> [u8; 256] {
+        self.0.borrow().sram
+    }
+
+    fn delay(&self) -> Delay {
+        Delay(self.clone())
+    }
+
+    fn set<T>(&self, f: impl FnOnce(&mut State) -> T) -> T {
+        f(&mut self.0.borrow_mut())
+    }
+}
+
+impl State {
+    fn apply_stuck_bits(&mut self) {
+        for (value, stuck) in self.registers.iter_mut().zip(self.stuck_at_zero.iter()) {
+            *value &= !stuck;
+        }
+    }
+}
+
+impl i2c::ErrorType for Device {
+    type Error = ErrorKind;
+}
+
+impl I2c for Device {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut state = self.0.borrow_mut();
+        let mut fake = FakeI2c {
+            registers: state.registers,
+        };
+        fake.transaction(address, operations)?;
+        state.registers = fake.registers;
+        state.apply_stuck_bits();
+        Ok(())
+    }
+}
+
+impl spi::ErrorType for Device {
+    type Error = spi::ErrorKind;
+}
+
+impl SpiDevice for Device {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let mut state = self.0.borrow_mut();
+        let mut address: Option<(u8, bool)> = None;
+        let access =
+            |state: &mut State, address: &mut Option<(u8, bool)>, byte: &mut u8| match *address {
+                None => *address = Some((*byte & 0x7F, *byte & 0x80 != 0)),
+                Some((register, is_write)) => {
+                    let register = usize::from(register);
+                    if register == DS3234_SRAM_DATA {
+                        let sram_address = usize::from(state.registers[DS3234_SRAM_ADDRESS]);
+                        if is_write {
+                            state.sram[sram_address] = *byte;
+                        } else {
+                            *byte = state.sram[sram_address];
+                        }
+                        state.registers[DS3234_SRAM_ADDRESS] =
+                            state.registers[DS3234_SRAM_ADDRESS].wrapping_add(1);
+                        return;
+                    }
+                    if is_write {
+                        state.registers[register] = *byte;
+                    } else {
+                        *byte = state.registers[register];
+                    }
+                    *address = Some(((register + 1) as u8, is_write));
+                }
+            };
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => {
+                    for byte in data.iter() {
+                        access(&mut state, &mut address, &mut byte.clone());
+                    }
+                }
+                Operation::Read(data) => {
+                    for byte in data.iter_mut() {
+                        access(&mut state, &mut address, byte);
+                    }
+                }
+                Operation::TransferInPlace(data) => {
+                    for byte in data.iter_mut() {
+                        access(&mut state, &mut address, byte);
+                    }
+                }
+                _ => unimplemented!(),
+            }
+        }
+        state.apply_stuck_bits();
+        Ok(())
+    }
+}
+
+// Delay advancing the simulated clock and finishing temperature conversions
+struct Delay(Device);
+
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.set(|state| {
+            if state.conversion_finishes {
+                state.registers[usize::from(Register::CONTROL)] &= !BF::TEMP_CONV;
+            }
+            if !state.oscillator_running {
+                return;
+            }
+            let seconds_before = state.elapsed_ns / 1_000_000_000;
+            state.elapsed_ns += u64::from(ns);
+            for _ in seconds_before..state.elapsed_ns / 1_000_000_000 {
+                tick(&mut state.registers);
+            }
+        });
+    }
+}
+
+// Advance the time by one second. Only seconds, minutes and hours are
+// updated, which is enough for these tests.
+fn tick(registers: &mut [u8; 256]) {
+    for (index, limit) in [(0, 0x59), (1, 0x59), (2, 0x23)] {
+        if registers[index] < limit {
+            let value = registers[index] + 1;
+            registers[index] = if value & 0xF > 9 { value + 6 } else { value };
+            return;
+        }
+        registers[index] = 0;
+    }
+}
+
+fn all_passed_with_sram(sram: CheckResult) -> SelfTestReport {
+    SelfTestReport {
+        communication: CheckResult::Passed,
+        oscillator: CheckResult::Passed,
+        temperature: CheckResult::Passed,
+        aging_offset: CheckResult::Passed,
+        alarms: CheckResult::Passed,
+        sram,
+    }
+}
+
+#[test]
+fn ds3231_passes() {
+    let device = Device::new();
+    let original = device.registers();
+    let mut dev = Ds323x::new_ds3231(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(all_passed_with_sram(CheckResult::Skipped), report);
+    assert!(report.passed());
+    let registers = device.registers();
+    assert_eq!(original[0x07..=0x10], registers[0x07..=0x10]);
+    assert_eq!(-3, dev.aging_offset().unwrap());
+}
+
+#[test]
+fn ds3232_passes_and_restores_sram() {
+    let device = Device::new();
+    let original = device.registers();
+    let mut dev = Ds323x::new_ds3232(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(all_passed_with_sram(CheckResult::Passed), report);
+    assert_eq!(original[0x07..], device.registers()[0x07..]);
+}
+
+#[test]
+fn ds3234_passes_and_restores_registers() {
+    let device = Device::new();
+    let original = device.registers();
+    let original_sram = device.sram();
+    let mut dev = Ds323x::new_ds3234(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(all_passed_with_sram(CheckResult::Passed), report);
+    assert_eq!(original[0x07..=0x10], device.registers()[0x07..=0x10]);
+    assert_eq!(original_sram, device.sram());
+}
+
+#[test]
+fn detects_stopped_oscillator() {
+    let device = Device::new();
+    device.set(|state| state.oscillator_running = false);
+    let mut dev = Ds323x::new_ds3231(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(CheckResult::Failed, report.oscillator);
+    assert_eq!(CheckResult::Passed, report.temperature);
+    assert!(!report.passed());
+}
+
+#[test]
+fn detects_invalid_time() {
+    let device = Device::new();
+    device.set(|state| state.registers[0] = 0x5A);
+    let mut dev = Ds323x::new_ds3231(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(CheckResult::Failed, report.oscillator);
+}
+
+#[test]
+fn detects_unfinished_temperature_conversion() {
+    let device = Device::new();
+    device.set(|state| state.conversion_finishes = false);
+    let mut dev = Ds323x::new_ds3231(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(CheckResult::Failed, report.temperature);
+    assert_eq!(CheckResult::Passed, report.oscillator);
+}
+
+#[test]
+fn detects_implausible_temperature() {
+    let device = Device::new();
+    device.set(|state| state.registers[usize::from(Register::TEMP_MSB)] = 100);
+    let mut dev = Ds323x::new_ds3231(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(CheckResult::Failed, report.temperature);
+}
+
+#[test]
+fn detects_stuck_register_bits() {
+    let device = Device::new();
+    device.set(|state| {
+        state.stuck_at_zero[usize::from(Register::AGING_OFFSET)] = 0x10;
+        state.stuck_at_zero[usize::from(Register::ALARM2_HOURS)] = 0x01;
+    });
+    let mut dev = Ds323x::new_ds3231(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(CheckResult::Failed, report.aging_offset);
+    assert_eq!(CheckResult::Failed, report.alarms);
+    assert_eq!(CheckResult::Passed, report.oscillator);
+    assert!(!report.passed());
+}
+
+#[test]
+fn detects_stuck_sram_bits() {
+    let device = Device::new();
+    device.set(|state| state.stuck_at_zero[0xFF] = 0x80);
+    let mut dev = Ds323x::new_ds3232(device.clone());
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(CheckResult::Failed, report.sram);
+    assert_eq!(CheckResult::Passed, report.alarms);
+}
+
+#[test]
+fn keeps_write_verification_setting() {
+    let device = Device::new();
+    device.set(|state| state.stuck_at_zero[usize::from(Register::AGING_OFFSET)] = 0x10);
+    let mut dev = Ds323x::new_ds3231(device.clone());
+    dev.enable_write_verification();
+    let report = dev.self_test(&mut device.delay());
+    assert_eq!(CheckResult::Failed, report.aging_offset);
+    assert!(dev.set_aging_offset(0x10).is_err());
+}
+
+#[test]
+fn reports_missing_device() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![Register::CONTROL], vec![0])
+            .with_error(ErrorKind::NoAcknowledge(i2c::NoAcknowledgeSource::Address)),
+    ];
+    let mut dev = Ds323x::new_ds3231(I2cMock::new(&trans));
+    let report = dev.self_test(&mut Delay(Device::new()));
+    assert_eq!(
+        SelfTestReport {
+            communication: CheckResult::Failed,
+            ..SelfTestReport::default()
+        },
+        report
+    );
+    assert!(!report.passed());
+    dev.destroy_ds3231().done();
+}
+
+#[test]
+fn reports_bus_error_as_failed_check() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![Register::CONTROL], vec![0]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::SECONDS], vec![0; 7])
+            .with_error(ErrorKind::Bus),
+    ];
+    let mut dev = Ds323x::new_ds3231(I2cMock::new(&trans));
+    let report = dev.self_test(&mut Delay(Device::new()));
+    assert_eq!(
+        SelfTestReport {
+            communication: CheckResult::Passed,
+            oscillator: CheckResult::Failed,
+            ..SelfTestReport::default()
+        },
+        report
+    );
+    assert!(!report.passed());
+    dev.destroy_ds3231().done();
+}
+
+#[test]
+fn keeps_results_before_bus_error() {
+    // 2024-02-29 23:59:58 and one second later
+    let before = vec![0x58, 0x59, 0x23, 5, 0x29, 0x02, 0x24];
+    let after = vec![0x59, 0x59, 0x23, 5, 0x29, 0x02, 0x24];
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![Register::CONTROL], vec![0]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::SECONDS], before),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::SECONDS], after),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::CONTROL], vec![0]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BF::TEMP_CONV])
+            .with_error(ErrorKind::Bus),
+    ];
+    let mut dev = Ds323x::new_ds3231(I2cMock::new(&trans));
+    dev.enable_write_verification();
+    let report = dev.self_test(&mut Delay(Device::new()));
+    assert_eq!(
+        SelfTestReport {
+            communication: CheckResult::Passed,
+            oscillator: CheckResult::Passed,
+            temperature: CheckResult::Failed,
+            ..SelfTestReport::default()
+        },
+        report
+    );
+    dev.destroy_ds3231().done();
+}
@@ -0,0 +1,142 @@
+#![cfg(feature = "serde")]
+
+use ds323x::{
+    Alarm1Matching, Alarm2Matching, Config, DayAlarm1, DayAlarm2, Ds323x, HourMode, Hours,
+    IntSqwMode, PowerProfile, SqWFreq, TempConvRate, TimeValidity, Weekday, WeekdayAlarm1,
+    WeekdayAlarm2, WeekdayNumbering,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{from_str, json, to_value, Value};
+
+#[allow(unused)]
+mod common;
+use self::common::FakeI2c;
+
+fn round_trip<T>(value: T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + core::fmt::Debug,
+{
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(value, from_str::<T>(&json).unwrap());
+}
+
+fn rejects<T: DeserializeOwned + core::fmt::Debug>(value: Value) {
+    assert!(serde_json::from_value::<T>(value).is_err());
+}
+
+#[test]
+fn can_round_trip_enums() {
+    round_trip(SqWFreq::_4_096Hz);
+    round_trip(IntSqwMode::SquareWave);
+    round_trip(HourMode::H12);
+    round_trip(WeekdayNumbering::MondayFirst);
+    round_trip(TempConvRate::_256s);
+    round_trip(TimeValidity::OscillatorStopped);
+    round_trip(Alarm1Matching::HoursMinutesAndSecondsMatch);
+    round_trip(Alarm2Matching::MinutesMatch);
+}
+
+#[test]
+fn can_round_trip_config() {
+    round_trip(Config {
+        int_sqw_mode: IntSqwMode::SquareWave,
+        temperature_conversion_rate: TempConvRate::_512s,
+        aging_offset: -12,
+        ..Config::default()
+    });
+}
+
+#[test]
+fn can_round_trip_power_profile() {
+    round_trip(PowerProfile {
+        square_wave_on_battery: Some(true),
+        temperature_conversion_rate: Some(TempConvRate::_64s),
+        ..PowerProfile::default()
+    });
+}
+
+#[test]
+fn can_round_trip_alarms() {
+    round_trip(DayAlarm1 {
+        day: 31,
+        hour: Hours::PM(12),
+        minute: 59,
+        second: 59,
+    });
+    round_trip(WeekdayAlarm1 {
+        weekday: Weekday::Sun,
+        hour: Hours::H24(23),
+        minute: 0,
+        second: 0,
+    });
+    round_trip(DayAlarm2 {
+        day: 1,
+        hour: Hours::AM(1),
+        minute: 30,
+    });
+    round_trip(WeekdayAlarm2 {
+        weekday: Weekday::Wed,
+        hour: Hours::H24(0),
+        minute: 15,
+    });
+}
+
+#[test]
+fn serializes_alarm_fields() {
+    let alarm = DayAlarm2 {
+        day: 13,
+        hour: Hours::H24(7),
+        minute: 30,
+    };
+    let expected = json!({ "day": 13, "hour": { "H24": 7 }, "minute": 30 });
+    assert_eq!(expected, to_value(alarm).unwrap());
+}
+
+#[test]
+fn can_round_trip_alarms_accepted_by_setters() {
+    let mut dev = Ds323x::new_ds3231(FakeI2c::default());
+    let alarm1 = DayAlarm1 {
+        day: 0,
+        hour: Hours::H24(24),
+        minute: 60,
+        second: 4,
+    };
+    dev.set_alarm1_day(alarm1, Alarm1Matching::OncePerSecond)
+        .unwrap();
+    round_trip(alarm1);
+    let alarm1 = WeekdayAlarm1 {
+        weekday: Weekday::Mon,
+        hour: Hours::AM(0),
+        minute: 60,
+        second: 60,
+    };
+    dev.set_alarm1_weekday(alarm1, Alarm1Matching::OncePerSecond)
+        .unwrap();
+    round_trip(alarm1);
+    let alarm2 = DayAlarm2 {
+        day: 32,
+        hour: Hours::PM(13),
+        minute: 60,
+    };
+    dev.set_alarm2_day(alarm2, Alarm2Matching::OncePerMinute)
+        .unwrap();
+    round_trip(alarm2);
+    let alarm2 = WeekdayAlarm2 {
+        weekday: Weekday::Mon,
+        hour: Hours::AM(13),
+        minute: 60,
+    };
+    dev.set_alarm2_weekday(alarm2, Alarm2Matching::OncePerMinute)
+        .unwrap();
+    round_trip(alarm2);
+}
+
+#[test]
+fn rejects_invalid_second_used_by_all_matching_strategies() {
+    rejects::<DayAlarm1>(json!({ "day": 1, "hour": { "H24": 7 }, "minute": 0, "second": 60 }));
+}
+
+#[test]
+fn rejects_invalid_weekday() {
+    rejects::<WeekdayAlarm2>(json!({ "weekday": "Funday", "hour": { "H24": 7 }, "minute": 0 }));
+}
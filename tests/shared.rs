@@ -0,0 +1,133 @@
+#![cfg(feature = "critical-section")]
+
+use ds323x::SharedDs323x;
+
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, i2c_read, i2c_write, new_ds3231, new_ds3232,
+    new_ds3234, spi_read, spi_write, BitFlags as BF, Register, CONTROL_POR_VALUE,
+    DS3231_POR_STATUS, DS323X_POR_STATUS,
+};
+
+const ALARM_FLAGS: u8 = BF::ALARM1F | BF::ALARM2F;
+
+macro_rules! shared_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident, $read:ident, $write:ident,
+    $status:expr) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn interleaved_calls_keep_status_cache_coherent() {
+                let status = $status & !BF::EN32KHZ;
+                let mut trans = $write(Register::STATUS, status | ALARM_FLAGS);
+                trans.extend($read(Register::STATUS, status | BF::ALARM1F));
+                trans.extend($write(Register::STATUS, status | BF::ALARM2F));
+                trans.extend($write(
+                    Register::STATUS,
+                    (status & !BF::OSC_STOP) | ALARM_FLAGS,
+                ));
+                let rtc = SharedDs323x::new($create_method(&trans));
+                // main loop
+                rtc.lock(|rtc| rtc.disable_32khz_output()).unwrap();
+                // interrupt handler
+                rtc.lock(|rtc| {
+                    if rtc.has_alarm1_matched()? {
+                        rtc.clear_alarm1_matched_flag()?;
+                    }
+                    Ok::<_, ds323x::Error<_>>(())
+                })
+                .unwrap();
+                // another task
+                rtc.lock(|rtc| rtc.clear_has_been_stopped_flag()).unwrap();
+                $destroy_method(rtc.into_inner());
+            }
+
+            #[test]
+            fn interleaved_calls_keep_control_cache_coherent() {
+                let mut trans = $write(Register::CONTROL, CONTROL_POR_VALUE | BF::ALARM1_INT_EN);
+                trans.extend($write(
+                    Register::CONTROL,
+                    CONTROL_POR_VALUE | BF::ALARM1_INT_EN | BF::ALARM2_INT_EN,
+                ));
+                trans.extend($write(
+                    Register::CONTROL,
+                    CONTROL_POR_VALUE | BF::ALARM2_INT_EN,
+                ));
+                let rtc = SharedDs323x::new($create_method(&trans));
+                rtc.lock(|rtc| rtc.enable_alarm1_interrupts()).unwrap();
+                rtc.lock(|rtc| rtc.enable_alarm2_interrupts()).unwrap();
+                rtc.lock(|rtc| rtc.disable_alarm1_interrupts()).unwrap();
+                $destroy_method(rtc.into_inner());
+            }
+
+            #[test]
+            fn calls_from_several_threads_are_serialized() {
+                const THREADS: usize = 4;
+                let mut trans = Vec::new();
+                for _ in 0..THREADS {
+                    trans.extend($write(
+                        Register::CONTROL,
+                        CONTROL_POR_VALUE | BF::ALARM1_INT_EN,
+                    ));
+                    trans.extend($write(Register::CONTROL, CONTROL_POR_VALUE));
+                }
+                let rtc = SharedDs323x::new($create_method(&trans));
+                std::thread::scope(|s| {
+                    for _ in 0..THREADS {
+                        s.spawn(|| {
+                            rtc.lock(|rtc| {
+                                rtc.enable_alarm1_interrupts()?;
+                                rtc.disable_alarm1_interrupts()
+                            })
+                            .unwrap();
+                        });
+                    }
+                });
+                $destroy_method(rtc.into_inner());
+            }
+
+            #[test]
+            fn can_get_mut() {
+                let mut trans = $write(Register::AGING_OFFSET, 5);
+                trans.extend($read(Register::AGING_OFFSET, 5));
+                let mut rtc = SharedDs323x::new($create_method(&trans));
+                rtc.get_mut().set_aging_offset(5).unwrap();
+                assert_eq!(5, rtc.lock(|rtc| rtc.aging_offset()).unwrap());
+                $destroy_method(rtc.into_inner());
+            }
+
+            #[test]
+            #[should_panic]
+            fn nested_lock_panics() {
+                let rtc = SharedDs323x::new($create_method(&[]));
+                rtc.lock(|_| rtc.lock(|_| ()));
+            }
+        }
+    };
+}
+
+shared_test!(
+    for_ds3231,
+    new_ds3231,
+    destroy_ds3231,
+    i2c_read,
+    i2c_write,
+    DS3231_POR_STATUS
+);
+shared_test!(
+    for_ds3232,
+    new_ds3232,
+    destroy_ds3232,
+    i2c_read,
+    i2c_write,
+    DS323X_POR_STATUS
+);
+shared_test!(
+    for_ds3234,
+    new_ds3234,
+    destroy_ds3234,
+    spi_read,
+    spi_write,
+    DS323X_POR_STATUS
+);
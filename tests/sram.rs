@@ -0,0 +1,186 @@
+use ds323x::Error;
+
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3232, destroy_ds3234, ds3232_sram_read, ds3232_sram_write, ds3234_sram_read,
+    ds3234_sram_write, new_ds3232, new_ds3234,
+};
+
+macro_rules! sram_test {
+    ($name:ident, $create_method:ident, $destroy_method:ident, $read:ident, $write:ident,
+    $size:expr, $erase_size:expr) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn can_read() {
+                let trans = $read(5, vec![1, 2, 3]);
+                let mut dev = $create_method(&trans);
+                let mut data = [0; 3];
+                dev.read_sram(5, &mut data).unwrap();
+                assert_eq!([1, 2, 3], data);
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn can_read_last_byte() {
+                let trans = $read(($size - 1) as u8, vec![0xAB]);
+                let mut dev = $create_method(&trans);
+                let mut data = [0];
+                dev.read_sram(($size - 1) as u8, &mut data).unwrap();
+                assert_eq!([0xAB], data);
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn can_write() {
+                let trans = $write(0, vec![4, 5, 6, 7]);
+                let mut dev = $create_method(&trans);
+                dev.write_sram(0, &[4, 5, 6, 7]).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn empty_data_does_nothing() {
+                let mut dev = $create_method(&[]);
+                dev.read_sram(0, &mut []).unwrap();
+                dev.write_sram(0, &[]).unwrap();
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn cannot_read_past_end() {
+                let mut dev = $create_method(&[]);
+                let mut data = [0; 2];
+                assert_invalid_input_data!(dev.read_sram(($size - 1) as u8, &mut data));
+                $destroy_method(dev);
+            }
+
+            #[test]
+            fn cannot_write_past_end() {
+                let mut dev = $create_method(&[]);
+                assert_invalid_input_data!(dev.write_sram(0, &[0; $size + 1]));
+                $destroy_method(dev);
+            }
+
+            #[cfg(feature = "embedded-storage")]
+            mod storage {
+                use super::*;
+                use embedded_storage::{
+                    nor_flash::{NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash},
+                    ReadStorage, Storage,
+                };
+
+                #[test]
+                fn capacity() {
+                    let dev = $create_method(&[]);
+                    assert_eq!($size, ReadStorage::capacity(&dev));
+                    assert_eq!($size, ReadNorFlash::capacity(&dev));
+                    $destroy_method(dev);
+                }
+
+                #[test]
+                fn can_read_and_write() {
+                    let mut trans = $write(10, vec![1, 2]);
+                    trans.extend($read(10, vec![1, 2]));
+                    let mut dev = $create_method(&trans);
+                    Storage::write(&mut dev, 10, &[1, 2]).unwrap();
+                    let mut data = [0; 2];
+                    ReadStorage::read(&mut dev, 10, &mut data).unwrap();
+                    assert_eq!([1, 2], data);
+                    $destroy_method(dev);
+                }
+
+                #[test]
+                fn out_of_bounds() {
+                    let mut dev = $create_method(&[]);
+                    let mut data = [0; 1];
+                    let result = ReadStorage::read(&mut dev, $size, &mut data);
+                    assert_invalid_input_data!(result);
+                    let result = ReadNorFlash::read(&mut dev, 1 << 16, &mut data);
+                    assert_eq!(NorFlashErrorKind::OutOfBounds, result.unwrap_err().kind());
+                    assert_invalid_input_data!(Storage::write(&mut dev, $size - 1, &[0; 2]));
+                    $destroy_method(dev);
+                }
+
+                #[test]
+                fn erase_size() {
+                    let dev = $create_method(&[]);
+                    assert_eq!($erase_size, erase_size_of(&dev));
+                    $destroy_method(dev);
+                }
+
+                fn erase_size_of<T: NorFlash>(_: &T) -> usize {
+                    T::ERASE_SIZE
+                }
+
+                // Flash parameter checks done by `sequential-storage`
+                fn check_sequential_storage_parameters<T: NorFlash>(flash: &T) {
+                    let word_size = T::READ_SIZE.max(T::WRITE_SIZE);
+                    assert!(T::ERASE_SIZE.is_power_of_two());
+                    assert_eq!(0, T::ERASE_SIZE % word_size);
+                    assert!(T::ERASE_SIZE >= 3 * word_size);
+                    assert_eq!(0, flash.capacity() % T::ERASE_SIZE);
+                    assert!(flash.capacity() / T::ERASE_SIZE >= 2);
+                }
+
+                #[test]
+                fn fulfills_sequential_storage_requirements() {
+                    let dev = $create_method(&[]);
+                    check_sequential_storage_parameters(&dev);
+                    $destroy_method(dev);
+                }
+
+                #[test]
+                fn can_erase_block() {
+                    const BLOCK: usize = $erase_size;
+                    let trans = $write(BLOCK as u8, vec![0xFF; BLOCK]);
+                    let mut dev = $create_method(&trans);
+                    NorFlash::erase(&mut dev, BLOCK as u32, 2 * BLOCK as u32).unwrap();
+                    $destroy_method(dev);
+                }
+
+                #[test]
+                fn can_erase_all() {
+                    let mut trans = Vec::new();
+                    for start in (0..$size).step_by(64) {
+                        let len = ($size - start).min(64);
+                        trans.extend($write(start as u8, vec![0xFF; len]));
+                    }
+                    let mut dev = $create_method(&trans);
+                    NorFlash::erase(&mut dev, 0, $size).unwrap();
+                    $destroy_method(dev);
+                }
+
+                #[test]
+                fn cannot_erase_unaligned() {
+                    let mut dev = $create_method(&[]);
+                    assert_invalid_input_data!(NorFlash::erase(&mut dev, 1, $size));
+                    assert_invalid_input_data!(NorFlash::erase(&mut dev, 0, $size - 1));
+                    assert_invalid_input_data!(NorFlash::erase(&mut dev, $size, 0));
+                    $destroy_method(dev);
+                }
+            }
+        }
+    };
+}
+
+sram_test!(
+    for_ds3232,
+    new_ds3232,
+    destroy_ds3232,
+    ds3232_sram_read,
+    ds3232_sram_write,
+    236,
+    4
+);
+sram_test!(
+    for_ds3234,
+    new_ds3234,
+    destroy_ds3234,
+    ds3234_sram_read,
+    ds3234_sram_write,
+    256,
+    64
+);
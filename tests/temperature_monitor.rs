@@ -0,0 +1,220 @@
+use ds323x::{
+    Ds323x, PersistentTemperatureMonitor, TemperatureEvent as Ev, TemperatureMonitor,
+    TemperatureStats, TemperatureThresholds,
+};
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3234, new_ds3231, new_ds3234, FakeI2c, Register,
+    DEVICE_ADDRESS as DEV_ADDR,
+};
+
+const THRESHOLDS: TemperatureThresholds = TemperatureThresholds {
+    low: 0.0,
+    high: 50.0,
+    hysteresis: 2.0,
+};
+
+fn record(monitor: &mut TemperatureMonitor, temperatures: &[f32]) {
+    for temperature in temperatures {
+        monitor.record(*temperature);
+    }
+}
+
+fn events(monitor: &mut TemperatureMonitor) -> Vec<Ev> {
+    core::iter::from_fn(|| monitor.poll_event()).collect()
+}
+
+#[test]
+fn no_stats_without_samples() {
+    let monitor = TemperatureMonitor::new(None);
+    assert_eq!(None, monitor.stats());
+}
+
+#[test]
+fn tracks_stats() {
+    let mut monitor = TemperatureMonitor::new(None);
+    record(&mut monitor, &[20.0, 22.5, -3.25, 25.0]);
+    let expected = TemperatureStats {
+        min: -3.25,
+        max: 25.0,
+        average: 16.0625,
+        samples: 4,
+    };
+    assert_eq!(Some(expected), monitor.stats());
+}
+
+#[test]
+fn can_reset_stats() {
+    let mut monitor = TemperatureMonitor::new(None);
+    record(&mut monitor, &[20.0, 22.5]);
+    monitor.reset();
+    assert_eq!(None, monitor.stats());
+    record(&mut monitor, &[30.0]);
+    assert_eq!(30.0, monitor.stats().unwrap().min);
+}
+
+#[test]
+fn can_sample_ds3231() {
+    let trans = [I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::TEMP_MSB],
+        vec![0b0001_1001, 0b0100_0000],
+    )];
+    let mut dev = new_ds3231(&trans);
+    let mut monitor = TemperatureMonitor::new(None);
+    assert_eq!(25.25, monitor.sample(&mut dev).unwrap());
+    assert_eq!(25.25, monitor.stats().unwrap().max);
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn can_sample_ds3234() {
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(
+            vec![Register::TEMP_MSB, 0, 0],
+            vec![Register::TEMP_MSB, 0b1111_1111, 0b0000_0000],
+        ),
+        SpiTrans::transaction_end(),
+    ];
+    let mut dev = new_ds3234(&trans);
+    let mut monitor = TemperatureMonitor::new(None);
+    assert_eq!(-1.0, monitor.sample(&mut dev).unwrap());
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn no_events_without_thresholds() {
+    let mut monitor = TemperatureMonitor::new(None);
+    record(&mut monitor, &[-40.0, 100.0]);
+    assert_eq!(Vec::<Ev>::new(), events(&mut monitor));
+}
+
+#[test]
+fn reports_high_threshold_with_hysteresis() {
+    let mut monitor = TemperatureMonitor::new(Some(THRESHOLDS));
+    record(&mut monitor, &[49.75, 50.0, 51.0, 48.5, 50.25, 48.0, 47.0]);
+    assert_eq!(
+        vec![Ev::AboveHigh(50.0), Ev::HighCleared(48.0)],
+        events(&mut monitor)
+    );
+}
+
+#[test]
+fn reports_low_threshold_with_hysteresis() {
+    let mut monitor = TemperatureMonitor::new(Some(THRESHOLDS));
+    record(&mut monitor, &[0.25, 0.0, 1.75, -0.5, 2.0]);
+    assert_eq!(
+        vec![Ev::BelowLow(0.0), Ev::LowCleared(2.0)],
+        events(&mut monitor)
+    );
+}
+
+#[test]
+fn reports_jump_across_both_thresholds() {
+    let mut monitor = TemperatureMonitor::new(Some(THRESHOLDS));
+    record(&mut monitor, &[60.0, -5.0]);
+    let expected = vec![
+        Ev::AboveHigh(60.0),
+        Ev::HighCleared(-5.0),
+        Ev::BelowLow(-5.0),
+    ];
+    assert_eq!(expected, events(&mut monitor));
+}
+
+#[test]
+fn drops_oldest_events_when_queue_is_full() {
+    let mut monitor = TemperatureMonitor::new(Some(THRESHOLDS));
+    record(&mut monitor, &[60.0, 20.0, 61.0, 20.0, 62.0]);
+    let expected = vec![
+        Ev::HighCleared(20.0),
+        Ev::AboveHigh(61.0),
+        Ev::HighCleared(20.0),
+        Ev::AboveHigh(62.0),
+    ];
+    assert_eq!(expected, events(&mut monitor));
+}
+
+#[test]
+fn new_thresholds_are_checked_from_scratch() {
+    let mut monitor = TemperatureMonitor::new(Some(THRESHOLDS));
+    record(&mut monitor, &[55.0]);
+    monitor.set_thresholds(Some(TemperatureThresholds {
+        high: 70.0,
+        ..THRESHOLDS
+    }));
+    record(&mut monitor, &[55.0, 70.0]);
+    assert_eq!(
+        vec![Ev::AboveHigh(55.0), Ev::AboveHigh(70.0)],
+        events(&mut monitor)
+    );
+}
+
+#[test]
+fn can_save_and_restore_stats() {
+    let mut dev = Ds323x::new_ds3232(FakeI2c::default());
+    let mut monitor = TemperatureMonitor::new(None);
+    record(&mut monitor, &[20.0, 30.0]);
+    monitor.save(&mut dev, 100).unwrap();
+    record(&mut monitor, &[40.0]);
+    monitor.save(&mut dev, 100).unwrap();
+
+    let mut restored = TemperatureMonitor::new(None);
+    assert!(restored.restore(&mut dev, 100).unwrap());
+    assert_eq!(monitor.stats(), restored.stats());
+    record(&mut restored, &[50.0]);
+    assert_eq!(4, restored.stats().unwrap().samples);
+}
+
+#[test]
+fn keeps_stats_if_none_saved() {
+    let mut dev = Ds323x::new_ds3232(FakeI2c::default());
+    let mut monitor = TemperatureMonitor::new(None);
+    record(&mut monitor, &[20.0]);
+    assert!(!monitor.restore(&mut dev, 100).unwrap());
+    assert_eq!(1, monitor.stats().unwrap().samples);
+}
+
+#[test]
+fn persistent_monitor_saves_each_sample() {
+    let mut i2c = FakeI2c::default();
+    i2c.registers[usize::from(Register::TEMP_MSB)] = 25;
+    let mut dev = Ds323x::new_ds3232(i2c);
+    let mut monitor = PersistentTemperatureMonitor::new(&mut dev, 100, None).unwrap();
+    assert_eq!(None, monitor.stats());
+    assert_eq!(25.0, monitor.sample().unwrap());
+    monitor.record(30.0).unwrap();
+    let stats = monitor.stats();
+
+    let mut restored = TemperatureMonitor::new(None);
+    assert!(restored.restore(&mut dev, 100).unwrap());
+    assert_eq!(stats, restored.stats());
+}
+
+#[test]
+fn persistent_monitor_restores_stats() {
+    let mut dev = Ds323x::new_ds3232(FakeI2c::default());
+    let mut monitor = TemperatureMonitor::new(None);
+    record(&mut monitor, &[20.0, 30.0]);
+    monitor.save(&mut dev, 100).unwrap();
+
+    let mut restored = PersistentTemperatureMonitor::new(dev, 100, Some(THRESHOLDS)).unwrap();
+    assert_eq!(monitor.stats(), restored.stats());
+    restored.record(60.0).unwrap();
+    assert_eq!(Some(Ev::AboveHigh(60.0)), restored.poll_event());
+    assert_eq!(3, restored.stats().unwrap().samples);
+    let _dev = restored.destroy();
+}
+
+#[test]
+fn persistent_monitor_saves_reset() {
+    let mut dev = Ds323x::new_ds3232(FakeI2c::default());
+    let mut monitor = PersistentTemperatureMonitor::new(&mut dev, 100, None).unwrap();
+    monitor.record(20.0).unwrap();
+    monitor.reset().unwrap();
+    let monitor = PersistentTemperatureMonitor::new(&mut dev, 100, None).unwrap();
+    assert_eq!(None, monitor.stats());
+}
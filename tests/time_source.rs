@@ -0,0 +1,143 @@
+#![cfg(feature = "embedded-sdmmc")]
+
+use ds323x::Ds323xTimeSource;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+use embedded_sdmmc::{TimeSource, Timestamp};
+
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3234, new_ds3231, new_ds3234, BitFlags as BF, Register,
+    DEVICE_ADDRESS as DEV_ADDR,
+};
+
+// 2018-08-13 23:59:58
+const DATETIME: [u8; 7] = [
+    0b0101_1000,
+    0b0101_1001,
+    0b0010_0011,
+    0b0000_0010,
+    0b0001_0011,
+    0b0000_1000,
+    0b0001_1000,
+];
+
+fn timestamp(year: u16, month: u8, day: u8, hours: u8, minutes: u8, seconds: u8) -> Timestamp {
+    Timestamp::from_calendar(year, month, day, hours, minutes, seconds).unwrap()
+}
+
+fn fat_epoch() -> Timestamp {
+    timestamp(1980, 1, 1, 0, 0, 0)
+}
+
+fn i2c_read_datetime(registers: [u8; 7]) -> I2cTrans {
+    I2cTrans::write_read(DEV_ADDR, vec![Register::SECONDS], registers.to_vec())
+}
+
+fn i2c_read_status(status: u8) -> I2cTrans {
+    I2cTrans::write_read(DEV_ADDR, vec![Register::STATUS], vec![status])
+}
+
+#[test]
+fn can_get_timestamp() {
+    let trans = [i2c_read_datetime(DATETIME), i2c_read_status(0)];
+    let time_source = Ds323xTimeSource::new(new_ds3231(&trans));
+    assert_eq!(
+        timestamp(2018, 8, 13, 23, 59, 58),
+        time_source.get_timestamp()
+    );
+    destroy_ds3231(time_source.into_inner());
+}
+
+#[test]
+fn can_get_timestamp_ds3234() {
+    let mut read = vec![Register::SECONDS];
+    read.extend_from_slice(&DATETIME);
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![Register::SECONDS, 0, 0, 0, 0, 0, 0, 0], read),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![Register::STATUS, 0], vec![Register::STATUS, 0]),
+        SpiTrans::transaction_end(),
+    ];
+    let time_source = Ds323xTimeSource::new(new_ds3234(&trans));
+    assert_eq!(
+        timestamp(2018, 8, 13, 23, 59, 58),
+        time_source.get_timestamp()
+    );
+    destroy_ds3234(time_source.into_inner());
+}
+
+#[test]
+fn fallback_is_fat_epoch_per_default() {
+    let time_source = Ds323xTimeSource::new(new_ds3231(&[]));
+    assert_eq!(fat_epoch(), time_source.fallback());
+    destroy_ds3231(time_source.into_inner());
+}
+
+#[test]
+fn returns_fallback_if_oscillator_has_been_stopped() {
+    let trans = [i2c_read_datetime(DATETIME), i2c_read_status(BF::OSC_STOP)];
+    let time_source = Ds323xTimeSource::new(new_ds3231(&trans));
+    assert_eq!(fat_epoch(), time_source.get_timestamp());
+    destroy_ds3231(time_source.into_inner());
+}
+
+#[test]
+fn returns_fallback_if_invalid_device_state() {
+    let mut registers = DATETIME;
+    registers[Register::MONTH as usize] = 0x13;
+    let trans = [i2c_read_datetime(registers)];
+    let time_source = Ds323xTimeSource::new(new_ds3231(&trans));
+    assert_eq!(fat_epoch(), time_source.get_timestamp());
+    destroy_ds3231(time_source.into_inner());
+}
+
+#[test]
+fn returns_fallback_on_communication_error() {
+    let trans = [i2c_read_datetime([0; 7]).with_error(ErrorKind::Other)];
+    let time_source = Ds323xTimeSource::new(new_ds3231(&trans));
+    assert_eq!(fat_epoch(), time_source.get_timestamp());
+    destroy_ds3231(time_source.into_inner());
+}
+
+#[test]
+fn returns_fallback_if_year_not_representable() {
+    let mut registers = DATETIME;
+    // 2118 with the century bit set
+    registers[Register::MONTH as usize] |= 0b1000_0000;
+    let trans = [i2c_read_datetime(registers), i2c_read_status(0)];
+    let time_source = Ds323xTimeSource::new(new_ds3231(&trans));
+    assert_eq!(fat_epoch(), time_source.get_timestamp());
+    destroy_ds3231(time_source.into_inner());
+}
+
+#[test]
+fn returns_fallback_while_locked() {
+    let time_source = Ds323xTimeSource::new(new_ds3231(&[]));
+    let timestamp = time_source.lock(|_| time_source.get_timestamp());
+    assert_eq!(fat_epoch(), timestamp);
+    destroy_ds3231(time_source.into_inner());
+}
+
+#[test]
+fn can_set_fallback() {
+    let trans = [i2c_read_datetime(DATETIME), i2c_read_status(BF::OSC_STOP)];
+    let mut time_source = Ds323xTimeSource::new(new_ds3231(&trans));
+    let fallback = timestamp(2024, 1, 1, 0, 0, 0);
+    time_source.set_fallback(fallback);
+    assert_eq!(fallback, time_source.fallback());
+    assert_eq!(fallback, time_source.get_timestamp());
+    destroy_ds3231(time_source.into_inner());
+}
+
+#[test]
+fn can_access_driver() {
+    let trans = [i2c_read_status(BF::OSC_STOP)];
+    let mut time_source = Ds323xTimeSource::new(new_ds3231(&trans));
+    assert!(time_source.lock(|dev| dev.has_been_stopped()).unwrap());
+    time_source.get_mut().set_century_base(2100).unwrap();
+    destroy_ds3231(time_source.into_inner());
+}
@@ -0,0 +1,410 @@
+use ds323x::{
+    ic, interface::I2cInterface, DateTimeAccess, Datelike, Ds323x, Error, Hours, NaiveDate,
+    NaiveDateTime, NaiveTime, Rtcc, TimeValidity,
+};
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, new_ds3231, new_ds3232, new_ds3234, BitFlags,
+    FakeI2c, Register, DEVICE_ADDRESS as DEV_ADDR,
+};
+
+const OFFSET: u8 = 20;
+const MARKER_SIZE: usize = 9;
+const DS3232_SRAM_START: usize = 0x14;
+const DS3234_SRAM_ADDRESS: u8 = 0x18;
+const DS3234_SRAM_DATA: u8 = 0x19;
+
+type Dev = Ds323x<I2cInterface<FakeI2c>, ic::DS3232>;
+
+fn new() -> Dev {
+    Ds323x::new_ds3232(FakeI2c::default())
+}
+
+fn with_registers(dev: Dev, f: impl FnOnce(&mut [u8; 256])) -> Dev {
+    let mut i2c = dev.destroy_ds3232();
+    f(&mut i2c.registers);
+    Ds323x::new_ds3232(i2c)
+}
+
+fn new_with_marker() -> Dev {
+    let mut dev = new();
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    dev
+}
+
+fn reopen_with_marker(dev: Dev) -> Dev {
+    let mut dev = with_registers(dev, |_| ());
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    dev
+}
+
+fn datetime(day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 5, day)
+        .unwrap()
+        .and_hms_opt(12, 30, 15)
+        .unwrap()
+}
+
+// CRC-8/SMBUS
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if (crc & 0x80) != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn marker(dt: NaiveDateTime) -> Vec<u8> {
+    let mut data = vec![0x54, 0x07, 0xE8, 5, dt.day() as u8, 12, 30, 15];
+    data.push(crc8(&data));
+    data
+}
+
+#[test]
+fn implausible_date_after_power_up() {
+    let mut dev = new();
+    assert_eq!(TimeValidity::ImplausibleDate, dev.time_validity().unwrap());
+}
+
+#[test]
+fn valid_after_setting_time() {
+    let mut dev = new();
+    dev.set_datetime(&datetime(17)).unwrap();
+    assert_eq!(TimeValidity::Valid, dev.time_validity().unwrap());
+}
+
+#[test]
+fn never_set_with_marker_after_power_up() {
+    let mut dev = new_with_marker();
+    assert_eq!(TimeValidity::NeverSet, dev.time_validity().unwrap());
+}
+
+#[test]
+fn marker_is_written_when_setting_time() {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(17)).unwrap();
+    let start = DS3232_SRAM_START + usize::from(OFFSET);
+    let i2c = dev.destroy_ds3232();
+    assert_eq!(
+        marker(datetime(17)),
+        i2c.registers[start..start + MARKER_SIZE].to_vec()
+    );
+}
+
+#[test]
+fn valid_with_marker_after_setting_time() {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(17)).unwrap();
+    let mut dev = reopen_with_marker(dev);
+    assert_eq!(TimeValidity::Valid, dev.time_validity().unwrap());
+}
+
+#[test]
+fn never_set_with_corrupted_marker() {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(17)).unwrap();
+    let mut dev = with_registers(dev, |registers| {
+        registers[DS3232_SRAM_START + usize::from(OFFSET) + 4] ^= 0x01;
+    });
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    assert_eq!(TimeValidity::NeverSet, dev.time_validity().unwrap());
+}
+
+#[test]
+fn oscillator_stopped() {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(17)).unwrap();
+    let mut dev = with_registers(dev, |registers| {
+        registers[usize::from(Register::STATUS)] |= BitFlags::OSC_STOP;
+        registers[usize::from(Register::CONTROL)] |= BitFlags::EOSC;
+    });
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    assert_eq!(
+        TimeValidity::OscillatorStopped,
+        dev.time_validity().unwrap()
+    );
+}
+
+#[test]
+fn oscillator_disabled() {
+    let mut dev = new();
+    dev.set_datetime(&datetime(17)).unwrap();
+    let mut dev = with_registers(dev, |registers| {
+        registers[usize::from(Register::CONTROL)] |= BitFlags::EOSC;
+    });
+    assert_eq!(
+        TimeValidity::OscillatorDisabled,
+        dev.time_validity().unwrap()
+    );
+}
+
+#[test]
+fn implausible_date_if_invalid_registers() {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(17)).unwrap();
+    let mut dev = with_registers(dev, |registers| {
+        registers[usize::from(Register::MONTH)] = 0x13;
+    });
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    assert_eq!(TimeValidity::ImplausibleDate, dev.time_validity().unwrap());
+}
+
+#[test]
+fn implausible_date_if_earlier_than_marker() {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(17)).unwrap();
+    dev.disable_time_set_marker();
+    dev.set_datetime(&datetime(16)).unwrap();
+    let mut dev = reopen_with_marker(dev);
+    assert_eq!(TimeValidity::ImplausibleDate, dev.time_validity().unwrap());
+}
+
+// Set the date and time, move them backwards with `set` and check that
+// the marker has been refreshed.
+fn valid_after_moving_backwards(set: impl FnOnce(&mut Dev)) {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(17)).unwrap();
+    set(&mut dev);
+    let mut dev = reopen_with_marker(dev);
+    assert_eq!(TimeValidity::Valid, dev.time_validity().unwrap());
+}
+
+#[test]
+fn marker_is_written_when_setting_date() {
+    valid_after_moving_backwards(|dev| {
+        dev.set_date(&NaiveDate::from_ymd_opt(2024, 5, 16).unwrap())
+            .unwrap()
+    });
+}
+
+#[test]
+fn marker_is_written_when_setting_time_only() {
+    valid_after_moving_backwards(|dev| {
+        dev.set_time(&NaiveTime::from_hms_opt(8, 0, 0).unwrap())
+            .unwrap()
+    });
+}
+
+#[test]
+fn marker_is_written_when_setting_year() {
+    valid_after_moving_backwards(|dev| dev.set_year(2023).unwrap());
+}
+
+#[test]
+fn marker_is_written_when_setting_month() {
+    valid_after_moving_backwards(|dev| dev.set_month(4).unwrap());
+}
+
+#[test]
+fn marker_is_written_when_setting_day() {
+    valid_after_moving_backwards(|dev| dev.set_day(3).unwrap());
+}
+
+#[test]
+fn marker_is_written_when_setting_hours() {
+    valid_after_moving_backwards(|dev| dev.set_hours(Hours::H24(1)).unwrap());
+}
+
+#[test]
+fn marker_is_written_when_setting_minutes() {
+    valid_after_moving_backwards(|dev| dev.set_minutes(0).unwrap());
+}
+
+#[test]
+fn marker_is_written_when_setting_seconds() {
+    valid_after_moving_backwards(|dev| dev.set_seconds(0).unwrap());
+}
+
+#[test]
+fn marker_contains_date_and_time_after_setting_day() {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(17)).unwrap();
+    dev.set_day(3).unwrap();
+    let start = DS3232_SRAM_START + usize::from(OFFSET);
+    let i2c = dev.destroy_ds3232();
+    assert_eq!(
+        marker(datetime(3)),
+        i2c.registers[start..start + MARKER_SIZE].to_vec()
+    );
+}
+
+#[test]
+fn marker_is_kept_while_date_is_not_valid() {
+    let mut dev = new_with_marker();
+    dev.set_datetime(&datetime(31)).unwrap();
+    // the 31st of April does not exist
+    dev.set_month(4).unwrap();
+    let start = DS3232_SRAM_START + usize::from(OFFSET);
+    let i2c = dev.destroy_ds3232();
+    assert_eq!(
+        marker(datetime(31)),
+        i2c.registers[start..start + MARKER_SIZE].to_vec()
+    );
+}
+
+#[test]
+fn does_not_mark_time_valid_before_setting_time() {
+    let mut dev = with_registers(new(), |registers| {
+        registers[usize::from(Register::STATUS)] = BitFlags::OSC_STOP;
+    });
+    assert!(!dev.mark_time_valid().unwrap());
+    assert!(dev.has_been_stopped().unwrap());
+}
+
+#[test]
+fn can_mark_time_valid_after_setting_time() {
+    let mut dev = with_registers(new(), |registers| {
+        registers[usize::from(Register::STATUS)] = BitFlags::OSC_STOP;
+    });
+    dev.set_datetime(&datetime(17)).unwrap();
+    assert_eq!(
+        TimeValidity::OscillatorStopped,
+        dev.time_validity().unwrap()
+    );
+    assert!(dev.mark_time_valid().unwrap());
+    assert_eq!(TimeValidity::Valid, dev.time_validity().unwrap());
+}
+
+#[test]
+fn cannot_enable_marker_not_fitting_in_sram() {
+    let mut dev = new();
+    assert_invalid_input_data!(dev.enable_time_set_marker(228));
+    dev.enable_time_set_marker(227).unwrap();
+}
+
+#[test]
+fn ds3231_oscillator_stopped() {
+    let trans = [I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::STATUS],
+        vec![BitFlags::OSC_STOP],
+    )];
+    let mut dev = new_ds3231(&trans);
+    assert_eq!(
+        TimeValidity::OscillatorStopped,
+        dev.time_validity().unwrap()
+    );
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn ds3234_writes_marker_when_setting_time() {
+    let mut payload = vec![Register::SECONDS + 0x80];
+    payload.extend_from_slice(&[0x15, 0x30, 0x12, 0x06, 0x17, 0x05, 0x24]);
+    let mut data = vec![DS3234_SRAM_DATA + 0x80];
+    data.extend(marker(datetime(17)));
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(payload),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![DS3234_SRAM_ADDRESS + 0x80, OFFSET]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(data),
+        SpiTrans::transaction_end(),
+    ];
+    let mut dev = new_ds3234(&trans);
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    dev.set_datetime(&datetime(17)).unwrap();
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn verifies_marker() {
+    let start = DS3232_SRAM_START as u8 + OFFSET;
+    let mut data = vec![start];
+    data.extend(marker(datetime(17)));
+    let mut read_back = marker(datetime(17));
+    read_back[3] ^= 0x01;
+    let trans = [
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::SECONDS, 0x15, 0x30, 0x12, 0x06, 0x17, 0x05, 0x24],
+        ),
+        I2cTrans::write(DEV_ADDR, data),
+        I2cTrans::write_read(DEV_ADDR, vec![start], read_back),
+    ];
+    let mut dev = new_ds3232(&trans);
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    dev.enable_write_verification();
+    match dev.set_datetime(&datetime(17)) {
+        Err(Error::VerificationFailed { register }) => assert_eq!(start + 3, register),
+        _ => panic!("VerificationFailed error not returned."),
+    }
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn ds3234_verifies_marker() {
+    let mut payload = vec![Register::SECONDS + 0x80];
+    payload.extend_from_slice(&[0x15, 0x30, 0x12, 0x06, 0x17, 0x05, 0x24]);
+    let mut data = vec![DS3234_SRAM_DATA + 0x80];
+    data.extend(marker(datetime(17)));
+    let mut read = vec![DS3234_SRAM_DATA];
+    read.extend_from_slice(&[0; MARKER_SIZE]);
+    let mut read_back = vec![0];
+    read_back.extend(marker(datetime(17)));
+    read_back[5] ^= 0x01;
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(payload),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![DS3234_SRAM_ADDRESS + 0x80, OFFSET]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(data),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![DS3234_SRAM_ADDRESS + 0x80, OFFSET]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(read, read_back),
+        SpiTrans::transaction_end(),
+    ];
+    let mut dev = new_ds3234(&trans);
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    dev.enable_write_verification();
+    match dev.set_datetime(&datetime(17)) {
+        Err(Error::SramVerificationFailed { offset }) => assert_eq!(OFFSET + 4, offset),
+        _ => panic!("SramVerificationFailed error not returned."),
+    }
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn ds3234_never_set_with_empty_marker() {
+    let mut read = vec![DS3234_SRAM_DATA];
+    read.extend_from_slice(&[0; MARKER_SIZE]);
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![DS3234_SRAM_ADDRESS + 0x80, OFFSET]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(read.clone(), read),
+        SpiTrans::transaction_end(),
+    ];
+    let mut dev = new_ds3234(&trans);
+    dev.enable_time_set_marker(OFFSET).unwrap();
+    assert_eq!(TimeValidity::NeverSet, dev.time_validity().unwrap());
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn ds3234_cannot_enable_marker_not_fitting_in_sram() {
+    let mut dev = new_ds3234(&[]);
+    assert_invalid_input_data!(dev.enable_time_set_marker(248));
+    dev.enable_time_set_marker(247).unwrap();
+    destroy_ds3234(dev);
+}
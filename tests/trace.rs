@@ -0,0 +1,379 @@
+use ds323x::{
+    trace::{TraceBus, TraceEntry, TraceOp, TracingI2c, TracingSpi, TRACE_DATA_SIZE},
+    Ds323x,
+};
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::{
+    i2c::{Mock as I2cMock, Transaction as I2cTrans},
+    spi::{Mock as SpiMock, Transaction as SpiTrans},
+};
+
+#[allow(unused)]
+mod common;
+use self::common::{
+    BitFlags as BF, FakeI2c, Register, CONTROL_POR_VALUE, DEVICE_ADDRESS as DEV_ADDR,
+    DS3234_SRAM_DATA,
+};
+
+fn entries<'a>(entries: impl Iterator<Item = &'a TraceEntry>) -> Vec<(TraceOp, u8, Vec<u8>)> {
+    entries
+        .map(|entry| (entry.op(), entry.register(), entry.data().to_vec()))
+        .collect()
+}
+
+#[test]
+fn records_i2c_register_operations() {
+    let bus: TracingI2c<_, 8> = TracingI2c::with_trace_buffer(FakeI2c::default());
+    let mut dev = Ds323x::new_ds3232(bus);
+    dev.disable().unwrap();
+    dev.set_aging_offset(-2).unwrap();
+    dev.aging_offset().unwrap();
+    dev.write_sram(1, &[1, 2, 3]).unwrap();
+    let bus = dev.destroy_ds3232();
+    assert_eq!(
+        vec![
+            (
+                TraceOp::Write,
+                Register::CONTROL,
+                vec![CONTROL_POR_VALUE | BF::EOSC]
+            ),
+            (TraceOp::Write, Register::AGING_OFFSET, vec![0xFE]),
+            (TraceOp::Read, Register::AGING_OFFSET, vec![0xFE]),
+            (TraceOp::Write, 0x15, vec![1, 2, 3]),
+        ],
+        entries(bus.trace().iter())
+    );
+    let entry = bus.trace().iter().next().unwrap();
+    assert_eq!(TraceBus::I2c, entry.bus());
+    assert_eq!("CONTROL", entry.register_name());
+    assert!(entry.is_ok());
+    assert_eq!("SRAM", bus.trace().iter().last().unwrap().register_name());
+}
+
+#[test]
+fn decodes_register_bits() {
+    let bus: TracingI2c<_, 2> = TracingI2c::with_trace_buffer(FakeI2c::default());
+    let mut dev = Ds323x::new_ds3231(bus);
+    dev.enable_alarm1_interrupts().unwrap();
+    dev.disable_32khz_output().unwrap();
+    let bus = dev.destroy_ds3231();
+    let lines: Vec<String> = bus.trace().iter().map(|entry| entry.to_string()).collect();
+    assert_eq!(
+        vec![
+            "write CONTROL (0x0E): 1D [RS2 RS1 INTCN A1IE]",
+            "write STATUS (0x0F): 83 [OSF A2F A1F]",
+        ],
+        lines
+    );
+}
+
+#[test]
+fn displays_multiple_registers() {
+    let bus: TracingI2c<_, 1> = TracingI2c::with_trace_buffer(FakeI2c::default());
+    let mut dev = Ds323x::new_ds3231(bus);
+    dev.current_config().unwrap();
+    let bus = dev.destroy_ds3231();
+    assert_eq!(
+        "read CONTROL (0x0E): 00 [] 00 [] 00",
+        bus.trace().iter().next().unwrap().to_string()
+    );
+}
+
+#[test]
+fn keeps_last_operations() {
+    let bus: TracingI2c<_, 2> = TracingI2c::with_trace_buffer(FakeI2c::default());
+    let mut dev = Ds323x::new_ds3231(bus);
+    for offset in 1..=3 {
+        dev.set_aging_offset(offset).unwrap();
+    }
+    let mut bus = dev.destroy_ds3231();
+    assert_eq!(2, bus.trace().len());
+    assert_eq!(1, bus.trace().dropped());
+    assert_eq!(
+        vec![
+            (TraceOp::Write, Register::AGING_OFFSET, vec![2]),
+            (TraceOp::Write, Register::AGING_OFFSET, vec![3]),
+        ],
+        entries(bus.trace().iter())
+    );
+    bus.trace_mut().clear();
+    assert!(bus.trace().is_empty());
+}
+
+#[test]
+fn does_not_record_without_buffer() {
+    let mut dev = Ds323x::new_ds3231(TracingI2c::new(FakeI2c::default()));
+    dev.set_aging_offset(1).unwrap();
+    let bus = dev.destroy_ds3231();
+    assert!(bus.trace().is_empty());
+    assert_eq!(1, bus.trace().dropped());
+}
+
+#[test]
+fn truncates_long_data() {
+    let bus: TracingI2c<_, 1> = TracingI2c::with_trace_buffer(FakeI2c::default());
+    let mut dev = Ds323x::new_ds3232(bus);
+    let data = [0xAA; 20];
+    dev.write_sram(0, &data).unwrap();
+    let bus = dev.destroy_ds3232();
+    let entry = bus.trace().iter().next().unwrap();
+    assert!(entry.is_truncated());
+    assert_eq!(20, entry.data_len());
+    assert_eq!(&data[..TRACE_DATA_SIZE], entry.data());
+    assert!(entry.to_string().ends_with(" ... (20 bytes)"));
+}
+
+#[test]
+fn records_failed_operations() {
+    let trans =
+        [I2cTrans::write(DEV_ADDR, vec![Register::AGING_OFFSET, 1]).with_error(ErrorKind::Bus)];
+    let bus: TracingI2c<_, 1> = TracingI2c::with_trace_buffer(I2cMock::new(&trans));
+    let mut dev = Ds323x::new_ds3231(bus);
+    dev.set_aging_offset(1).unwrap_err();
+    let bus = dev.destroy_ds3231();
+    let entry = bus.trace().iter().next().unwrap();
+    assert!(!entry.is_ok());
+    assert!(entry.to_string().ends_with(" failed"));
+    bus.into_inner().done();
+}
+
+#[test]
+fn records_spi_register_operations() {
+    let trans = [
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![Register::STATUS, 0], vec![0, 0x88]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![Register::TEMP_CONV + 0x80, BF::TEMP_CONV_BAT]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![0x18 + 0x80, 4]),
+        SpiTrans::transaction_end(),
+        SpiTrans::transaction_start(),
+        SpiTrans::write_vec(vec![0x19]),
+        SpiTrans::read_vec(vec![7, 8]),
+        SpiTrans::transaction_end(),
+    ];
+    let bus: TracingSpi<_, 4> = TracingSpi::with_trace_buffer(SpiMock::new(&trans));
+    let mut dev = Ds323x::new_ds3234(bus);
+    dev.has_been_stopped().unwrap();
+    dev.disable_temperature_conversions_on_battery().unwrap();
+    let mut data = [0; 2];
+    dev.read_sram(4, &mut data).unwrap();
+    let bus = dev.destroy_ds3234();
+    assert_eq!(
+        vec![
+            (TraceOp::Read, Register::STATUS, vec![0x88]),
+            (TraceOp::Write, Register::TEMP_CONV, vec![BF::TEMP_CONV_BAT]),
+            (TraceOp::Write, 0x18, vec![4]),
+            (TraceOp::Read, 0x19, vec![7, 8]),
+        ],
+        entries(bus.trace().iter())
+    );
+    let lines: Vec<String> = bus.trace().iter().map(|entry| entry.to_string()).collect();
+    assert_eq!(
+        vec![
+            "read STATUS (0x0F): 88 [OSF EN32KHZ]",
+            "write TEMP_CONV (0x13): 01 [BB_TD]",
+            "write SRAM_ADDRESS (0x18): 04",
+            "read SRAM_DATA (0x19): 07 08",
+        ],
+        lines
+    );
+    bus.into_inner().done();
+}
+
+mod replay {
+    use super::*;
+    use ds323x::{trace::TraceBuffer, DateTimeAccess};
+
+    // Convert the recorded operations into I²C mock transactions if they
+    // were recorded completely on an I²C bus.
+    fn to_i2c_transactions<const N: usize>(
+        trace: &TraceBuffer<N>,
+        address: u8,
+    ) -> Option<Vec<I2cTrans>> {
+        if !trace.is_complete() || trace.iter().any(|entry| entry.bus() != TraceBus::I2c) {
+            return None;
+        }
+        let transactions = trace.iter().map(|entry| {
+            let mut data = vec![entry.register()];
+            match entry.op() {
+                TraceOp::Read => I2cTrans::write_read(address, data, entry.data().to_vec()),
+                TraceOp::Write => {
+                    data.extend_from_slice(entry.data());
+                    I2cTrans::write(address, data)
+                }
+            }
+        });
+        Some(transactions.collect())
+    }
+
+    // Convert the recorded operations into SPI mock transactions in the form
+    // used by the driver if they were recorded completely on an SPI device.
+    fn to_spi_transactions<const N: usize>(trace: &TraceBuffer<N>) -> Option<Vec<SpiTrans<u8>>> {
+        if !trace.is_complete() || trace.iter().any(|entry| entry.bus() != TraceBus::Spi) {
+            return None;
+        }
+        let mut transactions = Vec::new();
+        for entry in trace.iter() {
+            let register = entry.register();
+            let data = entry.data().to_vec();
+            transactions.push(SpiTrans::transaction_start());
+            match entry.op() {
+                // the SRAM data is sent separately from the address
+                TraceOp::Read if register == DS3234_SRAM_DATA => {
+                    transactions.push(SpiTrans::write_vec(vec![register]));
+                    transactions.push(SpiTrans::read_vec(data));
+                }
+                TraceOp::Write if register == DS3234_SRAM_DATA => {
+                    transactions.push(SpiTrans::write_vec(vec![register + 0x80]));
+                    transactions.push(SpiTrans::write_vec(data));
+                }
+                TraceOp::Read => {
+                    let mut sent = vec![0; data.len() + 1];
+                    sent[0] = register;
+                    let mut received = vec![0];
+                    received.extend(data);
+                    transactions.push(SpiTrans::transfer_in_place(sent, received));
+                }
+                TraceOp::Write => {
+                    let mut sent = vec![register + 0x80];
+                    sent.extend(data);
+                    transactions.push(SpiTrans::write_vec(sent));
+                }
+            }
+            transactions.push(SpiTrans::transaction_end());
+        }
+        Some(transactions)
+    }
+
+    // Driver operations used to record a trace and to replay it.
+    fn session<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> I2C {
+        let mut dev = Ds323x::new_ds3231(i2c);
+        dev.use_int_sqw_output_as_interrupt().unwrap();
+        dev.set_aging_offset(3).unwrap();
+        dev.datetime().unwrap();
+        dev.clear_alarm1_matched_flag().unwrap();
+        dev.destroy_ds3231()
+    }
+
+    #[test]
+    fn can_replay_i2c_trace() {
+        let mut fake = FakeI2c::default();
+        // 2024-02-29 23:59:58
+        fake.registers[..7].copy_from_slice(&[0x58, 0x59, 0x23, 5, 0x29, 0x02, 0x24]);
+        let bus: TracingI2c<_, 16> = TracingI2c::with_trace_buffer(fake);
+        let bus = session(bus);
+        assert!(bus.trace().is_complete());
+        let trans = to_i2c_transactions(bus.trace(), DEV_ADDR).unwrap();
+        assert_eq!(4, trans.len());
+        session(I2cMock::new(&trans)).done();
+    }
+
+    fn spi_session<SPI: embedded_hal::spi::SpiDevice>(spi: SPI) -> SPI {
+        let mut dev = Ds323x::new_ds3234(spi);
+        dev.has_been_stopped().unwrap();
+        dev.set_aging_offset(5).unwrap();
+        dev.write_sram(4, &[1, 2]).unwrap();
+        let mut data = [0; 2];
+        dev.read_sram(4, &mut data).unwrap();
+        assert_eq!([1, 2], data);
+        dev.destroy_ds3234()
+    }
+
+    #[test]
+    fn can_replay_spi_trace() {
+        let trans = [
+            SpiTrans::transaction_start(),
+            SpiTrans::transfer_in_place(vec![Register::STATUS, 0], vec![0, 0x88]),
+            SpiTrans::transaction_end(),
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![Register::AGING_OFFSET + 0x80, 5]),
+            SpiTrans::transaction_end(),
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![0x18 + 0x80, 4]),
+            SpiTrans::transaction_end(),
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![0x19 + 0x80]),
+            SpiTrans::write_vec(vec![1, 2]),
+            SpiTrans::transaction_end(),
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![0x18 + 0x80, 4]),
+            SpiTrans::transaction_end(),
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![0x19]),
+            SpiTrans::read_vec(vec![1, 2]),
+            SpiTrans::transaction_end(),
+        ];
+        let bus: TracingSpi<_, 8> = TracingSpi::with_trace_buffer(SpiMock::new(&trans));
+        let bus = spi_session(bus);
+        assert!(to_i2c_transactions(bus.trace(), DEV_ADDR).is_none());
+        let replay = to_spi_transactions(bus.trace()).unwrap();
+        bus.into_inner().done();
+        spi_session(SpiMock::new(&replay)).done();
+    }
+
+    #[test]
+    fn cannot_replay_incomplete_trace() {
+        let bus: TracingI2c<_, 1> = TracingI2c::with_trace_buffer(FakeI2c::default());
+        let mut dev = Ds323x::new_ds3232(bus);
+        dev.write_sram(0, &[0xAA; 20]).unwrap();
+        let bus = dev.destroy_ds3232();
+        assert!(!bus.trace().is_complete());
+        assert!(to_i2c_transactions(bus.trace(), DEV_ADDR).is_none());
+
+        let mut dev = Ds323x::new_ds3232(bus);
+        dev.set_aging_offset(1).unwrap();
+        let bus = dev.destroy_ds3232();
+        assert_eq!(1, bus.trace().dropped());
+        assert!(to_i2c_transactions(bus.trace(), DEV_ADDR).is_none());
+    }
+
+    #[test]
+    fn cannot_replay_failed_operations() {
+        let trans = [
+            I2cTrans::write(DEV_ADDR, vec![Register::AGING_OFFSET, 1]).with_error(ErrorKind::Bus)
+        ];
+        let bus: TracingI2c<_, 1> = TracingI2c::with_trace_buffer(I2cMock::new(&trans));
+        let mut dev = Ds323x::new_ds3231(bus);
+        dev.set_aging_offset(1).unwrap_err();
+        let bus = dev.destroy_ds3231();
+        assert!(to_i2c_transactions(bus.trace(), DEV_ADDR).is_none());
+        bus.into_inner().done();
+    }
+}
+
+#[cfg(feature = "log")]
+mod logging {
+    use super::*;
+    use std::sync::Mutex;
+
+    static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Logger;
+
+    impl log::Log for Logger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            LINES.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Logger = Logger;
+
+    #[test]
+    fn logs_operations() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Debug);
+        let mut dev = Ds323x::new_ds3231(TracingI2c::new(FakeI2c::default()));
+        dev.set_aging_offset(0x15).unwrap();
+        let expected = "ds323x: write AGING_OFFSET (0x10): 15";
+        assert!(LINES.lock().unwrap().iter().any(|line| line == expected));
+    }
+}
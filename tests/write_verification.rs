@@ -0,0 +1,246 @@
+use ds323x::{Alarm2Matching, DayAlarm2, Error, ErrorClass, HourMode, Hours};
+use embedded_hal_mock::eh1::{i2c::Transaction as I2cTrans, spi::Transaction as SpiTrans};
+
+#[allow(unused)]
+mod common;
+use self::common::{
+    destroy_ds3231, destroy_ds3232, destroy_ds3234, i2c_read, i2c_read_data, i2c_write, new_ds3231,
+    new_ds3232, new_ds3234, spi_write, BitFlags as BF, Register, CONTROL_POR_VALUE,
+    DEVICE_ADDRESS as DEV_ADDR, DS3231_POR_STATUS, DS3232_SRAM_START, DS3234_SRAM_ADDRESS,
+    DS3234_SRAM_DATA,
+};
+
+fn assert_verification_failed<T>(result: Result<T, Error<impl core::fmt::Debug>>, expected: u8) {
+    match result {
+        Err(Error::VerificationFailed { register }) => assert_eq!(expected, register),
+        _ => panic!("VerificationFailed error not returned."),
+    }
+}
+
+#[test]
+fn does_not_read_back_by_default() {
+    let trans = [i2c_write(Register::AGING_OFFSET, 5)].concat();
+    let mut dev = new_ds3231(&trans);
+    dev.set_aging_offset(5).unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn verifies_control_ignoring_conversion_bit() {
+    let control = CONTROL_POR_VALUE | BF::EOSC;
+    let trans = [
+        i2c_write(Register::CONTROL, control),
+        i2c_read(Register::CONTROL, control | BF::TEMP_CONV),
+        // the conversion bit is not kept in the cache
+        i2c_write(Register::CONTROL, control | BF::BBSQW),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    dev.disable().unwrap();
+    dev.disable_write_verification();
+    dev.enable_square_wave().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn control_mismatch_updates_cache() {
+    let control = CONTROL_POR_VALUE | BF::EOSC;
+    let trans = [
+        i2c_write(Register::CONTROL, control),
+        i2c_read(Register::CONTROL, CONTROL_POR_VALUE),
+        // the cache contains the value read back
+        i2c_write(Register::CONTROL, CONTROL_POR_VALUE | BF::BBSQW),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    assert_verification_failed(dev.disable(), Register::CONTROL);
+    dev.disable_write_verification();
+    dev.enable_square_wave().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn verifies_status_ignoring_flags() {
+    let status = DS3231_POR_STATUS & !BF::EN32KHZ;
+    let written = status | BF::ALARM2F | BF::ALARM1F;
+    let read_back = status | BF::BUSY | BF::ALARM1F;
+    let trans = [
+        i2c_write(Register::STATUS, written),
+        i2c_read(Register::STATUS, read_back & !BF::OSC_STOP),
+        // the cache does not contain the flags and the cleared
+        // oscillator-stop flag is kept
+        i2c_write(
+            Register::STATUS,
+            status & !BF::OSC_STOP | BF::EN32KHZ | BF::ALARM2F | BF::ALARM1F,
+        ),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    dev.disable_32khz_output().unwrap();
+    dev.disable_write_verification();
+    dev.enable_32khz_output().unwrap();
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn status_mismatch_fails() {
+    let status = DS3231_POR_STATUS & !BF::EN32KHZ;
+    let trans = [
+        i2c_write(Register::STATUS, status | BF::ALARM2F | BF::ALARM1F),
+        i2c_read(Register::STATUS, DS3231_POR_STATUS),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    assert_verification_failed(dev.disable_32khz_output(), Register::STATUS);
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn verifies_aging_offset() {
+    let trans = [
+        i2c_write(Register::AGING_OFFSET, 5),
+        i2c_read(Register::AGING_OFFSET, 5),
+        i2c_write(Register::AGING_OFFSET, 6),
+        i2c_read(Register::AGING_OFFSET, 5),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    dev.set_aging_offset(5).unwrap();
+    assert_verification_failed(dev.set_aging_offset(6), Register::AGING_OFFSET);
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn verifies_alarm_registers() {
+    let alarm = DayAlarm2 {
+        day: 1,
+        hour: Hours::H24(2),
+        minute: 3,
+    };
+    let trans = [
+        vec![I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::ALARM2_MINUTES, 3, 2, 1],
+        )],
+        i2c_read_data(Register::ALARM2_MINUTES, vec![3, 2, 1]),
+        vec![I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::ALARM2_MINUTES, 3, 2, 1],
+        )],
+        i2c_read_data(Register::ALARM2_MINUTES, vec![3, 0, 1]),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    dev.set_alarm2_day(alarm, Alarm2Matching::AllMatch).unwrap();
+    assert_verification_failed(
+        dev.set_alarm2_day(alarm, Alarm2Matching::AllMatch),
+        Register::ALARM2_HOURS,
+    );
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn verifies_ds3232_sram() {
+    let data: Vec<u8> = (0..20).collect();
+    let mut wrong = data.clone();
+    wrong[18] = 0;
+    let mut trans = Vec::new();
+    for read_back in [&data, &wrong] {
+        trans.extend(
+            [
+                vec![I2cTrans::transaction_start(DEV_ADDR)],
+                vec![I2cTrans::write(DEV_ADDR, vec![DS3232_SRAM_START + 2])],
+                vec![I2cTrans::write(DEV_ADDR, data.clone())],
+                vec![I2cTrans::transaction_end(DEV_ADDR)],
+                i2c_read_data(DS3232_SRAM_START + 2, read_back[..16].to_vec()),
+                i2c_read_data(DS3232_SRAM_START + 18, read_back[16..].to_vec()),
+            ]
+            .concat(),
+        );
+    }
+    let mut dev = new_ds3232(&trans);
+    dev.enable_write_verification();
+    dev.write_sram(2, &data).unwrap();
+    assert_verification_failed(dev.write_sram(2, &data), DS3232_SRAM_START + 20);
+    destroy_ds3232(dev);
+}
+
+#[test]
+fn verifies_ds3234_sram() {
+    let mut trans = Vec::new();
+    for read_back in [vec![1, 2], vec![1, 3]] {
+        trans.extend(spi_write(DS3234_SRAM_ADDRESS, 7));
+        trans.extend([
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![DS3234_SRAM_DATA + 0x80]),
+            SpiTrans::write_vec(vec![1, 2]),
+            SpiTrans::transaction_end(),
+        ]);
+        trans.extend(spi_write(DS3234_SRAM_ADDRESS, 7));
+        trans.extend([
+            SpiTrans::transaction_start(),
+            SpiTrans::write_vec(vec![DS3234_SRAM_DATA]),
+            SpiTrans::read_vec(read_back),
+            SpiTrans::transaction_end(),
+        ]);
+    }
+    let mut dev = new_ds3234(&trans);
+    dev.enable_write_verification();
+    dev.write_sram(7, &[1, 2]).unwrap();
+    match dev.write_sram(7, &[1, 2]) {
+        Err(Error::SramVerificationFailed { offset }) => assert_eq!(8, offset),
+        _ => panic!("SramVerificationFailed error not returned."),
+    }
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn verifies_alarm_hours_when_setting_hour_mode() {
+    let trans = [
+        i2c_read_data(Register::SECONDS, vec![0, 0, 0x13]),
+        i2c_write(Register::HOURS, 0x61),
+        i2c_read(Register::ALARM1_HOURS, 0x13),
+        i2c_write(Register::ALARM1_HOURS, 0x61),
+        i2c_read(Register::ALARM1_HOURS, 0x41),
+    ]
+    .concat();
+    let mut dev = new_ds3231(&trans);
+    dev.enable_write_verification();
+    assert_verification_failed(dev.set_hour_mode(HourMode::H12), Register::ALARM1_HOURS);
+    destroy_ds3231(dev);
+}
+
+#[test]
+fn verifies_ds3234_control() {
+    let control = CONTROL_POR_VALUE | BF::BBSQW;
+    let mut trans = spi_write(Register::CONTROL, control);
+    trans.extend([
+        SpiTrans::transaction_start(),
+        SpiTrans::transfer_in_place(vec![Register::CONTROL, 0], vec![Register::CONTROL, control]),
+        SpiTrans::transaction_end(),
+    ]);
+    let mut dev = new_ds3234(&trans);
+    dev.enable_write_verification();
+    dev.enable_square_wave().unwrap();
+    destroy_ds3234(dev);
+}
+
+#[test]
+fn can_display_and_classify_verification_error() {
+    let error: Error<embedded_hal::i2c::ErrorKind> = Error::VerificationFailed { register: 0x0E };
+    assert_eq!("verification failed in register 0x0E", error.to_string());
+    assert_eq!(ErrorClass::VerificationFailed, error.i2c_class());
+    assert!(!error.i2c_class().is_retryable(true));
+    let error: Error<embedded_hal::i2c::ErrorKind> = Error::SramVerificationFailed { offset: 0x20 };
+    assert_eq!(
+        "verification failed at SRAM address 0x20",
+        error.to_string()
+    );
+    assert_eq!(ErrorClass::VerificationFailed, error.i2c_class());
+}